@@ -0,0 +1,45 @@
+//! Matching records against the filters
+use alto_logger::Filters;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+fn filters(c: &mut Criterion) {
+    let mut group = c.benchmark_group("filters");
+    group.throughput(Throughput::Elements(1));
+
+    let cases = [
+        ("default level", "info"),
+        (
+            "modules",
+            "warn,hyper=off,h2=off,tokio=info,my_app=debug,my_app::db=trace",
+        ),
+        (
+            "ranges",
+            "error,my_app=warn..error,my_app::net=only:debug,my_app::db=trace..info",
+        ),
+    ];
+
+    for (name, directives) in cases {
+        let filters = Filters::parse(directives);
+        let targets = ["my_app::db::pool", "my_app::net", "hyper::client", "other"];
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for target in targets {
+                    let metadata = log::Metadata::builder()
+                        .level(log::Level::Debug)
+                        .target(target)
+                        .build();
+                    black_box(filters.is_enabled(black_box(&metadata)));
+                }
+            })
+        });
+    }
+
+    group.bench_function("parse", |b| {
+        b.iter(|| Filters::parse(black_box(cases[2].1)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, filters);
+criterion_main!(benches);
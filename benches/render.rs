@@ -0,0 +1,52 @@
+//! Rendering a record, without writing it anywhere
+use alto_logger::{
+    layout::{self, FixedClock},
+    options::{StyleConfig, TimeConfig},
+    Options,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::time::{Duration, Instant, SystemTime};
+
+fn render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    group.throughput(Throughput::Elements(1));
+
+    // a stopped clock, so every iteration renders the same timestamp
+    let clock = FixedClock(
+        Instant::now(),
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_571_999),
+    );
+
+    for (name, style) in [
+        ("single line", StyleConfig::SingleLine),
+        ("multi line", StyleConfig::MultiLine),
+    ] {
+        let options = Options::default()
+            .with_style(style)
+            .with_time(TimeConfig::unix_timestamp());
+
+        let mut out = String::new();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                out.clear();
+                layout::render(
+                    &options,
+                    &log::Record::builder()
+                        .level(log::Level::Info)
+                        .target("bench::render")
+                        .args(format_args!("hello world {}", 42))
+                        .key_values(&[("user", "alice"), ("attempt", "3")])
+                        .build(),
+                    &clock,
+                    &mut out,
+                );
+                black_box(&out);
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
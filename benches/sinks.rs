@@ -0,0 +1,44 @@
+//! Writing records to files, and fanning them out to several loggers
+use alto_logger::{options::StyleConfig, FileLogger, MultiLogger};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use log::Log as _;
+
+fn record(f: impl FnOnce(&log::Record<'_>)) {
+    f(&log::Record::builder()
+        .level(log::Level::Info)
+        .target("bench::sinks")
+        .args(format_args!("hello world {}", 42))
+        .build())
+}
+
+fn sinks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sinks");
+    group.throughput(Throughput::Elements(1));
+
+    let path = std::env::temp_dir().join(format!("alto_logger_bench_{}.log", std::process::id()));
+    let logger = FileLogger::truncate(StyleConfig::SingleLine, &path).unwrap();
+    logger.reload_handle().set("trace");
+    group.bench_function("file append", |b| {
+        b.iter(|| record(|record| logger.log(record)))
+    });
+    drop(logger);
+    let _ = std::fs::remove_file(path);
+
+    for count in [1, 4] {
+        let mut multi = MultiLogger::new();
+        for _ in 0..count {
+            let logger = FileLogger::new(StyleConfig::SingleLine, std::io::sink());
+            logger.reload_handle().set("trace");
+            multi = multi.with(logger);
+        }
+
+        group.bench_function(format!("multi logger x{}", count), |b| {
+            b.iter(|| record(|record| multi.log(record)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, sinks);
+criterion_main!(benches);
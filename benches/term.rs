@@ -0,0 +1,46 @@
+//! Throughput of the terminal logger
+//!
+//! This writes to stdout, so it is best run as `cargo bench --bench term | grep -v "^TRACE"`
+use alto_logger::{options::Detection, Options, TermLogger};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use log::Log as _;
+use std::io::Write as _;
+
+fn record(f: impl FnOnce(&log::Record<'_>)) {
+    f(&log::Record::builder()
+        .level(log::Level::Trace)
+        .target("bench::term")
+        .args(format_args!("hello world {}", 42))
+        .build())
+}
+
+fn term_logger(c: &mut Criterion) {
+    std::env::set_var("RUST_LOG", "trace");
+
+    let mut group = c.benchmark_group("term");
+    group.throughput(Throughput::Elements(1));
+
+    // what the logger used to do: a new writer and buffer for each record
+    group.bench_function("writer per record", |b| {
+        b.iter(|| {
+            record(|record| {
+                let writer = termcolor::BufferWriter::stdout(termcolor::ColorChoice::Never);
+                let mut buffer = writer.buffer();
+                let _ = writeln!(buffer, "{:<5} [{}]", record.level(), record.target());
+                let _ = writeln!(buffer, "⤷ {}", record.args());
+                let _ = writer.print(&buffer);
+            })
+        })
+    });
+
+    let logger =
+        TermLogger::new(Options::default().with_color_detection(Detection::Never)).unwrap();
+    group.bench_function("term logger", |b| {
+        b.iter(|| record(|record| logger.log(record)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, term_logger);
+criterion_main!(benches);
@@ -24,7 +24,7 @@ fn main() {
     let file = alto_logger::FileLogger::timestamp(opts, "out.log").unwrap(); // will make a out-$unix_timestamp.log
 
     // this will be the path to the file
-    let name = file.file_name();
+    let _name = file.file_name();
 
     // combine them so it logs to both
     let logger = alto_logger::MultiLogger::new().with(term).with(file);
@@ -0,0 +1,84 @@
+use crate::{config::EffectiveConfig, Error};
+
+/// Initialize the logger, and then log a banner describing the process and the installed logger
+///
+/// The banner is logged at the `INFO` level with the `alto_logger` target, e.g:
+///
+/// `app=my_app version=1.2.0 pid=1234 host=my-host level=trace outputs="term (console: ansi) (filters: info); file=out.log (filters: debug)"`
+///
+/// The [`banner!`](macro.banner.html) macro passes the name and version of the crate it is used in.
+///
+/// ***Note*** The banner is subject to the filters of the logger,
+/// so `alto_logger=info` has to be enabled for it to be shown
+///
+/// ```rust
+/// # use alto_logger::TermLogger;
+/// alto_logger::init_with_banner(TermLogger::default(), "my_app", "1.2.0").unwrap();
+/// ```
+pub fn init_with_banner(
+    logger: impl log::Log + 'static,
+    app: &str,
+    version: &str,
+) -> Result<(), Error> {
+    crate::init(logger)?;
+    if let Some(config) = crate::current_config() {
+        log::logger().log(
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target("alto_logger")
+                .module_path_static(Some(module_path!()))
+                .args(format_args!("{}", message(&config, app, version)))
+                .build(),
+        );
+    }
+    Ok(())
+}
+
+fn message(config: &EffectiveConfig, app: &str, version: &str) -> String {
+    let outputs = config
+        .loggers
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    format!(
+        "app={} version={} pid={} host={} level={} outputs={:?}",
+        app,
+        version,
+        std::process::id(),
+        hostname(),
+        config.max_level.as_str().to_ascii_lowercase(),
+        outputs,
+    )
+}
+
+fn hostname() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn banner() {
+        let config = EffectiveConfig {
+            loggers: vec![],
+            max_level: log::LevelFilter::Info,
+        };
+        let message = message(&config, "my_app", "1.2.0");
+
+        let pid = format!("pid={} ", std::process::id());
+        let (start, end) = message.split_once(&pid).unwrap();
+        assert_eq!(start, "app=my_app version=1.2.0 ");
+        assert!(end.starts_with("host="));
+        assert!(end.ends_with(r#" level=info outputs="""#), "{}", end);
+    }
+}
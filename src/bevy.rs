@@ -0,0 +1,78 @@
+use crate::{FrameLogger, Options, TermLogger};
+use bevy_app::{App, Last, Plugin};
+
+/// A Bevy plugin which installs a [`TermLogger`](struct.TermLogger.html), in place of Bevy's own `LogPlugin`
+///
+/// The logger is installed when the plugin is added, so records logged by the plugins added after it are written.
+/// With a frame budget, records are held by a [`FrameLogger`](struct.FrameLogger.html) until the end of each frame,
+/// in the `Last` schedule.
+///
+/// ***Note*** This requires the `bevy` feature. Bevy's `LogPlugin` has to be disabled, as it installs its own logger
+///
+/// ```rust
+/// # use alto_logger::{LogPlugin, Options};
+/// # use bevy_app::App;
+/// // with bevy: `DefaultPlugins.build().disable::<bevy::log::LogPlugin>()`
+/// let mut app = App::new();
+/// app.add_plugins(LogPlugin::new(Options::default()).with_frame_budget(Some(256)));
+///
+/// log::info!("held until the end of the frame");
+/// app.update();
+/// ```
+pub struct LogPlugin {
+    options: Options,
+    frame_budget: Option<usize>,
+}
+
+impl LogPlugin {
+    /// Create a plugin which installs a terminal logger with these `Options`, and their filters
+    pub fn new(options: impl Into<Options>) -> Self {
+        Self {
+            options: options.into(),
+            frame_budget: None,
+        }
+    }
+
+    /// Hold at most this many records per frame, writing them at the end of the frame
+    ///
+    /// See [`FrameLogger`](struct.FrameLogger.html)
+    ///
+    /// ***Note*** Defaults to `None`, so records are written when they are logged
+    pub const fn with_frame_budget(mut self, budget: Option<usize>) -> Self {
+        self.frame_budget = budget;
+        self
+    }
+}
+
+impl Default for LogPlugin {
+    fn default() -> Self {
+        Self::new(Options::default())
+    }
+}
+
+impl Plugin for LogPlugin {
+    fn build(&self, app: &mut App) {
+        let logger = match TermLogger::new(self.options.clone()) {
+            Ok(logger) => logger,
+            Err(err) => {
+                return log::warn!(target: "alto_logger", "cannot create the logger: {}", err)
+            }
+        };
+
+        let result = match self.frame_budget {
+            Some(budget) => {
+                let logger = FrameLogger::new(logger, budget);
+                let frames = logger.handle();
+                logger.init().map(|()| {
+                    app.add_systems(Last, move || frames.end_frame());
+                })
+            }
+            None => logger.init(),
+        };
+
+        // e.g. Bevy's `LogPlugin` was added first, which is what logs this
+        if let Err(err) = result {
+            log::warn!(target: "alto_logger", "cannot install the logger: {}", err)
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    filters::SharedFilters, options::Options, sync::Mutex, ConsoleProbe, FileLogger, MultiLogger,
+    StderrLogger, TermLogger,
+};
+use std::{any::Any, path::PathBuf};
+
+crate::sync::static_lock! {
+    static CURRENT: Mutex<Option<Vec<LoggerConfig>>> = Mutex::new(None);
+}
+
+/// Get the configuration of the installed logger
+///
+/// This returns `None` if a logger hasn't been installed with [`init`](fn.init.html)
+///
+/// ```rust,no_run
+/// alto_logger::init_term_logger().unwrap();
+/// if let Some(config) = alto_logger::current_config() {
+///     log::info!("logging initialized: {}", config);
+/// }
+/// ```
+pub fn current_config() -> Option<EffectiveConfig> {
+    let loggers = crate::sync::lock(&CURRENT).clone()?;
+    Some(EffectiveConfig {
+        loggers,
+        max_level: log::max_level(),
+    })
+}
+
+pub(crate) fn set_current(loggers: Vec<LoggerConfig>) {
+    crate::sync::lock(&CURRENT).replace(loggers);
+}
+
+/// A description of the installed logger
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct EffectiveConfig {
+    /// The loggers that were installed.
+    ///
+    /// The loggers in a `MultiLogger` are listed individually
+    pub loggers: Vec<LoggerConfig>,
+    /// The maximum level that the `log` crate will allow
+    pub max_level: log::LevelFilter,
+}
+
+impl std::fmt::Display for EffectiveConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "level={}", self.max_level.as_str().to_ascii_lowercase())?;
+        for logger in &self.loggers {
+            write!(f, ", {}", logger)?;
+        }
+        Ok(())
+    }
+}
+
+/// A description of a single logger
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct LoggerConfig {
+    /// What kind of logger this is
+    pub kind: LoggerKind,
+    /// The options the logger was created with, if its one of ours
+    pub options: Option<Options>,
+    /// The filter directives the logger is using, if its one of ours
+    pub filters: Option<String>,
+    /// How colors are written, if its a terminal logger
+    pub console: Option<ConsoleProbe>,
+    // the filters of the logger, for the maximum level of the `log` crate
+    pub(crate) shared: Option<SharedFilters>,
+    // the filters of the `MultiLogger`s the logger is in, which also have to enable its records
+    pub(crate) parents: Vec<SharedFilters>,
+}
+
+impl LoggerConfig {
+    /// The most verbose level this logger can write a record at
+    pub(crate) fn max_level(&self) -> log::LevelFilter {
+        // a logger that isn't one of ours could enable anything
+        let level = self
+            .shared
+            .as_ref()
+            .map_or(log::LevelFilter::Trace, SharedFilters::max_level);
+        self.parents
+            .iter()
+            .map(SharedFilters::max_level)
+            .fold(level, Ord::min)
+    }
+}
+
+impl std::fmt::Display for LoggerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            LoggerKind::Term => write!(f, "term")?,
+            LoggerKind::Stderr => write!(f, "stderr")?,
+            LoggerKind::File(Some(path)) => write!(f, "file={}", path.display())?,
+            LoggerKind::File(None) => write!(f, "file")?,
+            LoggerKind::Other(name) => write!(f, "other={}", name)?,
+        }
+        if let Some(console) = &self.console {
+            write!(f, " (console: {})", console)?;
+        }
+        if let Some(filters) = &self.filters {
+            write!(f, " (filters: {})", filters)?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of logger
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LoggerKind {
+    /// A [`TermLogger`](struct.TermLogger.html)
+    Term,
+    /// A [`StderrLogger`](struct.StderrLogger.html)
+    Stderr,
+    /// A [`FileLogger`](struct.FileLogger.html), with the path of the file if known
+    File(Option<PathBuf>),
+    /// A logger not provided by this crate, with its type name
+    Other(&'static str),
+}
+
+/// Describe a logger, looking through a `MultiLogger` to find its loggers
+pub(crate) fn describe<L: log::Log + 'static>(logger: &L) -> Vec<LoggerConfig> {
+    let any = logger as &dyn Any;
+
+    if let Some(logger) = any.downcast_ref::<TermLogger>() {
+        return vec![logger.describe()];
+    }
+
+    if let Some(logger) = any.downcast_ref::<StderrLogger>() {
+        return vec![logger.describe()];
+    }
+
+    if let Some(logger) = any.downcast_ref::<FileLogger<std::fs::File>>() {
+        return vec![logger.describe()];
+    }
+
+    if let Some(logger) = any.downcast_ref::<MultiLogger>() {
+        return logger.describe();
+    }
+
+    vec![LoggerConfig {
+        kind: LoggerKind::Other(std::any::type_name::<L>()),
+        options: None,
+        filters: None,
+        console: None,
+        shared: None,
+        parents: Vec::new(),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChannelLogger, Filters};
+
+    #[test]
+    fn describe_loggers() {
+        let term =
+            TermLogger::new(Options::default().with_filters(Filters::parse("debug"))).unwrap();
+        let [config] = &describe(&term)[..] else {
+            panic!("one logger")
+        };
+        assert_eq!(config.kind, LoggerKind::Term);
+        assert_eq!(config.filters.as_deref(), Some("debug"));
+        assert!(config.options.is_some() && config.console.is_some());
+
+        let [config] = &describe(&StderrLogger::default())[..] else {
+            panic!("one logger")
+        };
+        assert_eq!(config.kind, LoggerKind::Stderr);
+
+        let (channel, _rx) = ChannelLogger::capture();
+        let [config] = &describe(&channel)[..] else {
+            panic!("one logger")
+        };
+        assert_eq!(
+            config.kind,
+            LoggerKind::Other("alto_logger::loggers::channel::ChannelLogger")
+        );
+        assert!(config.options.is_none() && config.filters.is_none() && config.shared.is_none());
+    }
+
+    #[test]
+    fn describe_multi() {
+        let path =
+            std::env::temp_dir().join(format!("alto_logger_config_{}.log", std::process::id()));
+        let file = FileLogger::append(
+            Options::default().with_filters(Filters::parse("info")),
+            &path,
+        )
+        .unwrap();
+
+        let multi = MultiLogger::new().with(StderrLogger::default()).with(file);
+        let configs = describe(&multi);
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].kind, LoggerKind::Stderr);
+        assert_eq!(configs[1].kind, LoggerKind::File(Some(path.clone())));
+        assert!(configs[1].console.is_none());
+
+        let config = EffectiveConfig {
+            loggers: configs[1..].to_vec(),
+            max_level: log::LevelFilter::Info,
+        };
+        assert_eq!(
+            config.to_string(),
+            format!("level=info, file={} (filters: info)", path.display())
+        );
+
+        drop(multi);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn display() {
+        let config = |kind| LoggerConfig {
+            kind,
+            options: None,
+            filters: None,
+            console: None,
+            shared: None,
+            parents: Vec::new(),
+        };
+        let effective = EffectiveConfig {
+            loggers: vec![
+                LoggerConfig {
+                    filters: Some(String::from("warn,my_app=trace")),
+                    ..config(LoggerKind::Term)
+                },
+                config(LoggerKind::File(None)),
+                config(LoggerKind::Other("my_app::Logger")),
+            ],
+            max_level: log::LevelFilter::Trace,
+        };
+        assert_eq!(
+            effective.to_string(),
+            "level=trace, term (filters: warn,my_app=trace), file, other=my_app::Logger"
+        );
+
+        let empty = EffectiveConfig {
+            loggers: vec![],
+            max_level: log::LevelFilter::Off,
+        };
+        assert_eq!(empty.to_string(), "level=off");
+    }
+}
@@ -0,0 +1,195 @@
+//! UTC calendar math, so file names and timestamps don't require the `time` crate
+
+/// A UTC date and time, with a second resolution
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct DateTime {
+    pub(crate) year: i64,
+    pub(crate) month: u8,
+    pub(crate) day: u8,
+    pub(crate) hour: u8,
+    pub(crate) minute: u8,
+    pub(crate) second: u8,
+}
+
+impl DateTime {
+    /// The current UTC date and time
+    pub(crate) fn now() -> Self {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self::from_unix(secs)
+    }
+
+    /// Convert seconds since the UNIX epoch to a date and time
+    pub(crate) fn from_unix(secs: u64) -> Self {
+        let days = (secs / 86400) as i64;
+        let rem = secs % 86400;
+
+        // from Howard Hinnant's `civil_from_days`
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = yoe + era * 400 + (month <= 2) as i64;
+
+        Self {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as u8,
+            minute: ((rem % 3600) / 60) as u8,
+            second: (rem % 60) as u8,
+        }
+    }
+
+    /// Convert this date and time to seconds since the UNIX epoch, or `None` if it's before the epoch
+    pub(crate) fn to_unix(self) -> Option<u64> {
+        // from Howard Hinnant's `days_from_civil`
+        let year = self.year - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (self.month as i64 + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+
+        let secs =
+            days * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64;
+        u64::try_from(secs).ok()
+    }
+
+    /// Parse `YYYY-MM-DDTHH:MM:SS`, with an optional fraction of a second, and a `Z` or `+HH:MM` offset
+    ///
+    /// This returns the time since the UNIX epoch, in UTC
+    pub(crate) fn parse_rfc3339(input: &str) -> Option<std::time::Duration> {
+        fn num<T: std::str::FromStr>(s: &str) -> Option<T> {
+            match !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+                true => s.parse().ok(),
+                false => None,
+            }
+        }
+
+        let (date, time) = input.split_once(['T', 't', ' '])?;
+        let mut parts = date.splitn(3, '-');
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if time.len() < 8 || !time.is_char_boundary(8) {
+            return None;
+        }
+        let (clock, rest) = time.split_at(8);
+        let mut parts = clock.splitn(3, ':');
+        let (hour, minute, second) = (parts.next()?, parts.next()?, parts.next()?);
+
+        let this = Self {
+            year: num(year)?,
+            month: num(month).filter(|m| (1..=12).contains(m))?,
+            day: num(day).filter(|d| (1..=31).contains(d))?,
+            hour: num(hour).filter(|h| *h < 24)?,
+            minute: num(minute).filter(|m| *m < 60)?,
+            second: num(second).filter(|s| *s < 61)?,
+        };
+
+        let (fraction, offset) = match rest.strip_prefix('.') {
+            Some(rest) => {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+            None => ("", rest),
+        };
+        let nanos = match fraction {
+            "" => 0,
+            f => format!("{:0<9}", &f[..f.len().min(9)]).parse().ok()?,
+        };
+
+        let offset = match offset {
+            "Z" | "z" => 0,
+            offset => {
+                let (sign, offset) = match offset.split_at_checked(1)? {
+                    ("+", rest) => (1, rest),
+                    ("-", rest) => (-1, rest),
+                    _ => return None,
+                };
+                let (hours, minutes) = offset.split_once(':')?;
+                sign * (num::<i64>(hours)? * 3600 + num::<i64>(minutes)? * 60)
+            }
+        };
+
+        let secs = (this.to_unix()? as i64).checked_sub(offset)?;
+        Some(std::time::Duration::new(u64::try_from(secs).ok()?, nanos))
+    }
+
+    /// `YYYY-MM-DD`
+    pub(crate) fn date(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+
+    /// `YYYY-MM-DDTHH:MM:SSZ`
+    pub(crate) fn rfc3339(&self) -> String {
+        format!(
+            "{}T{:02}:{:02}:{:02}Z",
+            self.date(),
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_unix() {
+        let cases = [
+            (0, "1970-01-01 00:00:00"),
+            (951_782_400, "2000-02-29 00:00:00"),
+            (1_587_429_534, "2020-04-21 00:38:54"),
+            (1_714_521_599, "2024-04-30 23:59:59"),
+            (1_714_521_600, "2024-05-01 00:00:00"),
+            (4_102_444_800, "2100-01-01 00:00:00"),
+        ];
+
+        for (secs, expected) in cases {
+            let date_time = DateTime::from_unix(secs);
+            assert_eq!(date_time.to_string(), expected);
+            assert_eq!(date_time.to_unix(), Some(secs));
+        }
+    }
+
+    #[test]
+    fn parse_rfc3339() {
+        let parse = |s| DateTime::parse_rfc3339(s).map(|d| (d.as_secs(), d.subsec_millis()));
+        assert_eq!(parse("2024-05-01T00:00:00Z"), Some((1_714_521_600, 0)));
+        assert_eq!(parse("2024-05-01T00:00:00.25Z"), Some((1_714_521_600, 250)));
+        assert_eq!(parse("2024-05-01T02:00:00+02:00"), Some((1_714_521_600, 0)));
+        assert_eq!(parse("2024-04-30T23:00:00-01:00"), Some((1_714_521_600, 0)));
+        assert_eq!(parse("2024-05-01 00:00:00Z"), Some((1_714_521_600, 0)));
+
+        for invalid in [
+            "",
+            "2024-05-01",
+            "2024-13-01T00:00:00Z",
+            "2024-05-01T00:00:00",
+            "1969-12-31T23:59:59Z",
+        ] {
+            assert_eq!(parse(invalid), None, "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,566 @@
+use crate::{
+    config::LoggerConfig,
+    record::OwnedRecord,
+    sync::{Mutex, RwLock},
+    Error,
+};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// How many records are kept before a logger is installed
+const PREINIT_CAPACITY: usize = 1024;
+
+crate::sync::static_lock! {
+    static DISPATCHER: Dispatcher = Dispatcher::new();
+}
+
+thread_local! {
+    // a logger used instead of the installed logger, for records from this thread
+    static OVERRIDE: RefCell<Option<Arc<dyn log::Log>>> = const { RefCell::new(None) };
+}
+
+/// Install a temporary logger which buffers records until a logger is installed with [`init`](fn.init.html)
+///
+/// When a logger is installed, the buffered records are replayed through it (and its filters).
+/// This lets records logged during early startup, e.g. while parsing the configuration for the logger, be kept.
+///
+/// ***Note*** Only the most recent 1024 records are kept.
+/// They keep the time they were logged at, rather than the time they are replayed.
+///
+/// ```rust,no_run
+/// alto_logger::preinit().unwrap();
+/// log::info!("this is logged before the logger is configured");
+/// alto_logger::init_term_logger().unwrap();
+/// ```
+pub fn preinit() -> Result<(), Error> {
+    let dispatcher: &'static Dispatcher = &DISPATCHER;
+    log::set_logger(dispatcher).map_err(Error::SetLogger)?;
+    DISPATCHER.registered.store(true, Ordering::SeqCst);
+    DISPATCHER.start_buffering();
+    update_max_level();
+    Ok(())
+}
+
+/// Install the logger, replaying any records buffered since [`preinit`](fn.preinit.html)
+///
+/// The maximum level of the `log` crate is set from the filters in its `config`
+pub(crate) fn install(logger: Box<dyn log::Log>, config: &[LoggerConfig]) -> Result<(), Error> {
+    // if a logger was already installed, this'll produce the error for it
+    if DISPATCHER.current().is_some() || !DISPATCHER.registered.load(Ordering::SeqCst) {
+        let dispatcher: &'static Dispatcher = &DISPATCHER;
+        log::set_logger(dispatcher).map_err(Error::SetLogger)?;
+        DISPATCHER.registered.store(true, Ordering::SeqCst);
+    }
+    DISPATCHER.set_filters(config);
+    DISPATCHER.set(logger);
+    update_max_level();
+    Ok(())
+}
+
+/// Set the maximum level of the `log` crate to the most verbose level the installed logger's filters enable
+///
+/// This lets the `log` macros reject records cheaply, before they reach the logger.
+/// The level is `TRACE` when the filters aren't known (e.g. a logger not provided by this crate),
+/// while records are buffered by [`preinit`](fn.preinit.html), or while [`with_logger`](fn.with_logger.html) is used.
+pub(crate) fn update_max_level() {
+    // the installed logger isn't part of the loom models, and its locks would outlive a model
+    if cfg!(loom) {
+        return;
+    }
+    if DISPATCHER.registered.load(Ordering::SeqCst) {
+        log::set_max_level(DISPATCHER.max_level());
+    }
+}
+
+/// Flush and remove the installed logger
+///
+/// Dropping the logger lets it finish cleanly, e.g. a [`FileLogger`](struct.FileLogger.html) writes its footer.
+/// Records logged afterwards are discarded, until another logger is installed with [`init`](fn.init.html).
+///
+/// ```rust,no_run
+/// # use alto_logger::{FileLogger, Options};
+/// let options = Options::default().with_file_footer(true);
+/// FileLogger::append(options, "out.log").unwrap().init().unwrap();
+/// // ..
+/// alto_logger::shutdown();
+/// ```
+pub fn shutdown() {
+    let previous = crate::sync::write(&DISPATCHER.logger).take();
+    DISPATCHER.set_filters(&[]);
+    update_max_level();
+    if let Some(previous) = previous {
+        previous.flush();
+    }
+}
+
+/// Suppress every record below `ERROR`, until this is called again with `false`
+///
+/// This applies to every logger, including overrides from [`with_logger`](fn.with_logger.html),
+/// e.g. so a subcommand that writes JSON to stdout isn't interleaved with records.
+///
+/// ```rust,no_run
+/// # let json = true;
+/// alto_logger::init_term_logger().unwrap();
+/// alto_logger::set_quiet(json);
+/// ```
+pub fn set_quiet(quiet: bool) {
+    DISPATCHER.quiet.store(quiet, Ordering::SeqCst);
+    update_max_level();
+}
+
+/// Whether records below `ERROR` are suppressed by [`set_quiet`](fn.set_quiet.html)
+pub fn is_quiet() -> bool {
+    DISPATCHER.quiet.load(Ordering::SeqCst)
+}
+
+/// Replace the installed logger at runtime, or install it if there isn't one
+///
+/// The `log` crate only allows a logger to be installed once, so [`init`](fn.init.html) installs
+/// a dispatcher which forwards to the actual logger. This swaps out that logger, e.g. to reconfigure it.
+///
+/// Records being logged while this is called will finish with the previous logger,
+/// which is then flushed and dropped.
+///
+/// ```rust
+/// # use alto_logger::{options::StyleConfig, TermLogger};
+/// alto_logger::init_term_logger().unwrap();
+/// // ..
+/// alto_logger::swap(TermLogger::new(StyleConfig::SingleLine).unwrap()).unwrap();
+/// ```
+pub fn swap(logger: impl log::Log + 'static) -> Result<(), Error> {
+    let config = crate::config::describe(&logger);
+    if DISPATCHER.current().is_none() {
+        return crate::init(logger);
+    }
+    DISPATCHER.set_filters(&config);
+    let previous = DISPATCHER.set(Box::new(logger));
+    update_max_level();
+    if let Some(previous) = previous {
+        previous.flush();
+    }
+    crate::config::set_current(config);
+    Ok(())
+}
+
+/// Use `logger` for records logged on this thread while `f` is running
+///
+/// This is useful for capturing the output of a single operation. The override is removed when `f` returns,
+/// even if it panics, and overrides can be nested.
+///
+/// ***Note*** This only has an effect when a logger has been installed with [`init`](fn.init.html) or [`preinit`](fn.preinit.html)
+///
+/// ```rust,no_run
+/// # use alto_logger::{FileLogger, Options};
+/// # fn migrate_database() {}
+/// alto_logger::init_term_logger().unwrap();
+///
+/// let file = FileLogger::truncate(Options::default(), "migration.log").unwrap();
+/// alto_logger::with_logger(file, || migrate_database());
+/// ```
+pub fn with_logger<T>(logger: impl log::Log + 'static, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Arc<dyn log::Log>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let _ = OVERRIDE.try_with(|cell| *cell.borrow_mut() = self.0.take());
+            DISPATCHER.overrides.fetch_sub(1, Ordering::SeqCst);
+            update_max_level();
+        }
+    }
+
+    let logger = Arc::new(logger) as Arc<dyn log::Log>;
+    DISPATCHER.overrides.fetch_add(1, Ordering::SeqCst);
+    update_max_level();
+    let _restore = Restore(OVERRIDE.with(|cell| cell.borrow_mut().replace(logger)));
+    f()
+}
+
+/// The logger given to the `log` crate, which forwards to the installed logger
+struct Dispatcher {
+    logger: RwLock<Option<Arc<dyn log::Log>>>,
+    buffer: Mutex<Option<VecDeque<OwnedRecord>>>,
+    // whether this was given to the `log` crate
+    registered: AtomicBool,
+    // whether only errors are logged
+    quiet: AtomicBool,
+    // the installed loggers, for the filters that limit what they write
+    configs: Mutex<Vec<LoggerConfig>>,
+    // how many `with_logger` overrides are in use, on any thread
+    overrides: AtomicUsize,
+}
+
+impl Dispatcher {
+    #[cfg(not(loom))]
+    const fn new() -> Self {
+        Self {
+            logger: RwLock::new(None),
+            buffer: Mutex::new(None),
+            registered: AtomicBool::new(false),
+            quiet: AtomicBool::new(false),
+            configs: Mutex::new(Vec::new()),
+            overrides: AtomicUsize::new(0),
+        }
+    }
+
+    // loom's locks can't be created in a `const`
+    #[cfg(loom)]
+    fn new() -> Self {
+        Self {
+            logger: RwLock::new(None),
+            buffer: Mutex::new(None),
+            registered: AtomicBool::new(false),
+            quiet: AtomicBool::new(false),
+            configs: Mutex::new(Vec::new()),
+            overrides: AtomicUsize::new(0),
+        }
+    }
+
+    fn set_filters(&self, config: &[LoggerConfig]) {
+        *crate::sync::lock(&self.configs) = config.to_vec();
+    }
+
+    /// The most verbose level a record can be logged at, see [`update_max_level`]
+    fn max_level(&self) -> log::LevelFilter {
+        let level = match &*crate::sync::lock(&self.configs) {
+            // an override, or the logger that buffered records are replayed through, could enable anything
+            _ if self.overrides.load(Ordering::SeqCst) > 0 || self.is_buffering() => {
+                log::LevelFilter::Trace
+            }
+            configs => configs
+                .iter()
+                .map(LoggerConfig::max_level)
+                .max()
+                .unwrap_or(log::LevelFilter::Off),
+        };
+        match self.quiet.load(Ordering::SeqCst) {
+            true => level.min(log::LevelFilter::Error),
+            false => level,
+        }
+    }
+
+    fn is_suppressed(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() > log::Level::Error && self.quiet.load(Ordering::Relaxed)
+    }
+
+    fn start_buffering(&self) {
+        crate::sync::lock(&self.buffer).get_or_insert_with(VecDeque::new);
+    }
+
+    fn is_buffering(&self) -> bool {
+        crate::sync::lock(&self.buffer).is_some()
+    }
+
+    /// Get the installed logger
+    ///
+    /// Its cloned out so the lock isn't held while logging, and a swap doesn't wait on a slow logger
+    fn current(&self) -> Option<Arc<dyn log::Log>> {
+        crate::sync::read(&self.logger).clone()
+    }
+
+    /// Get the logger for this thread, which is either an override or the installed logger
+    fn logger(&self) -> Option<Arc<dyn log::Log>> {
+        OVERRIDE
+            .try_with(|cell| cell.borrow().clone())
+            .ok()
+            .flatten()
+            .or_else(|| self.current())
+    }
+
+    fn set(&self, logger: Box<dyn log::Log>) -> Option<Arc<dyn log::Log>> {
+        let logger = Arc::<dyn log::Log>::from(logger);
+        let (previous, pending) = {
+            let mut buffer = crate::sync::lock(&self.buffer);
+            let previous = crate::sync::write(&self.logger).replace(Arc::clone(&logger));
+            (previous, buffer.take())
+        };
+
+        for record in pending.into_iter().flatten() {
+            record.with_record(|owned| {
+                crate::record::replaying(record.timestamp, record.thread.as_deref(), || {
+                    logger.log(owned)
+                })
+            })
+        }
+        previous
+    }
+}
+
+impl log::Log for Dispatcher {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        if self.is_suppressed(metadata) {
+            return false;
+        }
+        match self.logger() {
+            Some(logger) => logger.enabled(metadata),
+            // a logger could have been installed, ending the buffering, after it was checked
+            None => {
+                self.is_buffering()
+                    || self
+                        .current()
+                        .is_some_and(|logger| logger.enabled(metadata))
+            }
+        }
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.is_suppressed(record.metadata()) {
+            return;
+        }
+        if let Some(logger) = self.logger() {
+            return crate::latency::measure(|| logger.log(record));
+        }
+
+        let mut buffer = crate::sync::lock(&self.buffer);
+        match &mut *buffer {
+            Some(buffer) => {
+                if buffer.len() == PREINIT_CAPACITY {
+                    buffer.pop_front();
+                }
+                buffer.push_back(OwnedRecord::from_record(record));
+            }
+            // a logger was installed after it was checked above
+            None => {
+                drop(buffer);
+                if let Some(logger) = self.current() {
+                    logger.log(record)
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = self.logger() {
+            logger.flush()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChannelLogger;
+    use log::Log as _;
+    use std::sync::mpsc::Receiver;
+
+    fn messages(rx: &Receiver<OwnedRecord>) -> Vec<String> {
+        rx.try_iter().map(|record| record.message).collect()
+    }
+
+    #[test]
+    fn replay() {
+        let log = |dispatcher: &Dispatcher, msg: &str| {
+            dispatcher.log(&log::Record::builder().args(format_args!("{}", msg)).build())
+        };
+
+        let dispatcher = Dispatcher::new();
+        log(&dispatcher, "dropped");
+        assert!(!dispatcher.enabled(&log::Metadata::builder().build()));
+
+        dispatcher.start_buffering();
+        assert!(dispatcher.enabled(&log::Metadata::builder().build()));
+        for i in 0..PREINIT_CAPACITY + 2 {
+            log(&dispatcher, &i.to_string());
+        }
+
+        let buffered = std::time::SystemTime::now();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        let (capture, rx) = ChannelLogger::capture();
+        dispatcher.set(Box::new(capture));
+        log(&dispatcher, "after");
+        assert!(!dispatcher.is_buffering());
+
+        let records = rx.try_iter().collect::<Vec<_>>();
+        // the buffered records keep the time they were logged at
+        assert!(records[..PREINIT_CAPACITY]
+            .iter()
+            .all(|record| record.timestamp <= buffered));
+        assert!(records[PREINIT_CAPACITY].timestamp > buffered);
+
+        let replayed = records
+            .into_iter()
+            .map(|record| record.message)
+            .collect::<Vec<_>>();
+        assert_eq!(replayed.len(), PREINIT_CAPACITY + 1);
+        assert_eq!(replayed[0], "2");
+        assert_eq!(
+            replayed[PREINIT_CAPACITY - 1],
+            (PREINIT_CAPACITY + 1).to_string()
+        );
+        assert_eq!(replayed[PREINIT_CAPACITY], "after");
+
+        let (capture, rx) = ChannelLogger::capture();
+        assert!(dispatcher.set(Box::new(capture)).is_some());
+        log(&dispatcher, "replaced");
+        assert_eq!(messages(&rx), ["replaced"]);
+    }
+
+    #[test]
+    fn max_level() {
+        use crate::Filters;
+
+        let dispatcher = Dispatcher::new();
+        let term = |directives| {
+            let options = crate::Options::default().with_filters(Filters::parse(directives));
+            crate::TermLogger::new(options).unwrap().describe()
+        };
+        let config = term("info,foo=debug,*_sys=off");
+        let filters = config.shared.clone().unwrap();
+        dispatcher.set_filters(&[config, term("warn")]);
+        assert_eq!(dispatcher.max_level(), log::LevelFilter::Debug);
+
+        filters.replace(Filters::parse("error,bar=only:info"));
+        assert_eq!(dispatcher.max_level(), log::LevelFilter::Info);
+
+        dispatcher.quiet.store(true, Ordering::SeqCst);
+        assert_eq!(dispatcher.max_level(), log::LevelFilter::Error);
+        dispatcher.quiet.store(false, Ordering::SeqCst);
+
+        dispatcher.start_buffering();
+        assert_eq!(dispatcher.max_level(), log::LevelFilter::Trace);
+        crate::sync::lock(&dispatcher.buffer).take();
+
+        dispatcher.set_filters(&[]);
+        assert_eq!(dispatcher.max_level(), log::LevelFilter::Off);
+
+        // a logger that isn't one of ours
+        let (channel, _rx) = ChannelLogger::capture();
+        dispatcher.set_filters(&crate::config::describe(&channel));
+        assert_eq!(dispatcher.max_level(), log::LevelFilter::Trace);
+
+        // the filters of a `MultiLogger` limit the loggers in it
+        let multi = crate::MultiLogger::new().with(channel);
+        let config = crate::config::describe(&multi);
+        config[0].parents[0].replace(Filters::parse("info"));
+        dispatcher.set_filters(&config);
+        assert_eq!(dispatcher.max_level(), log::LevelFilter::Info);
+    }
+
+    // a threaded stress test of what `set_model` checks every interleaving of, with `--cfg loom`.
+    // `enabled` and `log` take the logger and buffer locks, while `set` takes both of them
+    #[test]
+    fn contention() {
+        let dispatcher = Dispatcher::new();
+        dispatcher.start_buffering();
+
+        let receivers = std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        let metadata = log::Metadata::builder().level(log::Level::Info).build();
+                        if dispatcher.enabled(&metadata) {
+                            dispatcher.log(&log::Record::builder().metadata(metadata).build());
+                        }
+                    }
+                });
+            }
+
+            let setter = s.spawn(|| {
+                (0..20)
+                    .map(|_| {
+                        let (capture, rx) = ChannelLogger::capture();
+                        dispatcher.set(Box::new(capture));
+                        rx
+                    })
+                    .collect::<Vec<_>>()
+            });
+            setter.join().unwrap()
+        });
+
+        // every record was either written, or buffered and replayed into the first logger
+        let written = receivers
+            .iter()
+            .map(|rx| rx.try_iter().count())
+            .sum::<usize>();
+        assert_eq!(written, 400);
+    }
+
+    // `RUSTFLAGS="--cfg loom" cargo test --lib model`
+    #[cfg(loom)]
+    #[test]
+    fn set_model() {
+        loom::model(|| {
+            let dispatcher = Arc::new(Dispatcher::new());
+            dispatcher.start_buffering();
+
+            let logging = loom::thread::spawn({
+                let dispatcher = Arc::clone(&dispatcher);
+                move || {
+                    let metadata = log::Metadata::builder().level(log::Level::Info).build();
+                    if dispatcher.enabled(&metadata) {
+                        dispatcher.log(&log::Record::builder().metadata(metadata).build());
+                    }
+                }
+            });
+            let (capture, rx) = ChannelLogger::capture();
+            dispatcher.set(Box::new(capture));
+            logging.join().unwrap();
+
+            // the record was either written, or buffered and replayed
+            assert_eq!(rx.try_iter().count(), 1);
+        });
+    }
+
+    #[test]
+    fn override_logger() {
+        let dispatcher = Dispatcher::new();
+        let log = |msg: &str| {
+            dispatcher.log(&log::Record::builder().args(format_args!("{}", msg)).build())
+        };
+
+        let (capture, installed) = ChannelLogger::capture();
+        dispatcher.set(Box::new(capture));
+
+        let (capture, outer) = ChannelLogger::capture();
+        let (inner_capture, inner) = ChannelLogger::capture();
+        with_logger(capture, || {
+            log("a");
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                with_logger(inner_capture, || {
+                    log("b");
+                    panic!("oops")
+                })
+            }));
+            assert!(result.is_err());
+            log("c");
+
+            // other threads still use the installed logger
+            std::thread::scope(|s| s.spawn(|| log("d")).join().unwrap());
+        });
+        log("e");
+
+        assert_eq!(messages(&outer), ["a", "c"]);
+        assert_eq!(messages(&inner), ["b"]);
+        assert_eq!(messages(&installed), ["d", "e"]);
+    }
+
+    #[test]
+    fn quiet() {
+        let dispatcher = Dispatcher::new();
+        let (capture, rx) = ChannelLogger::capture();
+        dispatcher.set(Box::new(capture));
+
+        let log = |level, msg: &str| {
+            dispatcher.log(
+                &log::Record::builder()
+                    .level(level)
+                    .args(format_args!("{}", msg))
+                    .build(),
+            )
+        };
+
+        dispatcher.quiet.store(true, Ordering::SeqCst);
+        log(log::Level::Warn, "hidden");
+        log(log::Level::Error, "shown");
+        assert!(!dispatcher.enabled(&log::Metadata::builder().level(log::Level::Info).build()));
+
+        dispatcher.quiet.store(false, Ordering::SeqCst);
+        log(log::Level::Warn, "visible");
+        assert_eq!(messages(&rx), ["shown", "visible"]);
+    }
+}
@@ -1,11 +1,88 @@
+use std::path::PathBuf;
+
 #[derive(Debug)]
-/// An error returned by the logger initialization
+/// An error returned by the logger, e.g. from its initialization
 #[non_exhaustive]
 pub enum Error {
     /// Logger was already set
     SetLogger(log::SetLoggerError),
     /// An i/o error occured when opening a file logger
     FileLogger(std::io::Error),
+    /// A filter directive couldn't be parsed
+    InvalidFilter {
+        /// The directive that couldn't be parsed
+        directive: String,
+    },
+    /// A timestamp format couldn't be parsed
+    InvalidTimeFormat {
+        /// The format that couldn't be parsed
+        format: String,
+        /// Why it couldn't be parsed
+        reason: String,
+    },
+    /// A file for a rolling logger couldn't be opened
+    RotationFailed {
+        /// The file that couldn't be opened
+        path: PathBuf,
+        /// The i/o error from opening it
+        source: std::io::Error,
+    },
+    /// A request to a remote service failed
+    NetworkError {
+        /// The url of the request
+        url: String,
+        /// Why it failed
+        reason: String,
+    },
+    /// The receiving side of a channel was dropped, see [`ChannelLogger::try_log`](struct.ChannelLogger.html#method.try_log)
+    ChannelClosed,
+    /// A file of filters couldn't be read
+    FiltersFile {
+        /// The file that couldn't be read
+        path: PathBuf,
+        /// The i/o error from reading it
+        source: std::io::Error,
+    },
+}
+
+impl Error {
+    /// Whether a logger was already set
+    pub const fn is_set_logger(&self) -> bool {
+        matches!(self, Self::SetLogger(..))
+    }
+
+    /// Whether this was an i/o error from a file logger, a rolling logger or a file of filters
+    pub const fn is_io(&self) -> bool {
+        matches!(
+            self,
+            Self::FileLogger(..) | Self::RotationFailed { .. } | Self::FiltersFile { .. }
+        )
+    }
+
+    /// Whether a filter directive couldn't be parsed
+    pub const fn is_invalid_filter(&self) -> bool {
+        matches!(self, Self::InvalidFilter { .. })
+    }
+
+    /// Whether a timestamp format couldn't be parsed
+    pub const fn is_invalid_time_format(&self) -> bool {
+        matches!(self, Self::InvalidTimeFormat { .. })
+    }
+
+    /// Whether a file for a rolling logger couldn't be opened
+    pub const fn is_rotation_failed(&self) -> bool {
+        matches!(self, Self::RotationFailed { .. })
+    }
+
+    /// Whether a request to a remote service failed
+    pub const fn is_network_error(&self) -> bool {
+        matches!(self, Self::NetworkError { .. })
+    }
+
+    /// Whether the receiving side of a channel was dropped
+    pub const fn is_channel_closed(&self) -> bool {
+        matches!(self, Self::ChannelClosed)
+    }
 }
 
 impl std::fmt::Display for Error {
@@ -13,6 +90,27 @@ impl std::fmt::Display for Error {
         match self {
             Self::SetLogger(err) => write!(f, "{}", err),
             Self::FileLogger(err) => write!(f, "{}", err),
+            Self::InvalidFilter { directive } => {
+                write!(f, "invalid filter directive: '{}'", directive)
+            }
+            Self::InvalidTimeFormat { format, reason } => {
+                write!(f, "invalid time format '{}': {}", format, reason)
+            }
+            Self::RotationFailed { path, source } => {
+                write!(f, "cannot open '{}': {}", path.display(), source)
+            }
+            Self::NetworkError { url, reason } => {
+                write!(f, "request to {} failed: {}", url, reason)
+            }
+            Self::ChannelClosed => f.write_str("the channel was closed"),
+            Self::FiltersFile { path, source } => {
+                write!(
+                    f,
+                    "cannot read filters from '{}': {}",
+                    path.display(),
+                    source
+                )
+            }
         }
     }
 }
@@ -22,6 +120,8 @@ impl std::error::Error for Error {
         match self {
             Self::SetLogger(err) => Some(err),
             Self::FileLogger(err) => Some(err),
+            Self::RotationFailed { source, .. } | Self::FiltersFile { source, .. } => Some(source),
+            _ => None,
         }
     }
 }
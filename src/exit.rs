@@ -0,0 +1,38 @@
+use std::sync::OnceLock;
+
+/// Flush the installed logger when the process exits
+///
+/// This registers an `atexit` handler, which runs when `main` returns and when
+/// `std::process::exit` is called. Rust doesn't run the destructors of statics, so without this
+/// the records still buffered by e.g. an [`AsyncLogger`](struct.AsyncLogger.html) are lost.
+///
+/// This can be called more than once, the handler is only registered the first time.
+/// It returns whether the handler is registered.
+///
+/// ***Note*** This doesn't run when the process is killed by a signal, or aborts.
+/// See `flush_on_signal` with the `signal-hook` feature for signals.
+///
+/// ```rust,no_run
+/// # use alto_logger::{AsyncLogger, FileLogger, Options, Overflow};
+/// let file = FileLogger::append(Options::default(), "service.log").unwrap();
+/// AsyncLogger::new(file, 1024, Overflow::Block).init().unwrap();
+/// alto_logger::flush_on_exit();
+///
+/// log::error!("cannot start");
+/// std::process::exit(1);
+/// ```
+pub fn flush_on_exit() -> bool {
+    extern "C" {
+        fn atexit(callback: extern "C" fn()) -> std::os::raw::c_int;
+    }
+
+    extern "C" fn flush() {
+        // a panic cannot unwind out of here
+        let _ = std::panic::catch_unwind(|| log::logger().flush());
+    }
+
+    static REGISTERED: OnceLock<bool> = OnceLock::new();
+
+    // SAFETY: `flush` is a plain function which doesn't unwind
+    *REGISTERED.get_or_init(|| unsafe { atexit(flush) == 0 })
+}
@@ -1,17 +1,139 @@
-use std::{borrow::Cow, collections::HashMap};
+pub(crate) mod file;
 
-#[derive(Debug)]
+use crate::sync::{Mutex, RwLock};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+#[derive(Clone, Debug)]
 pub(crate) enum FiltersKind {
     Default,
     Blanket,
-    List(Vec<(Cow<'static, str>, log::LevelFilter)>),
-    Map(HashMap<Cow<'static, str>, log::LevelFilter>),
+    List(Vec<(Cow<'static, str>, LevelRange)>),
+    Map(HashMap<Cow<'static, str>, LevelRange>),
+}
+
+/// The levels a directive allows, from its least verbose level `min` up to `max`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct LevelRange {
+    max: log::LevelFilter,
+    min: log::Level,
+}
+
+impl LevelRange {
+    const fn up_to(max: log::LevelFilter) -> Self {
+        Self {
+            max,
+            min: log::Level::Error,
+        }
+    }
+
+    #[inline]
+    fn contains(&self, level: log::Level) -> bool {
+        self.min <= level && level <= self.max
+    }
+
+    /// Parse `level`, `only:level` or `level..level` (in either order)
+    fn parse(input: &str) -> Option<Self> {
+        let level = |s: &str| s.to_ascii_uppercase().parse::<log::LevelFilter>().ok();
+
+        if let Some(only) = input.strip_prefix("only:") {
+            let min = level(only)?.to_level()?;
+            return Some(Self {
+                max: min.to_level_filter(),
+                min,
+            });
+        }
+
+        if let Some((left, right)) = input.split_once("..") {
+            let (left, right) = (level(left)?.to_level()?, level(right)?.to_level()?);
+            return Some(Self {
+                max: left.max(right).to_level_filter(),
+                min: left.min(right),
+            });
+        }
+
+        level(input).map(Self::up_to)
+    }
+}
+
+impl std::fmt::Display for LevelRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let max = self.max.as_str().to_ascii_lowercase();
+        match self.min {
+            log::Level::Error => f.write_str(&max),
+            min if Some(min) == self.max.to_level() => write!(f, "only:{}", max),
+            min => write!(f, "{}..{}", max, min.as_str().to_ascii_lowercase()),
+        }
+    }
 }
 
-#[derive(Debug)]
-pub(crate) struct Filters {
+/// A set of filter directives, using the same syntax as `RUST_LOG`
+///
+/// This can be applied to a logger with [`ReloadHandle::set_filters`](struct.ReloadHandle.html#method.set_filters)
+///
+/// ```rust
+/// # use alto_logger::{Filters, Preset, TermLogger};
+/// let logger = TermLogger::default();
+/// logger.reload_handle().set_filters(Filters::preset(Preset::Verbose));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Filters {
     kind: FiltersKind,
-    minimum: Option<log::LevelFilter>,
+    minimum: Option<LevelRange>,
+    // the length of the longest module with a directive, so longer parents of a target aren't looked up
+    longest: usize,
+    // directives with a glob or a regex, in the order they were given
+    patterns: Vec<Pattern>,
+    // what the message has to match, from the part of the directives after a `/`
+    message: Option<MessageFilter>,
+    // the file these filters were read from, when it should be watched for changes
+    pub(crate) watch: Option<PathBuf>,
+}
+
+#[cfg(feature = "regex")]
+type MessageFilter = regex::Regex;
+#[cfg(not(feature = "regex"))]
+type MessageFilter = String;
+
+/// A directive for the modules matching a glob or a regex, rather than for a module and the modules below it
+#[derive(Clone, Debug)]
+struct Pattern {
+    // the module of the directive, e.g. `*_sys` or `/^hyper::(client|proto)/`
+    text: Cow<'static, str>,
+    level: LevelRange,
+    #[cfg(feature = "regex")]
+    regex: Option<regex::Regex>,
+}
+
+impl Pattern {
+    /// Whether a glob matches the module or one of its parents, or a regex matches the module
+    fn matches(&self, module: &str) -> bool {
+        #[cfg(feature = "regex")]
+        if let Some(regex) = &self.regex {
+            return regex.is_match(module);
+        }
+        module
+            .match_indices("::")
+            .map(|(i, _)| &module[..i])
+            .chain(std::iter::once(module))
+            .any(|parent| glob_match(&self.text, parent))
+    }
+}
+
+/// What a directive applies to
+#[derive(Clone, Debug)]
+enum Directive {
+    Default,
+    Module(Cow<'static, str>),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
 }
 
 impl Default for Filters {
@@ -19,24 +141,271 @@ impl Default for Filters {
         Self {
             kind: FiltersKind::Default,
             minimum: None,
+            longest: 0,
+            patterns: Vec::new(),
+            message: None,
+            watch: None,
         }
     }
 }
 
+/// A curated set of filter directives
+///
+/// The noisier presets keep common networking dependencies (`hyper`, `h2`, `tokio`, ..) at a quieter level.
+///
+/// ***Note*** Defaults to `Normal`
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Preset {
+    /// Only warnings and errors
+    Quiet,
+    /// Informational messages, warnings and errors
+    #[default]
+    Normal,
+    /// Debug messages, with only informational messages from noisy dependencies
+    Verbose,
+    /// Everything, with only debug messages from noisy dependencies
+    Debugging,
+}
+
+impl Preset {
+    /// Get the filter directives of this preset
+    pub fn directives(self) -> String {
+        let data = match self {
+            Self::Quiet => include_str!("filters/presets/quiet.txt"),
+            Self::Normal => include_str!("filters/presets/normal.txt"),
+            Self::Verbose => include_str!("filters/presets/verbose.txt"),
+            Self::Debugging => include_str!("filters/presets/debugging.txt"),
+        };
+        join_lines(data)
+    }
+}
+
 impl Filters {
-    pub(crate) fn from_str(input: &str) -> Self {
-        let mut mapping = input.split(',').filter_map(parse).collect::<Vec<_>>();
+    /// Parse these filter directives, e.g. `info,my_crate=trace`
+    pub fn parse(directives: &str) -> Self {
+        Self::from_str(directives)
+    }
+
+    /// Read the filters from a file
+    ///
+    /// A file with a `.toml` extension has `key = "value"` lines for a default level, levels for modules,
+    /// and whether it should be watched for changes:
+    /// ```toml
+    /// level = "info"
+    /// # re-read the file when it changes
+    /// watch = true
+    ///
+    /// [modules]
+    /// hyper = "warn"
+    /// "my_app::db" = "trace..debug"
+    /// ```
+    /// This isn't parsed as TOML, it's a restricted format which only has these keys, the `[modules]` table,
+    /// and `#` comments. Strings can't contain escapes, and values can't span lines.
+    /// Any other file has directives separated by commas or newlines, like a [`FileSource`](struct.FileSource.html).
+    ///
+    /// With `watch = true`, a logger given these filters with [`Options::with_filters`](struct.Options.html#method.with_filters)
+    /// checks the file every 2 seconds, and replaces its filters when the file has changed.
+    /// It stops checking once the logger, and every [`ReloadHandle`](struct.ReloadHandle.html) for it, is dropped.
+    ///
+    /// ```rust,no_run
+    /// # use alto_logger::{Filters, Options, TermLogger};
+    /// let filters = Filters::from_file("log.toml").unwrap();
+    /// TermLogger::new(Options::default().with_filters(filters))
+    ///     .unwrap()
+    ///     .init()
+    ///     .unwrap();
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, crate::Error> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path).map_err(|source| crate::Error::FiltersFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let config = file::parse(path, &data)
+            .map_err(|directive| crate::Error::InvalidFilter { directive })?;
+        Ok(Self {
+            watch: config.watch.then(|| path.to_path_buf()),
+            ..Self::try_parse(&config.directives)?
+        })
+    }
 
-        let minimum = input
+    /// Parse these filter directives, failing on the first one that isn't valid
+    ///
+    /// ```rust
+    /// # use alto_logger::Filters;
+    /// assert!(Filters::try_parse("info,my_crate=trace").is_ok());
+    /// assert!(Filters::try_parse("info,my_crate=verbose").unwrap_err().is_invalid_filter());
+    /// ```
+    pub fn try_parse(input: &str) -> Result<Self, crate::Error> {
+        let (directives, message) = split_message(input);
+        #[cfg(feature = "regex")]
+        if let Some(message) = message.filter(|m| regex::Regex::new(m).is_err()) {
+            return Err(crate::Error::InvalidFilter {
+                directive: format!("/{}", message),
+            });
+        }
+        #[cfg(not(feature = "regex"))]
+        let _ = message;
+
+        let invalid = directives
             .split(',')
-            .filter(|s| !s.contains('='))
-            .flat_map(|s| s.parse().ok())
-            .filter(|&l| l != log::LevelFilter::Off)
-            .max();
+            .filter(|directive| !directive.is_empty())
+            .find(|directive| match is_regex(directive) {
+                #[cfg(feature = "regex")]
+                true => parse_regex(directive).is_none(),
+                #[cfg(not(feature = "regex"))]
+                true => true,
+                false => parse(directive).is_none() && LevelRange::parse(directive).is_none(),
+            });
+        match invalid {
+            Some(directive) => Err(crate::Error::InvalidFilter {
+                directive: directive.to_string(),
+            }),
+            None => Ok(Self::from_str(input)),
+        }
+    }
+
+    /// Use the filter directives of this preset
+    pub fn preset(preset: Preset) -> Self {
+        Self::from_str(&preset.directives())
+    }
+
+    /// Use the filter directives of the `RUST_LOG` environment variable, or this preset if it isn't set
+    pub fn from_env_or(preset: Preset) -> Self {
+        std::env::var("RUST_LOG")
+            .map(|s| Self::from_str(&s))
+            .unwrap_or_else(|_| Self::preset(preset))
+    }
+
+    /// Get the module directives, with the most verbose level each one enables
+    ///
+    /// These are in the order they were given (or sorted by module, when there are many of them),
+    /// and the default level isn't included.
+    ///
+    /// ```rust
+    /// # use alto_logger::Filters;
+    /// let filters = Filters::parse("info,hyper=warn,my_crate=trace");
+    /// assert_eq!(filters.minimum(), Some(log::LevelFilter::Info));
+    /// for (module, level) in filters.directives() {
+    ///     println!("{}: {}", module, level);
+    /// }
+    /// ```
+    pub fn directives(&self) -> impl Iterator<Item = (&str, log::LevelFilter)> + '_ {
+        let mut directives = match &self.kind {
+            FiltersKind::Default | FiltersKind::Blanket => vec![],
+            FiltersKind::List(levels) => levels.iter().map(|(m, l)| (&**m, l.max)).collect(),
+            FiltersKind::Map(levels) => levels.iter().map(|(m, l)| (&**m, l.max)).collect(),
+        };
+        if let FiltersKind::Map(..) = self.kind {
+            directives.sort();
+        }
+        directives.extend(self.patterns.iter().map(|p| (&*p.text, p.level.max)));
+        directives.into_iter()
+    }
+
+    /// Get the default level, for modules without a directive
+    ///
+    /// This is `None` if only modules with a directive are enabled
+    pub fn minimum(&self) -> Option<log::LevelFilter> {
+        self.minimum.map(|range| range.max)
+    }
+
+    /// Build filters in code, without parsing directives
+    ///
+    /// ```rust
+    /// # use alto_logger::{Filters, Options, TermLogger};
+    /// # use log::LevelFilter;
+    /// let filters = Filters::builder()
+    ///     .module("tokio", LevelFilter::Warn)
+    ///     .module("my_app::db", LevelFilter::Trace)
+    ///     .default(LevelFilter::Info)
+    ///     .build();
+    /// TermLogger::new(Options::default().with_filters(filters))
+    ///     .unwrap()
+    ///     .init()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> FiltersBuilder {
+        FiltersBuilder { directives: vec![] }
+    }
+
+    pub(crate) fn from_str(input: &str) -> Self {
+        let (input, message) = split_message(input);
+        let mut builder = FiltersBuilder { directives: vec![] };
+        for directive in input.split(',') {
+            if is_regex(directive) {
+                // a regex, which is ignored without the `regex` feature
+                #[cfg(feature = "regex")]
+                if let Some((regex, level)) = parse_regex(directive) {
+                    builder.directives.push((Directive::Regex(regex), level));
+                }
+            } else if let Some((module, level)) = parse(directive) {
+                builder.directives.push((Directive::Module(module), level));
+            } else if let Some(level) = LevelRange::parse(directive) {
+                builder.directives.push((Directive::Default, level));
+            }
+        }
+
+        #[cfg(feature = "regex")]
+        let message = message.and_then(|m| regex::Regex::new(m).ok());
+        #[cfg(not(feature = "regex"))]
+        let message = message.map(String::from);
+        Self {
+            message,
+            watch: None,
+            ..builder.build()
+        }
+    }
+
+    fn from_directives(directives: Vec<(Directive, LevelRange)>) -> Self {
+        // later directives replace earlier ones for the same module (or the default level)
+        let mut mapping: Vec<(Cow<'static, str>, LevelRange)> = vec![];
+        let mut index = HashMap::<Cow<'static, str>, usize>::new();
+        let mut minimum = None;
+        #[cfg(feature = "regex")]
+        let mut regexes = HashMap::new();
+        for (directive, level) in directives {
+            let module = match directive {
+                Directive::Default => {
+                    minimum.replace(level);
+                    continue;
+                }
+                Directive::Module(module) => module,
+                #[cfg(feature = "regex")]
+                Directive::Regex(regex) => {
+                    let module = Cow::<str>::Owned(format!("/{}/", regex.as_str()));
+                    regexes.insert(module.clone(), regex);
+                    module
+                }
+            };
+            match index.get(&module) {
+                Some(&i) => mapping[i].1 = level,
+                None => {
+                    index.insert(module.clone(), mapping.len());
+                    mapping.push((module, level))
+                }
+            }
+        }
+        let minimum = minimum.filter(|l| l.max != log::LevelFilter::Off);
+        let (patterns, mut mapping): (Vec<_>, Vec<_>) = mapping
+            .into_iter()
+            .partition(|(m, _)| m.starts_with('/') || m.contains(['*', '?']));
+        let patterns = patterns
+            .into_iter()
+            .map(|(text, level)| Pattern {
+                #[cfg(feature = "regex")]
+                regex: regexes.remove(&text),
+                text,
+                level,
+            })
+            .collect::<Vec<_>>();
+        let longest = mapping.iter().map(|(m, _)| m.len()).max().unwrap_or(0);
 
         let kind = match mapping.len() {
-            0 if minimum.is_none() => FiltersKind::Default,
-            0 => FiltersKind::Blanket,
+            0 if minimum.is_none() && patterns.is_empty() => FiltersKind::Default,
+            0 if patterns.is_empty() => FiltersKind::Blanket,
             d if d < 15 => {
                 mapping.shrink_to_fit();
                 FiltersKind::List(mapping)
@@ -44,7 +413,14 @@ impl Filters {
             _ => FiltersKind::Map(mapping.into_iter().collect()),
         };
 
-        Self { kind, minimum }
+        Self {
+            kind,
+            minimum,
+            longest,
+            patterns,
+            message: None,
+            watch: None,
+        }
     }
 
     pub(crate) fn from_env() -> Self {
@@ -53,16 +429,85 @@ impl Filters {
             .unwrap_or_default()
     }
 
+    /// Whether these filters enable a record with this metadata
+    ///
+    /// This doesn't check the message filter, see [`is_record_enabled`](#method.is_record_enabled)
     #[inline]
-    pub(crate) fn is_enabled(&self, metadata: &log::Metadata<'_>) -> bool {
-        match self.find_module(metadata.target()) {
-            Some(level) => metadata.level() <= level,
+    pub fn is_enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.is_target_enabled(metadata.target(), metadata.level())
+    }
+
+    /// Whether these filters enable this record, including whether its message matches the message filter
+    ///
+    /// ```rust
+    /// # use alto_logger::Filters;
+    /// let filters = Filters::parse("debug/connection reset");
+    /// let record = |message| {
+    ///     filters.is_record_enabled(&log::Record::builder().level(log::Level::Info).args(message).build())
+    /// };
+    /// assert!(record(format_args!("connection reset by peer")));
+    /// assert!(!record(format_args!("connected")));
+    /// ```
+    pub fn is_record_enabled(&self, record: &log::Record<'_>) -> bool {
+        self.is_enabled(record.metadata()) && self.matches_message(record)
+    }
+
+    /// Whether the message of this record matches the message filter, if there is one
+    pub(crate) fn matches_message(&self, record: &log::Record<'_>) -> bool {
+        let Some(filter) = &self.message else {
+            return true;
+        };
+        let message = match record.args().as_str() {
+            Some(message) => std::borrow::Cow::Borrowed(message),
+            None => std::borrow::Cow::Owned(record.args().to_string()),
+        };
+        #[cfg(feature = "regex")]
+        return filter.is_match(&message);
+        #[cfg(not(feature = "regex"))]
+        return message.contains(filter.as_str());
+    }
+
+    #[inline]
+    pub(crate) fn is_target_enabled(&self, target: &str, level: log::Level) -> bool {
+        match self.find_range(target) {
+            Some(range) => range.contains(level),
             None => false,
         }
     }
 
+    /// The most verbose level these filters enable for any module
+    ///
+    /// ```rust
+    /// # use alto_logger::Filters;
+    /// assert_eq!(Filters::parse("info,hyper=warn,my_app::db=debug").max_level(), log::LevelFilter::Debug);
+    /// assert_eq!(Filters::parse("off").max_level(), log::LevelFilter::Off);
+    /// ```
+    pub fn max_level(&self) -> log::LevelFilter {
+        let modules = match &self.kind {
+            FiltersKind::Default => return log::LevelFilter::Off,
+            FiltersKind::Blanket => vec![],
+            FiltersKind::List(levels) => levels.iter().map(|(_, l)| l.max).collect(),
+            FiltersKind::Map(levels) => levels.values().map(|l| l.max).collect(),
+        };
+        self.minimum
+            .iter()
+            .map(|l| l.max)
+            .chain(modules)
+            .chain(self.patterns.iter().map(|p| p.level.max))
+            .max()
+            .unwrap_or(log::LevelFilter::Off)
+    }
+
+    /// Find the most verbose level enabled for this module, or `None` if no directive applies to it
+    ///
+    /// ***Note*** a level range, e.g. `foo=warn..error`, may not enable every level below this
+    #[inline]
+    pub fn find_module(&self, module: &str) -> Option<log::LevelFilter> {
+        self.find_range(module).map(|range| range.max)
+    }
+
     #[inline]
-    pub(crate) fn find_module(&self, module: &str) -> Option<log::LevelFilter> {
+    pub(crate) fn find_range(&self, module: &str) -> Option<LevelRange> {
         match self.kind {
             FiltersKind::Default => return None,
             FiltersKind::Blanket => return self.minimum,
@@ -77,7 +522,8 @@ impl Filters {
         for (i, ch) in module.char_indices().rev() {
             if last {
                 last = false;
-                if ch == ':' {
+                // a parent longer than every directive can't have one
+                if ch == ':' && i <= self.longest {
                     if let Some(level) = self.find_exact(&module[..i]) {
                         return Some(level);
                     }
@@ -87,11 +533,20 @@ impl Filters {
             }
         }
 
-        self.minimum
+        self.find_pattern(module).or(self.minimum)
+    }
+
+    /// Find the last glob (matching this module, or one of its parents) or regex (matching this module) directive
+    fn find_pattern(&self, module: &str) -> Option<LevelRange> {
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(module))
+            .map(|pattern| pattern.level)
     }
 
     #[inline]
-    pub(crate) fn find_exact(&self, module: &str) -> Option<log::LevelFilter> {
+    pub(crate) fn find_exact(&self, module: &str) -> Option<LevelRange> {
         match &self.kind {
             FiltersKind::Default => None,
             FiltersKind::Blanket => self.minimum,
@@ -103,12 +558,351 @@ impl Filters {
     }
 }
 
+/// A builder for [`Filters`](struct.Filters.html), from [`Filters::builder`](struct.Filters.html#method.builder)
+///
+/// Like directives, a later level for the same module (or a later default level) replaces an earlier one.
+#[derive(Clone, Debug, Default)]
+pub struct FiltersBuilder {
+    directives: Vec<(Directive, LevelRange)>,
+}
+
+impl FiltersBuilder {
+    /// Enable up to `level` for this module, and the modules below it
+    pub fn module(mut self, module: impl Into<Cow<'static, str>>, level: log::LevelFilter) -> Self {
+        self.directives
+            .push((Directive::Module(module.into()), LevelRange::up_to(level)));
+        self
+    }
+
+    /// Enable up to `level` for the modules this regex matches
+    ///
+    /// This is the same as a `/regex/=level` directive. Regexes (and globs) are only used
+    /// for a module when there isn't a directive for the module or its parents.
+    #[cfg(feature = "regex")]
+    pub fn regex(mut self, regex: regex::Regex, level: log::LevelFilter) -> Self {
+        self.directives
+            .push((Directive::Regex(regex), LevelRange::up_to(level)));
+        self
+    }
+
+    /// Enable up to `level` for modules without their own level
+    ///
+    /// ***Note*** Defaults to only enabling the modules with a level
+    pub fn default(mut self, level: log::LevelFilter) -> Self {
+        self.directives
+            .push((Directive::Default, LevelRange::up_to(level)));
+        self
+    }
+
+    /// Build the `Filters`
+    pub fn build(self) -> Filters {
+        Filters::from_directives(self.directives)
+    }
+}
+
+/// Filters that can be replaced at runtime, shared between a logger and its [`ReloadHandle`](../struct.ReloadHandle.html)
+#[derive(Clone, Default)]
+pub(crate) struct SharedFilters(Arc<Shared>);
+
+#[derive(Default)]
+struct Shared {
+    filters: RwLock<Filters>,
+    observers: Mutex<Vec<Observer>>,
+    // incremented whenever the filters are replaced
+    generation: AtomicU64,
+    // targets (and their children) which are disabled regardless of the filters
+    muted: RwLock<Vec<String>>,
+    // whether the filters have a message filter, so records don't wait on the lock without one
+    has_message: AtomicBool,
+    // the filters set for a while, and what they revert to
+    ttls: Mutex<crate::reload::Ttls>,
+    // the thread watching the file the filters were read from
+    watcher: Mutex<Option<crate::PollHandle>>,
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        // this isn't joined, as it can be dropped on the thread itself
+        if let Some(watcher) = crate::sync::get_mut(&mut self.watcher) {
+            watcher.cancel()
+        }
+    }
+}
+
+type Observer = Arc<dyn Fn(&crate::FilterChange) + Send + Sync>;
+
+impl std::fmt::Debug for SharedFilters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SharedFilters")
+            .field(&*crate::sync::read(&self.0.filters))
+            .finish()
+    }
+}
+
+impl SharedFilters {
+    pub(crate) fn new(filters: Filters) -> Self {
+        Self(Arc::new(Shared {
+            has_message: AtomicBool::new(filters.message.is_some()),
+            filters: RwLock::new(filters),
+            observers: Mutex::default(),
+            generation: AtomicU64::new(0),
+            muted: RwLock::default(),
+            ttls: Mutex::default(),
+            watcher: Mutex::default(),
+        }))
+    }
+
+    pub(crate) fn from_env() -> Self {
+        Self::new(Filters::from_env())
+    }
+
+    /// Use the filters of these options, or the `RUST_LOG` environment variable if they don't have any
+    ///
+    /// Filters read from a file with `watch = true` start watching it, see [`Filters::from_file`](struct.Filters.html#method.from_file)
+    pub(crate) fn from_options(options: &crate::Options) -> Self {
+        let Some(filters) = &options.filters else {
+            return Self::from_env();
+        };
+
+        let this = Self::new(filters.clone());
+        if let Some(path) = &filters.watch {
+            // the thread only holds onto the filters while it is replacing them, so they can be dropped
+            let shared = Arc::downgrade(&this.0);
+            let watcher = crate::reload::poll_while(
+                move || {
+                    shared
+                        .upgrade()
+                        .map(|shared| crate::ReloadHandle::new(Self(shared)))
+                },
+                crate::reload::FileSource::new(path),
+                std::time::Duration::from_secs(2),
+            );
+            *crate::sync::lock(&this.0.watcher) = Some(watcher);
+        }
+        this
+    }
+
+    #[inline]
+    pub(crate) fn is_enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.is_target_enabled(metadata.target(), metadata.level())
+    }
+
+    #[inline]
+    pub(crate) fn is_target_enabled(&self, target: &str, level: log::Level) -> bool {
+        !self.is_muted(target)
+            && crate::sync::read(&self.0.filters).is_target_enabled(target, level)
+    }
+
+    fn is_muted(&self, target: &str) -> bool {
+        crate::sync::read(&self.0.muted).iter().any(|muted| {
+            target
+                .strip_prefix(muted.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+    }
+
+    /// Mute this target, returning whether it wasn't already muted
+    pub(crate) fn mute(&self, target: &str) -> bool {
+        let mut muted = crate::sync::write(&self.0.muted);
+        if muted.iter().any(|m| m == target) {
+            return false;
+        }
+        muted.push(target.to_string());
+        true
+    }
+
+    /// Unmute this target, returning whether it was muted
+    pub(crate) fn unmute(&self, target: &str) -> bool {
+        let mut muted = crate::sync::write(&self.0.muted);
+        let len = muted.len();
+        muted.retain(|m| m != target);
+        muted.len() != len
+    }
+
+    pub(crate) fn muted(&self) -> Vec<String> {
+        crate::sync::read(&self.0.muted).clone()
+    }
+
+    /// Replace the filters, returning the previous filters
+    ///
+    /// This updates the maximum level of the `log` crate, if these are the filters of the installed logger
+    pub(crate) fn replace(&self, filters: Filters) -> Filters {
+        let previous = {
+            let mut current = crate::sync::write(&self.0.filters);
+            self.0.generation.fetch_add(1, Ordering::SeqCst);
+            self.0
+                .has_message
+                .store(filters.message.is_some(), Ordering::SeqCst);
+            std::mem::replace(&mut *current, filters)
+        };
+        crate::dispatch::update_max_level();
+        previous
+    }
+
+    /// The most verbose level the filters enable for any module
+    pub(crate) fn max_level(&self) -> log::LevelFilter {
+        crate::sync::read(&self.0.filters).max_level()
+    }
+
+    /// Whether the message of this record matches the message filter, if there is one
+    #[inline]
+    pub(crate) fn matches_message(&self, record: &log::Record<'_>) -> bool {
+        !self.0.has_message.load(Ordering::Relaxed)
+            || crate::sync::read(&self.0.filters).matches_message(record)
+    }
+
+    pub(crate) fn get(&self) -> Filters {
+        crate::sync::read(&self.0.filters).clone()
+    }
+
+    /// How many times the filters have been replaced
+    pub(crate) fn generation(&self) -> u64 {
+        self.0.generation.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn ttls(&self) -> crate::sync::MutexGuard<'_, crate::reload::Ttls> {
+        crate::sync::lock(&self.0.ttls)
+    }
+
+    pub(crate) fn observe(&self, observer: Observer) {
+        crate::sync::lock(&self.0.observers).push(observer);
+    }
+
+    pub(crate) fn notify(&self, change: &crate::FilterChange) {
+        // the lock isn't held while they're called, so an observer can change the filters or add an observer
+        let observers = crate::sync::lock(&self.0.observers).clone();
+        for observer in observers {
+            observer(change)
+        }
+    }
+}
+
+impl std::fmt::Display for SharedFilters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::sync::read(&self.0.filters).fmt(f)
+    }
+}
+
+impl std::fmt::Display for Filters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut directives = match &self.kind {
+            FiltersKind::Default => return f.write_str("off"),
+            FiltersKind::Blanket => vec![],
+            FiltersKind::List(levels) => levels.iter().map(|(m, l)| (m, *l)).collect(),
+            FiltersKind::Map(levels) => {
+                let mut levels = levels.iter().map(|(m, l)| (m, *l)).collect::<Vec<_>>();
+                levels.sort();
+                levels
+            }
+        };
+
+        directives.extend(self.patterns.iter().map(|p| (&p.text, p.level)));
+
+        let minimum = self.minimum.map(|level| level.to_string());
+        let directives = minimum.into_iter().chain(
+            directives
+                .drain(..)
+                .map(|(module, level)| format!("{}={}", module, level)),
+        );
+
+        for (i, directive) in directives.enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(&directive)?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, "/{}", message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Join the lines of `data` into comma-separated directives, skipping empty lines and `#` comments
+pub(crate) fn join_lines(data: &str) -> String {
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Match `input` against a pattern where `*` matches any characters (including `::`), and `?` matches one
+fn glob_match(pattern: &str, input: &str) -> bool {
+    let (mut p, mut i) = (pattern, input);
+    // the pattern after the last `*`, and the input it has matched up to
+    let mut star = None;
+    while let Some(c) = i.chars().next() {
+        match p.chars().next() {
+            Some('*') => {
+                p = &p[1..];
+                star = Some((p, i));
+            }
+            Some(pc) if pc == '?' || pc == c => {
+                p = &p[pc.len_utf8()..];
+                i = &i[c.len_utf8()..];
+            }
+            _ => match star {
+                Some((sp, si)) => {
+                    let skipped = si.chars().next().map_or(0, char::len_utf8);
+                    star = Some((sp, &si[skipped..]));
+                    (p, i) = (sp, &si[skipped..]);
+                }
+                None => return false,
+            },
+        }
+    }
+    p.chars().all(|c| c == '*')
+}
+
+/// Split the directives from the message filter after the first `/` that isn't part of a `/regex/=level` directive
+fn split_message(input: &str) -> (&str, Option<&str>) {
+    let mut start = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            ',' => start = i + 1,
+            '/' if i > start && !is_regex(&input[start..]) => {
+                return (&input[..i], Some(&input[i + 1..]))
+            }
+            _ => {}
+        }
+    }
+    (input, None)
+}
+
+/// Whether a directive is a `/regex/=level` (or a `!/regex/`) directive
+fn is_regex(directive: &str) -> bool {
+    directive
+        .strip_prefix(['!', '-'])
+        .unwrap_or(directive)
+        .starts_with('/')
+}
+
+/// Parse a `/regex/=level` or a `!/regex/` directive
+#[cfg(feature = "regex")]
+fn parse_regex(input: &str) -> Option<(regex::Regex, LevelRange)> {
+    // `!/regex/` and `-/regex/` are the same as `/regex/=off`
+    if let Some(pattern) = input.strip_prefix(['!', '-']) {
+        let pattern = pattern.strip_prefix('/')?.strip_suffix('/')?;
+        let off = LevelRange::up_to(log::LevelFilter::Off);
+        return Some((regex::Regex::new(pattern).ok()?, off));
+    }
+    let (pattern, level) = input.strip_prefix('/')?.rsplit_once("/=")?;
+    Some((regex::Regex::new(pattern).ok()?, LevelRange::parse(level)?))
+}
+
 #[inline]
-pub(crate) fn parse(input: &str) -> Option<(Cow<'static, str>, log::LevelFilter)> {
+pub(crate) fn parse(input: &str) -> Option<(Cow<'static, str>, LevelRange)> {
+    // `!module` and `-module` are the same as `module=off`
+    if let Some(module) = input.strip_prefix(['!', '-']) {
+        let off = LevelRange::up_to(log::LevelFilter::Off);
+        return Some((Cow::Owned(module.to_string()), off));
+    }
+
     let mut iter = input.split('=');
     Some((
         Cow::Owned(iter.next()?.to_string()),
-        iter.next()?.to_ascii_uppercase().parse().ok()?,
+        LevelRange::parse(iter.next()?)?,
     ))
 }
 
@@ -135,6 +929,365 @@ mod tests {
         }
     }
 
+    #[test]
+    fn globs() {
+        let filters =
+            Filters::from_str("info,my_app::net::*=trace,*_sys=off,openssl_sys=warn,l?g=debug");
+        let level = |target| filters.find_module(target);
+        let cases = [
+            ("my_app::net::tcp", log::LevelFilter::Trace),
+            ("my_app::net::tcp::conn", log::LevelFilter::Trace),
+            ("my_app::net", log::LevelFilter::Info),
+            ("my_app::netx", log::LevelFilter::Info),
+            ("libz_sys", log::LevelFilter::Off),
+            ("libz_sys::ffi", log::LevelFilter::Off),
+            ("libz_sysx", log::LevelFilter::Info),
+            ("openssl_sys", log::LevelFilter::Warn),
+            ("openssl_sys::ssl", log::LevelFilter::Warn),
+            ("log", log::LevelFilter::Debug),
+            ("lög", log::LevelFilter::Debug),
+            ("logs", log::LevelFilter::Info),
+        ];
+        for (target, expected) in cases {
+            assert_eq!(level(target), Some(expected), "{}", target);
+        }
+
+        // a later glob replaces an earlier one
+        let filters = Filters::from_str("a*=debug,ab*=trace,a*c=warn");
+        assert_eq!(filters.find_module("abc"), Some(log::LevelFilter::Warn));
+        assert_eq!(filters.find_module("abd"), Some(log::LevelFilter::Trace));
+        assert_eq!(filters.find_module("b"), None);
+        assert_eq!(filters.to_string(), "a*=debug,ab*=trace,a*c=warn");
+
+        for (pattern, input, expected) in [
+            ("*", "", true),
+            ("a*b*c", "axxbyyc", true),
+            ("a*b*c", "axxbyy", false),
+            ("*::*", "a::b", true),
+            ("?", "", false),
+        ] {
+            assert_eq!(
+                glob_match(pattern, input),
+                expected,
+                "{} {}",
+                pattern,
+                input
+            );
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regexes() {
+        let filters = Filters::try_parse(
+            "info,/^hyper::(client|proto)/=debug,hyper::client::pool=warn,/_sys$/=off",
+        )
+        .unwrap();
+        let cases = [
+            ("hyper::client", log::LevelFilter::Debug),
+            ("hyper::proto::h1", log::LevelFilter::Debug),
+            ("hyper::client::pool", log::LevelFilter::Warn),
+            ("hyper::server", log::LevelFilter::Info),
+            ("openssl_sys", log::LevelFilter::Off),
+            ("openssl_sys::ssl", log::LevelFilter::Info),
+        ];
+        for (target, expected) in cases {
+            assert_eq!(filters.find_module(target), Some(expected), "{}", target);
+        }
+        assert_eq!(
+            filters.to_string(),
+            "info,hyper::client::pool=warn,/^hyper::(client|proto)/=debug,/_sys$/=off"
+        );
+        assert!(Filters::try_parse("/(/=debug")
+            .unwrap_err()
+            .is_invalid_filter());
+
+        let filters = Filters::builder()
+            .regex(regex::Regex::new("^a|b$").unwrap(), log::LevelFilter::Trace)
+            .build();
+        assert_eq!(filters.find_module("ab"), Some(log::LevelFilter::Trace));
+        assert_eq!(filters.find_module("c"), None);
+    }
+
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn regexes_need_feature() {
+        assert!(Filters::try_parse("/^foo/=debug")
+            .unwrap_err()
+            .is_invalid_filter());
+        assert_eq!(Filters::parse("info,/^foo/=debug").to_string(), "info");
+    }
+
+    #[test]
+    fn message_filter() {
+        let filters = Filters::from_str("debug,h2=info/connection reset");
+        assert_eq!(filters.find_module("h2"), Some(log::LevelFilter::Info));
+        assert_eq!(filters.to_string(), "debug,h2=info/connection reset");
+
+        let enabled = |target, args: std::fmt::Arguments<'_>| {
+            filters.is_record_enabled(
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target(target)
+                    .args(args)
+                    .build(),
+            )
+        };
+        let peer = "10.0.0.1";
+        assert!(enabled("app", format_args!("connection reset by {}", peer)));
+        assert!(!enabled("app", format_args!("connected to {}", peer)));
+        assert!(enabled("h2", format_args!("connection reset")));
+        assert!(!enabled("h2", format_args!("handshake")));
+
+        let (directives, message) = split_message("info/a/b");
+        assert_eq!((directives, message), ("info", Some("a/b")));
+        assert_eq!(split_message("/^a/=info"), ("/^a/=info", None));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn message_filter_regex() {
+        let filters = Filters::try_parse("info/^conn.*(reset|refused)$").unwrap();
+        let enabled = |args: std::fmt::Arguments<'_>| {
+            filters.is_record_enabled(&log::Record::builder().args(args).build())
+        };
+        assert!(enabled(format_args!("connection refused")));
+        assert!(!enabled(format_args!("a connection refused")));
+
+        assert!(Filters::try_parse("info/(")
+            .unwrap_err()
+            .is_invalid_filter());
+        assert_eq!(Filters::parse("info/(").to_string(), "info");
+    }
+
+    #[test]
+    fn deny() {
+        let filters =
+            Filters::from_str("trace,!rustls,!h2,h2::client=debug,-*_sys,openssl_sys=warn");
+        let modules = [
+            ("rustls::conn", log::LevelFilter::Off),
+            ("h2::frame", log::LevelFilter::Off),
+            ("h2::client::conn", log::LevelFilter::Debug),
+            ("libz_sys", log::LevelFilter::Off),
+            ("openssl_sys", log::LevelFilter::Warn),
+            ("app", log::LevelFilter::Trace),
+        ];
+        for (module, expected) in modules {
+            assert_eq!(filters.find_module(module), Some(expected), "{}", module);
+        }
+        assert_eq!(
+            filters.to_string(),
+            "trace,rustls=off,h2=off,h2::client=debug,openssl_sys=warn,*_sys=off"
+        );
+
+        // a deny doesn't start a message filter
+        let filters = Filters::from_str("info,!/^h2::frame/");
+        assert!(filters.message.is_none());
+        #[cfg(feature = "regex")]
+        assert_eq!(
+            filters.find_module("h2::frame"),
+            Some(log::LevelFilter::Off)
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        let filters = Filters::from_str("debug,-hyper,!h2,h2=info,foo=trace,foo=warn,trace,info");
+        let modules = &[
+            ("hyper::client", log::LevelFilter::Off),
+            ("h2", log::LevelFilter::Info),
+            ("foo", log::LevelFilter::Warn),
+            ("something", log::LevelFilter::Info),
+        ];
+        for (module, expected) in modules {
+            assert_eq!(filters.find_module(module).unwrap(), *expected);
+        }
+        assert_eq!(filters.to_string(), "info,hyper=off,h2=info,foo=warn");
+
+        assert_eq!(Filters::from_str("debug,off").to_string(), "off");
+    }
+
+    #[test]
+    fn ranges() {
+        let filters = Filters::from_str("info,foo=warn..error,bar=only:debug,baz=trace..info");
+        let enabled = |target, level| {
+            filters.is_enabled(&log::Metadata::builder().target(target).level(level).build())
+        };
+
+        use log::Level::*;
+        let cases = [
+            ("foo", [true, true, false, false, false]),
+            ("bar", [false, false, false, true, false]),
+            ("baz", [false, false, true, true, true]),
+            ("quux", [true, true, true, false, false]),
+        ];
+        for (target, expected) in cases {
+            for (level, expected) in [Error, Warn, Info, Debug, Trace].into_iter().zip(expected) {
+                assert_eq!(enabled(target, level), expected, "{} {}", target, level);
+            }
+        }
+
+        assert_eq!(
+            filters.to_string(),
+            "info,foo=warn,bar=only:debug,baz=trace..info"
+        );
+    }
+
+    #[test]
+    fn query() {
+        let filters = Filters::parse("debug,foo=warn..error,-bar,baz=info");
+        assert_eq!(filters.minimum(), Some(log::LevelFilter::Debug));
+        assert_eq!(
+            filters.directives().collect::<Vec<_>>(),
+            [
+                ("foo", log::LevelFilter::Warn),
+                ("bar", log::LevelFilter::Off),
+                ("baz", log::LevelFilter::Info)
+            ]
+        );
+
+        let filters = Filters::parse("off");
+        assert_eq!(filters.minimum(), None);
+        assert_eq!(filters.directives().count(), 0);
+    }
+
+    #[test]
+    fn presets() {
+        assert_eq!(Preset::Quiet.directives(), "warn");
+        assert_eq!(Preset::Normal.directives(), "info");
+
+        let verbose = Filters::preset(Preset::Verbose);
+        assert_eq!(
+            verbose.find_module("my_crate"),
+            Some(log::LevelFilter::Debug)
+        );
+        assert_eq!(
+            verbose.find_module("hyper::proto"),
+            Some(log::LevelFilter::Info)
+        );
+
+        let debugging = Filters::preset(Preset::Debugging);
+        assert_eq!(
+            debugging.find_module("my_crate"),
+            Some(log::LevelFilter::Trace)
+        );
+        assert_eq!(
+            debugging.find_module("h2::codec"),
+            Some(log::LevelFilter::Debug)
+        );
+
+        // everything verbose quiets is quieted when debugging too
+        for (module, _) in Preset::Verbose
+            .directives()
+            .split(',')
+            .filter_map(|directive| directive.split_once('='))
+        {
+            assert_eq!(
+                debugging.find_module(module),
+                Some(log::LevelFilter::Debug),
+                "{}",
+                module
+            );
+        }
+    }
+
+    #[test]
+    fn from_file() {
+        let path =
+            std::env::temp_dir().join(format!("alto_logger_filters_{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "level = \"info\"\nwatch = true\n[modules]\nfoo = \"trace\"\n",
+        )
+        .unwrap();
+
+        let filters = Filters::from_file(&path).unwrap();
+        assert_eq!(filters.to_string(), "info,foo=trace");
+        assert_eq!(filters.watch.as_deref(), Some(&*path));
+
+        let shared = SharedFilters::from_options(&crate::Options::default().with_filters(filters));
+        std::fs::write(&path, "level = \"warn\"\n").unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while shared.to_string() != "warn" && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        assert_eq!(shared.to_string(), "warn");
+
+        // dropping the filters stops watching the file
+        let stopped = crate::sync::lock(&shared.0.watcher)
+            .as_ref()
+            .map(crate::PollHandle::stopped)
+            .unwrap();
+        drop(shared);
+        // the thread could've been holding onto them, and drops them itself
+        while !stopped.load(Ordering::SeqCst) && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(stopped.load(Ordering::SeqCst));
+
+        std::fs::write(&path, "level = \"verbose\"\n").unwrap();
+        assert!(Filters::from_file(&path).unwrap_err().is_invalid_filter());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(Filters::from_file(&path).unwrap_err().is_io());
+    }
+
+    #[test]
+    fn builder() {
+        use log::{LevelFilter, Log as _};
+
+        let filters = Filters::builder()
+            .default(LevelFilter::Debug)
+            .module("foo::bar", LevelFilter::Off)
+            .module("foo", LevelFilter::Trace)
+            .module(String::from("foo"), LevelFilter::Info)
+            .build();
+        assert_eq!(filters.to_string(), "debug,foo::bar=off,foo=info");
+        assert_eq!(
+            Filters::builder().build().to_string(),
+            Filters::default().to_string()
+        );
+
+        let logger =
+            crate::TermLogger::new(crate::Options::default().with_filters(filters)).unwrap();
+        let enabled = |target, level| {
+            logger.enabled(&log::Metadata::builder().target(target).level(level).build())
+        };
+        assert!(enabled("foo", log::Level::Info));
+        assert!(!enabled("foo", log::Level::Debug));
+        assert!(!enabled("foo::bar", log::Level::Error));
+        assert!(enabled("other", log::Level::Debug));
+    }
+
+    #[test]
+    fn display() {
+        let filters = Filters::from_str("debug,foo::bar=off,foo=INFO");
+        assert_eq!(filters.to_string(), "debug,foo::bar=off,foo=info");
+        assert_eq!(Filters::from_str("").to_string(), "off");
+        assert_eq!(Filters::from_str("warn").to_string(), "warn");
+    }
+
+    #[test]
+    fn pathological() {
+        let directives = (0..100_000)
+            .map(|i| format!("m{}=trace", i % 50_000))
+            .collect::<Vec<_>>()
+            .join(",");
+        let filters = Filters::from_str(&directives);
+        assert_eq!(filters.directives().count(), 50_000);
+
+        let deep = "a::".repeat(100_000) + "b";
+        assert_eq!(filters.find_module(&deep), None);
+        assert_eq!(
+            Filters::from_str("a=warn").find_module(&deep),
+            Some(log::LevelFilter::Warn)
+        );
+
+        crate::fuzz::filters(&directives[..1000]);
+        crate::fuzz::filters(&deep);
+        crate::fuzz::layout(&"{".repeat(10_000), &deep);
+    }
+
     #[test]
     fn minimum() {
         let filters =
@@ -155,4 +1308,32 @@ mod tests {
             assert_eq!(filters.find_module(module).unwrap(), *expected);
         }
     }
+
+    // `RUSTFLAGS="--cfg loom" cargo test --lib model`
+    #[cfg(loom)]
+    #[test]
+    fn replace_model() {
+        loom::model(|| {
+            let filters = SharedFilters::new(Filters::parse("info"));
+            let replacing = loom::thread::spawn({
+                let filters = filters.clone();
+                move || filters.replace(Filters::parse("debug"))
+            });
+            let muting = loom::thread::spawn({
+                let filters = filters.clone();
+                move || filters.mute("noisy")
+            });
+
+            // the generation is incremented before the new filters can be seen
+            if filters.is_target_enabled("my_app", log::Level::Debug) {
+                assert_eq!(filters.generation(), 1);
+            }
+            assert!(filters.is_target_enabled("my_app", log::Level::Info));
+
+            assert_eq!(replacing.join().unwrap().to_string(), "info");
+            assert!(muting.join().unwrap());
+            assert!(!filters.is_target_enabled("noisy", log::Level::Error));
+            assert!(filters.is_target_enabled("my_app", log::Level::Debug));
+        });
+    }
 }
@@ -0,0 +1,139 @@
+use super::{join_lines, LevelRange};
+
+/// The configuration read from a filters file, see [`Filters::from_file`](../struct.Filters.html#method.from_file)
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct FileConfig {
+    pub(crate) directives: String,
+    pub(crate) watch: bool,
+}
+
+/// Read a filters file, as `key = "value"` lines when it has a `.toml` extension, otherwise as directives separated by commas or newlines
+///
+/// For `key = "value"` lines, the line that couldn't be understood is returned as the error.
+pub(crate) fn parse(path: &std::path::Path, data: &str) -> Result<FileConfig, String> {
+    match path.extension().is_some_and(|ext| ext == "toml") {
+        true => parse_toml(data),
+        false => Ok(FileConfig {
+            directives: join_lines(data),
+            watch: false,
+        }),
+    }
+}
+
+/// Parse the `key = "value"` lines used for filters, which look like TOML but aren't parsed as it:
+///
+/// ```toml
+/// level = "info"
+/// watch = true
+///
+/// [modules]
+/// hyper = "warn"
+/// "my_app::db" = "trace"
+/// ```
+fn parse_toml(data: &str) -> Result<FileConfig, String> {
+    let mut level = None;
+    let mut watch = false;
+    let mut modules = vec![];
+    let mut in_modules = false;
+
+    for line in data.lines() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(table) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            in_modules = match table.trim() {
+                "modules" => true,
+                _ => return Err(line.to_string()),
+            };
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| line.to_string())?;
+        let (key, value) = (unquote(key.trim()), value.trim());
+        match (in_modules, key) {
+            (true, module) if !module.is_empty() => {
+                let value = level_value(value).ok_or_else(|| line.to_string())?;
+                modules.push(format!("{}={}", module, value));
+            }
+            (false, "level") => level = Some(level_value(value).ok_or_else(|| line.to_string())?),
+            (false, "watch") => {
+                watch = match value {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(line.to_string()),
+                }
+            }
+            _ => return Err(line.to_string()),
+        }
+    }
+
+    let directives = level.into_iter().chain(modules).collect::<Vec<_>>();
+    Ok(FileConfig {
+        directives: directives.join(","),
+        watch,
+    })
+}
+
+/// A level, or a range of levels, as a string or a bare word
+fn level_value(value: &str) -> Option<String> {
+    let value = unquote(value);
+    LevelRange::parse(value)?;
+    Some(value.to_string())
+}
+
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+/// Remove a trailing `# comment`, which can't be inside of a string
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml() {
+        let data = r#"
+# the default level
+level = "info"
+watch = true # re-read it
+
+[modules]
+hyper = warn
+"my_app::db" = "trace..debug"
+"#;
+        assert_eq!(
+            parse_toml(data),
+            Ok(FileConfig {
+                directives: String::from("info,hyper=warn,my_app::db=trace..debug"),
+                watch: true,
+            })
+        );
+        assert_eq!(parse_toml(""), Ok(FileConfig::default()));
+
+        for invalid in [
+            "level = \"verbose\"",
+            "watch = yes",
+            "colors = true",
+            "[loggers]",
+            "[modules]\nhyper",
+        ] {
+            assert!(parse_toml(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+/*! Display adapters for writing values in a human-friendly way
+
+```rust
+# use std::time::Duration;
+use alto_logger::fmt;
+log::info!("downloaded {} in {}", fmt::bytes(3_565_158), fmt::duration(Duration::from_millis(120)));
+// downloaded 3.4 MiB in 120ms
+```
+*/
+
+use std::time::Duration;
+
+/// Display a number of bytes with binary units, e.g. `512 B`, `3.4 MiB`
+pub const fn bytes(n: u64) -> Bytes {
+    Bytes(n)
+}
+
+/// Display a duration with the largest fitting unit, e.g. `850ns`, `120ms`, `1.5s`, `2m 5s`, `1h 30m`
+pub const fn duration(d: Duration) -> HumanDuration {
+    HumanDuration(d)
+}
+
+/// A number of bytes. See [`bytes`](fn.bytes.html)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bytes(pub u64);
+
+impl std::fmt::Display for Bytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        write!(f, "{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// A duration. See [`duration`](fn.duration.html)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HumanDuration(pub Duration);
+
+impl std::fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let d = self.0;
+        let secs = d.as_secs();
+        match secs {
+            0 if d.as_micros() == 0 => write!(f, "{}ns", d.as_nanos()),
+            0 if d.as_millis() == 0 => write!(f, "{}µs", d.as_micros()),
+            0 => write!(f, "{}ms", d.as_millis()),
+            1..=59 => write!(f, "{}.{}s", secs, d.subsec_millis() / 100),
+            60..=3599 => write!(f, "{}m {}s", secs / 60, secs % 60),
+            _ => write!(f, "{}h {}m", secs / 3600, secs % 3600 / 60),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        let cases = [
+            (0, "0 B"),
+            (1023, "1023 B"),
+            (1024, "1.0 KiB"),
+            (3_565_158, "3.4 MiB"),
+            (5 << 30, "5.0 GiB"),
+            (u64::MAX, "16.0 EiB"),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(bytes(n).to_string(), expected);
+        }
+
+        let cases = [
+            (Duration::from_nanos(850), "850ns"),
+            (Duration::from_micros(42), "42µs"),
+            (Duration::from_millis(120), "120ms"),
+            (Duration::from_millis(1500), "1.5s"),
+            (Duration::from_millis(59_990), "59.9s"),
+            (Duration::from_secs(125), "2m 5s"),
+            (Duration::from_secs(5400), "1h 30m"),
+        ];
+        for (d, expected) in cases {
+            assert_eq!(duration(d).to_string(), expected);
+        }
+    }
+}
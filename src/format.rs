@@ -0,0 +1,266 @@
+use crate::{
+    date::DateTime,
+    options::{ColorDepth, LevelCase, Options},
+    record::collect_kv,
+    render::Renderer,
+};
+use std::{fmt::Write as _, time::SystemTime};
+
+/// How a [`Logger`](struct.Logger.html) formats each record, as a line
+///
+/// ```rust
+/// # use alto_logger::{Format, options::StyleConfig};
+/// let text = Format::text(StyleConfig::SingleLine);
+/// let json = Format::json();
+/// let logfmt = Format::logfmt();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Format {
+    kind: FormatKind,
+    level_case: LevelCase,
+}
+
+#[derive(Clone, Debug)]
+enum FormatKind {
+    Text(Box<Options>),
+    Json,
+    PrettyJson(&'static str),
+    Logfmt,
+}
+
+impl Format {
+    /// The same text as the other loggers, without colors
+    pub fn text(options: impl Into<Options>) -> Self {
+        Self {
+            kind: FormatKind::Text(Box::new(options.into())),
+            level_case: LevelCase::Upper,
+        }
+    }
+
+    /// The version of the fields written by [`json`](#method.json) and [`pretty_json`](#method.pretty_json)
+    ///
+    /// Within a major version of this crate, these fields are only ever added to, and the version is
+    /// incremented if one is removed or changes its meaning:
+    ///
+    /// | field            | value                                                  |
+    /// | ---------------- | ------------------------------------------------------ |
+    /// | `schema_version` | this version, as a number                              |
+    /// | `time`           | the UTC time of the record, as an RFC 3339 string      |
+    /// | `level`          | `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`            |
+    /// | `target`         | the target of the record                               |
+    /// | `message`        | the formatted message                                  |
+    /// | `fields`         | the key-values as strings, only if the record has some |
+    ///
+    /// The case of `level` can be changed with [`with_level_case`](#method.with_level_case).
+    pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+    /// A JSON object per line, e.g. `{"schema_version":1,"time":"2024-05-01T13:59:59Z","level":"INFO","target":"my_app","message":"hello"}`
+    ///
+    /// Any key-values are included as strings in a `fields` object.
+    /// See [`JSON_SCHEMA_VERSION`](#associatedconstant.JSON_SCHEMA_VERSION) for the fields that can be relied on.
+    pub const fn json() -> Self {
+        Self {
+            kind: FormatKind::Json,
+            level_case: LevelCase::Upper,
+        }
+    }
+
+    /// An indented JSON object per record, followed by `delimiter`, for reading structured records by eye
+    ///
+    /// The fields are the same as [`json`](#method.json). e.g. with a `delimiter` of `"\n"`,
+    /// there is an empty line between records:
+    /// ```text
+    /// {
+    ///   "schema_version": 1,
+    ///   "time": "2024-05-01T13:59:59Z",
+    ///   "level": "INFO",
+    ///   "target": "my_app",
+    ///   "message": "hello",
+    ///   "fields": {
+    ///     "user": "alice"
+    ///   }
+    /// }
+    ///
+    /// ```
+    pub const fn pretty_json(delimiter: &'static str) -> Self {
+        Self {
+            kind: FormatKind::PrettyJson(delimiter),
+            level_case: LevelCase::Upper,
+        }
+    }
+
+    /// `key=value` pairs per line, e.g. `time=2024-05-01T13:59:59Z level=info target=my_app msg="hello world"`
+    ///
+    /// Any key-values are added after the message.
+    pub const fn logfmt() -> Self {
+        Self {
+            kind: FormatKind::Logfmt,
+            level_case: LevelCase::Lower,
+        }
+    }
+
+    /// Write the level in this `LevelCase`
+    ///
+    /// For [`text`](#method.text), this replaces the level case of its options.
+    ///
+    /// ```rust
+    /// # use alto_logger::{Format, options::LevelCase};
+    /// let logfmt = Format::logfmt().with_level_case(LevelCase::Upper);
+    /// ```
+    ///
+    /// ***Note*** Defaults to `Upper` for JSON and `Lower` for logfmt
+    pub fn with_level_case(mut self, level_case: LevelCase) -> Self {
+        if let FormatKind::Text(options) = &mut self.kind {
+            options.level_case = level_case;
+        }
+        self.level_case = level_case;
+        self
+    }
+
+    /// Whether this renders records with its own [`Options`](struct.Options.html)
+    pub(crate) const fn is_text(&self) -> bool {
+        matches!(self.kind, FormatKind::Text(..))
+    }
+
+    /// Write this record to `out`, including the trailing newline
+    pub(crate) fn write(&self, record: &log::Record<'_>, out: &mut Vec<u8>) {
+        let time = || {
+            let secs = crate::record::replayed_time()
+                .unwrap_or_else(SystemTime::now)
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            DateTime::from_unix(secs).rfc3339()
+        };
+
+        let line = match &self.kind {
+            FormatKind::Text(options) => {
+                Renderer::new(options, ColorDepth::TrueColor)
+                    .render(record, &mut termcolor::NoColor::new(out));
+                return;
+            }
+
+            FormatKind::Json | FormatKind::PrettyJson(..) => {
+                let pretty = matches!(self.kind, FormatKind::PrettyJson(..));
+                let (open, sep, colon, close) = match pretty {
+                    true => ("{\n  ", ",\n  ", ": ", "\n}"),
+                    false => ("{", ",", ":", "}"),
+                };
+
+                let mut line = String::from(open);
+                let _ = write!(
+                    line,
+                    r#""schema_version"{colon}{}{sep}"time"{colon}"{}"{sep}"level"{colon}"{}"{sep}"target"{colon}{}{sep}"message"{colon}{}"#,
+                    Self::JSON_SCHEMA_VERSION,
+                    time(),
+                    self.level_case.apply(record.level()),
+                    json_string(record.target()),
+                    json_string(&record.args().to_string())
+                );
+                let kv = collect_kv(record.key_values());
+                if !kv.is_empty() {
+                    let _ = write!(line, r#"{sep}"fields"{colon}{{"#);
+                    for (i, (key, value)) in kv.iter().enumerate() {
+                        line.push_str(match (pretty, i) {
+                            (true, 0) => "\n    ",
+                            (true, _) => ",\n    ",
+                            (false, 0) => "",
+                            (false, _) => ",",
+                        });
+                        let _ = write!(line, "{}{colon}{}", json_string(key), json_string(value));
+                    }
+                    line.push_str(match pretty {
+                        true => "\n  }",
+                        false => "}",
+                    });
+                }
+                line.push_str(close);
+                line
+            }
+
+            FormatKind::Logfmt => {
+                let mut line = format!(
+                    "time={} level={} target={} msg={}",
+                    time(),
+                    self.level_case.apply(record.level()),
+                    logfmt_value(record.target()),
+                    logfmt_value(&record.args().to_string())
+                );
+                for (key, value) in collect_kv(record.key_values()) {
+                    let _ = write!(line, " {}={}", key, logfmt_value(&value));
+                }
+                line
+            }
+        };
+
+        out.extend_from_slice(line.as_bytes());
+        out.push(b'\n');
+        if let FormatKind::PrettyJson(delimiter) = self.kind {
+            out.extend_from_slice(delimiter.as_bytes());
+        }
+    }
+}
+
+/// Quote and escape `s` as a JSON string
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str(r#"\""#),
+            '\\' => out.push_str(r"\\"),
+            '\n' => out.push_str(r"\n"),
+            '\r' => out.push_str(r"\r"),
+            '\t' => out.push_str(r"\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Quote and escape `s` as a logfmt value, if it needs to be
+fn logfmt_value(s: &str) -> String {
+    let quote = s.is_empty()
+        || s.chars()
+            .any(|c| matches!(c, '=' | '"' | '\\') || c.is_whitespace() || c.is_control());
+    match quote {
+        true => json_string(s),
+        false => s.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // the fields of each schema version can only be added to, so this should only change with the version
+    #[test]
+    fn json_schema() {
+        let kv = [("user", "alice")];
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .target("my_app")
+            .args(format_args!("hello"))
+            .key_values(&kv)
+            .build();
+
+        let mut out = Vec::new();
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_571_999);
+        crate::record::replaying(timestamp, None, || Format::json().write(&record, &mut out));
+
+        assert_eq!(Format::JSON_SCHEMA_VERSION, 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"schema_version":1,"time":"2024-05-01T13:59:59Z","level":"INFO","#,
+                r#""target":"my_app","message":"hello","fields":{"user":"alice"}}"#,
+                "\n"
+            )
+        );
+    }
+}
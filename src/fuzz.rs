@@ -0,0 +1,75 @@
+//! Entry points for fuzzing the parsers, e.g. with `cargo fuzz`
+//!
+//! These panic if an input breaks an invariant of the parser, and are not part of the public API.
+//!
+//! ```rust,ignore
+//! #![no_main]
+//! libfuzzer_sys::fuzz_target!(|data: &str| alto_logger::fuzz::filters(data));
+//! ```
+use crate::{
+    layout::{self, FixedClock},
+    Filters, Options,
+};
+use std::time::{Instant, SystemTime};
+
+/// Parse filter directives, and look up some targets with them
+pub fn filters(input: &str) {
+    let filters = Filters::parse(input);
+    if let Ok(strict) = Filters::try_parse(input) {
+        assert_eq!(strict.to_string(), filters.to_string());
+    }
+
+    let targets = input.split([',', '=']).chain(["", "::", "a::b::c"]);
+    for target in targets {
+        let enabled = filters.find_module(target);
+        for level in [log::Level::Error, log::Level::Trace] {
+            if filters.is_target_enabled(target, level) {
+                assert!(enabled.is_some_and(|max| level <= max));
+            }
+        }
+    }
+
+    let _ = Filters::parse(&filters.to_string());
+}
+
+/// Render a record with this message and target, in each style
+///
+/// This covers splitting the span context that `tracing` puts before a message.
+pub fn layout(message: &str, target: &str) {
+    if let Some((spans, rest)) = layout::split_spans(message) {
+        assert!(message.len() >= spans.len() + rest.len());
+    }
+
+    let clock = FixedClock(Instant::now(), SystemTime::now());
+    for style in [
+        crate::StyleConfig::SingleLine,
+        crate::StyleConfig::MultiLine,
+        crate::StyleConfig::Aligned {
+            target_width: 8,
+            level_width: 3,
+        },
+    ] {
+        let options = Options::default().with_style(style);
+        let mut out = String::new();
+        layout::render(
+            &options,
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target(target)
+                .args(format_args!("{}", message))
+                .build(),
+            &clock,
+            &mut out,
+        );
+        assert!(out.ends_with('\n'));
+    }
+}
+
+/// Parse a `time` format description, and format a timestamp with it
+#[cfg(feature = "time")]
+pub fn time_format(input: &str) {
+    #[allow(deprecated)]
+    if let Ok(items) = time::format_description::parse(input) {
+        let _ = time::OffsetDateTime::UNIX_EPOCH.format(&items);
+    }
+}
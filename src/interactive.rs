@@ -0,0 +1,153 @@
+use crate::{Filters, Preset, ReloadHandle};
+use std::io::BufRead;
+
+/// The presets cycled through, from the quietest
+const PRESETS: [Preset; 4] = [
+    Preset::Quiet,
+    Preset::Normal,
+    Preset::Verbose,
+    Preset::Debugging,
+];
+
+impl ReloadHandle {
+    /// Cycle through the verbosity [`Preset`](enum.Preset.html)s whenever `key` is pressed, on a background thread
+    ///
+    /// Each time, the preset after the one the filters match is used, going from `Normal` to `Verbose`, `Debugging`,
+    /// `Quiet` and back to `Normal`. Filters which don't match a preset go to `Verbose`, as if they were `Normal`.
+    ///
+    /// On unix, if stdin is a terminal, it is switched out of its line-buffered mode and stops echoing,
+    /// so the key doesn't need to be followed by `Enter`. It is switched back when stdin is closed, or the process exits.
+    ///
+    /// ***Note*** Other keys are ignored, and stdin shouldn't be read by anything else.
+    /// Elsewhere, the terminal is left as it is, so the key has to be followed by `Enter`.
+    ///
+    /// ```rust,no_run
+    /// # use alto_logger::TermLogger;
+    /// let logger = TermLogger::default();
+    /// logger.reload_handle().cycle_on_key('v');
+    /// logger.init().unwrap();
+    /// ```
+    pub fn cycle_on_key(&self, key: char) -> std::thread::JoinHandle<()> {
+        let this = self.clone();
+        std::thread::Builder::new()
+            .name(String::from("alto_logger interactive"))
+            .spawn(move || {
+                #[cfg(unix)]
+                raw::enable();
+                this.cycle_from(std::io::stdin().lock(), key);
+                #[cfg(unix)]
+                raw::restore();
+            })
+            .expect("spawn interactive thread")
+    }
+
+    fn cycle_from(&self, input: impl BufRead, key: char) {
+        let mut pending = Vec::with_capacity(4);
+        for byte in input.bytes().map_while(Result::ok) {
+            pending.push(byte);
+            let ch = match std::str::from_utf8(&pending) {
+                Ok(s) => s.chars().next(),
+                // the rest of the character hasn't been read yet
+                Err(err) if err.error_len().is_none() => continue,
+                Err(..) => None,
+            };
+            pending.clear();
+
+            if ch == Some(key) {
+                self.cycle(key)
+            }
+        }
+    }
+
+    fn cycle(&self, key: char) {
+        let current = self.directives();
+        let index = PRESETS
+            .iter()
+            .position(|&preset| Filters::preset(preset).to_string() == current)
+            .unwrap_or(1);
+
+        let preset = PRESETS[(index + 1) % PRESETS.len()];
+        self.replace(
+            Filters::preset(preset),
+            format!("key '{}' ({:?})", key, preset),
+        );
+    }
+}
+
+#[cfg(unix)]
+mod raw {
+    use crate::sync::Mutex;
+
+    // the terminal settings from before `enable`, while they are changed
+    crate::sync::static_lock! {
+        static SAVED: Mutex<Option<libc::termios>> = Mutex::new(None);
+    }
+
+    /// Turn off line buffering and echoing for stdin, if it is a terminal
+    pub(super) fn enable() {
+        extern "C" fn restore_at_exit() {
+            restore()
+        }
+
+        let mut saved = crate::sync::lock(&SAVED);
+        if saved.is_some() {
+            return;
+        }
+
+        // SAFETY: `termios` is plain data, which `tcgetattr` fills in before it is read
+        unsafe {
+            let mut termios = std::mem::zeroed::<libc::termios>();
+            if libc::isatty(libc::STDIN_FILENO) != 1
+                || libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0
+            {
+                return;
+            }
+
+            let mut raw = termios;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return;
+            }
+
+            // a second `enable` after a `restore` registers it again, which is harmless
+            libc::atexit(restore_at_exit);
+            saved.replace(termios);
+        }
+    }
+
+    /// Restore the terminal settings from before `enable`
+    pub(super) fn restore() {
+        if let Some(termios) = crate::sync::lock(&SAVED).take() {
+            // SAFETY: `termios` came from `tcgetattr`
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::SharedFilters;
+
+    #[test]
+    fn cycle() {
+        let handle = ReloadHandle::new(SharedFilters::default());
+        let (tx, rx) = std::sync::mpsc::channel();
+        handle.on_change(move |change| tx.send(change.new.clone()).unwrap());
+
+        handle.cycle_from(&b"vx\n vv\xffv"[..], 'v');
+        let changes = rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(changes.len(), 4);
+        assert!(changes[0].starts_with("debug,"));
+        assert!(changes[1].starts_with("trace,"));
+        assert_eq!(changes[2], "warn");
+        assert_eq!(changes[3], "info");
+
+        // it continues from the preset the filters match
+        handle.set_filters(Filters::preset(Preset::Quiet));
+        handle.cycle_from("é".as_bytes(), 'é');
+        assert_eq!(rx.try_iter().last().unwrap(), "info");
+    }
+}
@@ -0,0 +1,192 @@
+use crate::fmt::HumanDuration;
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+// values below 8ns get their own bucket, then each power of two is split into 8 buckets
+const SUB_BUCKETS: usize = 8;
+const BUCKETS: usize = (64 - 2) * SUB_BUCKETS;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static HISTOGRAM: Histogram = Histogram::new();
+
+/// Measure the time spent in the installed logger for each record
+///
+/// This is meant for diagnosing whether logging itself is a bottleneck, and adds a little overhead to each record.
+/// See [`latency_summary`](fn.latency_summary.html) and [`report_latency_every`](fn.report_latency_every.html)
+pub fn set_latency_tracking(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst)
+}
+
+/// Get a summary of the time spent logging each record, since tracking was enabled or the last summary
+///
+/// The percentiles are accurate to within 12.5%.
+///
+/// ```rust
+/// alto_logger::init_term_logger().unwrap();
+/// alto_logger::set_latency_tracking(true);
+/// log::info!("hello");
+///
+/// let summary = alto_logger::latency_summary();
+/// assert!(summary.p50 <= summary.p99);
+/// ```
+pub fn latency_summary() -> LatencySummary {
+    HISTOGRAM.take()
+}
+
+/// Enable latency tracking, and log a [`LatencySummary`](struct.LatencySummary.html) every `interval`
+///
+/// The summary is logged at `INFO` with the target `alto_logger::latency`, unless no records were logged.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// alto_logger::init_term_logger().unwrap();
+/// alto_logger::report_latency_every(Duration::from_secs(60));
+/// ```
+pub fn report_latency_every(interval: Duration) -> std::thread::JoinHandle<()> {
+    set_latency_tracking(true);
+    std::thread::Builder::new()
+        .name(String::from("alto_logger latency"))
+        .spawn(move || loop {
+            std::thread::sleep(interval);
+            let summary = latency_summary();
+            if summary.count > 0 {
+                log::info!(target: "alto_logger::latency", "{}", summary);
+            }
+        })
+        .expect("spawn latency thread")
+}
+
+/// Time `f`, if latency tracking is enabled
+#[inline]
+pub(crate) fn measure<T>(f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+    let start = Instant::now();
+    let out = f();
+    HISTOGRAM.record(start.elapsed());
+    out
+}
+
+/// The time spent logging each record. See [`latency_summary`](fn.latency_summary.html)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LatencySummary {
+    /// How many records were measured
+    pub count: u64,
+    /// The median time
+    pub p50: Duration,
+    /// The 99th percentile time
+    pub p99: Duration,
+    /// The longest time
+    pub max: Duration,
+}
+
+impl std::fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "records={} p50={} p99={} max={}",
+            self.count,
+            HumanDuration(self.p50),
+            HumanDuration(self.p99),
+            HumanDuration(self.max)
+        )
+    }
+}
+
+/// A histogram of nanoseconds, with buckets that grow exponentially
+struct Histogram {
+    buckets: [AtomicU64; BUCKETS],
+    max: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicU64::new(0) }; BUCKETS],
+            max: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.buckets[index(nanos)].fetch_add(1, Ordering::Relaxed);
+        self.max.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    fn take(&self) -> LatencySummary {
+        let counts = self
+            .buckets
+            .each_ref()
+            .map(|bucket| bucket.swap(0, Ordering::Relaxed));
+        let max = self.max.swap(0, Ordering::Relaxed);
+
+        let count = counts.iter().sum::<u64>();
+        let percentile = |q: u64| {
+            let rank = (count * q).div_ceil(100).max(1);
+            let mut seen = 0;
+            counts
+                .iter()
+                .position(|&n| {
+                    seen += n;
+                    seen >= rank
+                })
+                .map_or(0, |index| upper_bound(index).min(max))
+        };
+
+        LatencySummary {
+            count,
+            p50: Duration::from_nanos(percentile(50)),
+            p99: Duration::from_nanos(percentile(99)),
+            max: Duration::from_nanos(max),
+        }
+    }
+}
+
+fn index(nanos: u64) -> usize {
+    if nanos < SUB_BUCKETS as u64 {
+        return nanos as usize;
+    }
+    let exp = 63 - nanos.leading_zeros() as usize;
+    let sub = (nanos >> (exp - 3)) as usize & (SUB_BUCKETS - 1);
+    (exp - 2) * SUB_BUCKETS + sub
+}
+
+/// The largest value in the bucket
+fn upper_bound(index: usize) -> u64 {
+    if index < SUB_BUCKETS {
+        return index as u64;
+    }
+    let (exp, sub) = (index / SUB_BUCKETS + 2, (index % SUB_BUCKETS) as u64);
+    let width = 1u64 << (exp - 3);
+    ((SUB_BUCKETS as u64 + sub) * width).saturating_add(width - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram() {
+        for nanos in [0, 7, 8, 9, 100, 1_000, 123_456_789, u64::MAX] {
+            let index = index(nanos);
+            assert!(upper_bound(index) >= nanos);
+            assert!(index == 0 || upper_bound(index - 1) < nanos);
+        }
+
+        let histogram = Histogram::new();
+        for micros in 1..=100 {
+            histogram.record(Duration::from_micros(micros));
+        }
+        let summary = histogram.take();
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.max, Duration::from_micros(100));
+        assert!((50_000..=56_250).contains(&summary.p50.as_nanos()));
+        assert!((99_000..=100_000).contains(&summary.p99.as_nanos()));
+
+        assert_eq!(histogram.take(), LatencySummary::default());
+    }
+}
@@ -0,0 +1,1169 @@
+/*! The layout of a record, without any terminal or I/O dependencies
+
+The loggers render records through this module, with writers that turn each [`Element`](enum.Element.html)
+into colors. Other environments can reuse the same layout by implementing [`LayoutWriter`](trait.LayoutWriter.html),
+and a [`Clock`](trait.Clock.html) if the system clock isn't available.
+
+```rust
+# use alto_logger::{layout, options::StyleConfig, Options};
+let options = Options::default().with_style(StyleConfig::SingleLine);
+let record = log::Record::builder()
+    .level(log::Level::Info)
+    .target("my_app")
+    .args(format_args!("hello"))
+    .build();
+
+let mut out = String::new();
+layout::render(&options, &record, &layout::SystemClock, &mut out);
+assert_eq!(out, "INFO  [my_app] hello\n");
+```
+*/
+
+use crate::options::{Options, StyleConfig, TimeConfig};
+use core::fmt::Write;
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        PoisonError,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+// the lock of `TimeConfig::Timing`
+#[cfg(loom)]
+use loom::sync::Mutex;
+#[cfg(not(loom))]
+use std::sync::Mutex;
+
+/// A part of a record, which a [`LayoutWriter`](trait.LayoutWriter.html) may style
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Element {
+    /// The level of the record
+    Level(log::Level),
+    /// The timestamp
+    Timestamp,
+    /// The target of the record
+    Target,
+    /// The thread rendering the record
+    Thread,
+    /// The source file and line of the record
+    Source,
+    /// The marker before the message of a `MultiLine` record
+    Continuation,
+    /// The span context of a record forwarded from `tracing`
+    Span,
+    /// The message of the record
+    Message,
+    /// The placeholder written when formatting the message panicked
+    Panic,
+}
+
+/// A writer for the layout of a record
+///
+/// The text of an element is written between `begin` and `end`. Writers without styling can ignore them.
+pub trait LayoutWriter: Write {
+    /// Start writing this element
+    fn begin(&mut self, element: Element) {
+        let _ = element;
+    }
+
+    /// Stop writing the current element
+    fn end(&mut self) {}
+}
+
+impl LayoutWriter for String {}
+
+impl LayoutWriter for core::fmt::Formatter<'_> {}
+
+/// The source of time for timestamps
+pub trait Clock {
+    /// The current wall-clock time
+    fn system_time(&self) -> SystemTime;
+    /// The current monotonic time
+    fn instant(&self) -> Instant;
+}
+
+/// The [`Clock`](trait.Clock.html) of the operating system
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A [`Clock`](trait.Clock.html) which is stopped at this `Instant` and `SystemTime`
+///
+/// This makes rendering deterministic, e.g. for tests and benchmarks.
+#[derive(Copy, Clone, Debug)]
+pub struct FixedClock(pub Instant, pub SystemTime);
+
+impl Clock for FixedClock {
+    fn system_time(&self) -> SystemTime {
+        self.1
+    }
+
+    fn instant(&self) -> Instant {
+        self.0
+    }
+}
+
+/// Write this `record` with the layout described by these `options`, including the trailing newline
+pub fn render(
+    options: &Options,
+    record: &log::Record<'_>,
+    clock: &dyn Clock,
+    out: &mut impl LayoutWriter,
+) {
+    let layout = Layout {
+        options,
+        clock,
+        origin: origin_time(options, record),
+    };
+    if options.priority_prefix {
+        let prefix = match record.level() {
+            log::Level::Error => "<3>",
+            log::Level::Warn => "<4>",
+            log::Level::Info => "<6>",
+            log::Level::Debug | log::Level::Trace => "<7>",
+        };
+        return layout.render_continued(record, &mut LinePrefix::new(out, prefix));
+    }
+
+    layout.render_continued(record, out)
+}
+
+struct Layout<'a> {
+    options: &'a Options,
+    clock: &'a dyn Clock,
+    // the time the record carries in its key-values, if it should be used
+    origin: Option<SystemTime>,
+}
+
+impl Layout<'_> {
+    /// The wall-clock time for a timestamp, clamped to the latest one rendered if the timestamps are monotonic
+    fn system_time(&self) -> SystemTime {
+        // nanoseconds since the epoch of the latest wall-clock timestamp
+        static LATEST: AtomicU64 = AtomicU64::new(0);
+
+        if let Some(origin) = self.origin {
+            return origin;
+        }
+
+        let now = self.clock.system_time();
+        if !self.options.monotonic_timestamps {
+            return now;
+        }
+
+        let nanos = now
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        let latest = LATEST.fetch_max(nanos, Ordering::Relaxed).max(nanos);
+        std::time::UNIX_EPOCH + Duration::from_nanos(latest)
+    }
+
+    /// The monotonic time of the record, which is earlier than now for a record being replayed
+    fn instant(&self) -> Instant {
+        let now = self.clock.instant();
+        let ago = self
+            .origin
+            .and_then(|origin| self.clock.system_time().duration_since(origin).ok());
+        match ago {
+            Some(ago) => now.checked_sub(ago).unwrap_or(now),
+            None => now,
+        }
+    }
+
+    fn render_continued(&self, record: &log::Record<'_>, out: &mut impl LayoutWriter) {
+        match self.options.continuation_marker {
+            Some(marker) => self.render_fields(record, &mut LinePrefix::continuation(out, marker)),
+            None => self.render_fields(record, out),
+        }
+    }
+
+    fn render_fields(&self, record: &log::Record<'_>, out: &mut impl LayoutWriter) {
+        let fields = self.options.fields;
+        let out = &mut Column {
+            inner: out,
+            column: 0,
+        };
+
+        // the fields after the first start with a space, which isn't wanted at the start of the line
+        let out = &mut SkipSpace {
+            inner: out,
+            skip: true,
+        };
+        let time_first = fields.time && fields.time_first;
+        if time_first {
+            self.render_timestamp(out);
+        }
+        if fields.level {
+            let _ = out.write_str(" ");
+            self.render_level(record, out);
+        }
+        if fields.time && !time_first {
+            self.render_timestamp(out);
+        }
+        if fields.target {
+            self.render_target(record, out);
+        }
+        if fields.thread {
+            self.render_thread(out);
+        }
+        if fields.source {
+            self.render_source(record, out);
+        }
+        match fields.message {
+            true => {
+                let header =
+                    fields.level || fields.time || fields.target || fields.thread || fields.source;
+                self.render_payload(record, header, out)
+            }
+            false => {
+                let _ = writeln!(out);
+            }
+        }
+    }
+
+    fn render_level(&self, record: &log::Record<'_>, out: &mut impl LayoutWriter) {
+        let level = self.options.level_case.apply(record.level());
+        out.begin(Element::Level(record.level()));
+        let _ = match self.options.style_for(record.level()) {
+            StyleConfig::Aligned { level_width, .. } => {
+                write!(out, "{:<1$}", fit(level, level_width, false), level_width)
+            }
+            _ => write!(out, "{:<5}", level),
+        };
+        out.end();
+    }
+
+    fn render_timestamp(&self, out: &mut impl LayoutWriter) {
+        match &self.options.time {
+            TimeConfig::None => {}
+
+            TimeConfig::Unix => {
+                // a clock before the epoch (e.g. a misconfigured or stepped clock) is clamped to it
+                let elapsed = self
+                    .system_time()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default();
+                out.begin(Element::Timestamp);
+                let _ = write!(out, " {:04}", elapsed.as_secs());
+                out.end();
+            }
+
+            TimeConfig::Relative(start) => {
+                let elapsed = self.instant().saturating_duration_since(*start);
+                out.begin(Element::Timestamp);
+                let _ = write!(
+                    out,
+                    " {:04}.{:09}s",
+                    elapsed.as_secs(),
+                    elapsed.subsec_nanos()
+                );
+                out.end();
+            }
+
+            TimeConfig::RelativeTo(start) => {
+                // a start in the future (e.g. from a skewed clock) is clamped to it
+                let elapsed = self
+                    .system_time()
+                    .duration_since(*start)
+                    .unwrap_or_default();
+                out.begin(Element::Timestamp);
+                let _ = write!(
+                    out,
+                    " {:04}.{:09}s",
+                    elapsed.as_secs(),
+                    elapsed.subsec_nanos()
+                );
+                out.end();
+            }
+
+            TimeConfig::MonotonicNanos(start) => {
+                let elapsed = self.instant().saturating_duration_since(*start);
+                out.begin(Element::Timestamp);
+                let _ = write!(out, " {}", elapsed.as_nanos());
+                out.end();
+            }
+
+            TimeConfig::Timing(previous) => {
+                let elapsed = lap(previous, self.instant());
+                out.begin(Element::Timestamp);
+                let _ = write!(
+                    out,
+                    " {:04}.{:09}s",
+                    elapsed.as_secs(),
+                    elapsed.subsec_nanos()
+                );
+                out.end();
+            }
+
+            #[cfg(feature = "time")]
+            TimeConfig::DateTime(format) => {
+                let now = time::OffsetDateTime::from(self.system_time());
+                if let Ok(now) = now.format(format) {
+                    out.begin(Element::Timestamp);
+                    let _ = write!(out, " {}", now);
+                    out.end();
+                }
+            }
+
+            #[cfg(feature = "time")]
+            TimeConfig::ParsedDateTime(format) => {
+                let now = time::OffsetDateTime::from(self.system_time());
+                if let Ok(now) = now.format(format) {
+                    out.begin(Element::Timestamp);
+                    let _ = write!(out, " {}", now);
+                    out.end();
+                }
+            }
+
+            #[cfg(feature = "time")]
+            TimeConfig::DateTimeIn(offset, format) => {
+                let now = time::OffsetDateTime::from(self.system_time()).to_offset(*offset);
+                if let Ok(now) = now.format(format) {
+                    out.begin(Element::Timestamp);
+                    let _ = write!(out, " {}", now);
+                    out.end();
+                }
+            }
+
+            #[cfg(feature = "time")]
+            TimeConfig::DateTimeUptime(start, format) => {
+                let now = time::OffsetDateTime::from(self.system_time());
+                let uptime = self.instant().saturating_duration_since(*start).as_secs();
+                if let Ok(now) = now.format(format) {
+                    out.begin(Element::Timestamp);
+                    let _ = write!(
+                        out,
+                        " {} (+{:02}:{:02}:{:02})",
+                        now,
+                        uptime / 3600,
+                        uptime / 60 % 60,
+                        uptime % 60
+                    );
+                    out.end();
+                }
+            }
+        }
+    }
+
+    fn render_target(&self, record: &log::Record<'_>, out: &mut impl LayoutWriter) {
+        let _ = write!(out, " [");
+        out.begin(Element::Target);
+        let target = &*self.options.target_rewrite.rewrite(record.target());
+        let StyleConfig::Aligned { target_width, .. } = self.options.style_for(record.level())
+        else {
+            if needs_quoting(target) {
+                // the debug representation escapes quotes and control characters
+                let _ = write!(out, "{:?}", target);
+            } else {
+                let _ = write!(out, "{}", target);
+            }
+            out.end();
+            let _ = write!(out, "]");
+            return;
+        };
+
+        let target = match needs_quoting(target) {
+            true => Cow::Owned(format!("{:?}", target)),
+            false => Cow::Borrowed(target),
+        };
+        let target = fit(&target, target_width, true);
+        let _ = write!(out, "{}", target);
+        out.end();
+        let padding = target_width.saturating_sub(target.chars().count());
+        let _ = write!(out, "]{:1$}", "", padding);
+    }
+
+    fn render_thread(&self, out: &mut impl LayoutWriter) {
+        let _ = write!(out, " (");
+        out.begin(Element::Thread);
+        // a record being replayed (e.g. by an async logger) has the thread it was logged on
+        crate::record::with_thread(|thread| write!(out, "{}", thread)).ok();
+        out.end();
+        let _ = write!(out, ")");
+    }
+
+    fn render_source(&self, record: &log::Record<'_>, out: &mut impl LayoutWriter) {
+        let Some(file) = record.file() else {
+            return;
+        };
+        let _ = write!(out, " ");
+        out.begin(Element::Source);
+        let _ = match record.line() {
+            Some(line) => write!(out, "{}:{}", file, line),
+            None => write!(out, "{}", file),
+        };
+        out.end();
+    }
+
+    fn render_payload<W: LayoutWriter>(
+        &self,
+        record: &log::Record<'_>,
+        header: bool,
+        out: &mut SkipSpace<'_, Column<'_, W>>,
+    ) {
+        // the message is only moved to its own line if there is a line before it
+        if let (true, StyleConfig::MultiLine) = (header, self.options.style_for(record.level())) {
+            let _ = writeln!(out);
+            out.begin(Element::Continuation);
+            let _ = write!(out, "⤷");
+            out.end();
+        }
+
+        let _ = write!(out, " ");
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            match self.options.hanging_indent {
+                true => {
+                    let indent = " ".repeat(out.inner.column);
+                    self.render_message(record, &mut LinePrefix::continuation(out, &indent))
+                }
+                false => self.render_message(record, out),
+            }
+        }));
+        out.end();
+
+        if let Err(panic) = result {
+            if self.options.repanic_in_debug && cfg!(debug_assertions) {
+                std::panic::resume_unwind(panic)
+            }
+            out.begin(Element::Panic);
+            let _ = write!(out, "<message panicked: {}>", panic_message(&*panic));
+            out.end();
+        }
+        let _ = writeln!(out);
+    }
+
+    fn render_message(&self, record: &log::Record<'_>, out: &mut impl LayoutWriter) {
+        if !self.options.tracing_spans {
+            out.begin(Element::Message);
+            let _ = write!(out, "{}", record.args());
+            return;
+        }
+
+        let message = record.args().to_string();
+        if record.target().starts_with("tracing::span") {
+            out.begin(Element::Span);
+            let _ = write!(out, "{}", message);
+            return;
+        }
+
+        let message = match split_spans(&message) {
+            Some((spans, message)) => {
+                out.begin(Element::Span);
+                let _ = write!(out, "{}", spans);
+                out.end();
+                let _ = write!(out, ": ");
+                message
+            }
+            None => &message,
+        };
+
+        out.begin(Element::Message);
+        let _ = write!(out, "{}", message);
+    }
+}
+
+/// Get the time from the `kv_timestamp` key-value of the record, if it has a valid one,
+/// or the time of the record being [`replay`](../fn.replay.html)ed
+fn origin_time(options: &Options, record: &log::Record<'_>) -> Option<SystemTime> {
+    let Some(value) = options
+        .kv_timestamp
+        .and_then(|key| record.key_values().get(log::kv::Key::from(key)))
+    else {
+        return crate::record::replayed_time();
+    };
+    let value = value.to_string();
+
+    let since_epoch = match value.parse::<f64>() {
+        Ok(secs) => Duration::try_from_secs_f64(secs).ok()?,
+        Err(..) => crate::date::DateTime::parse_rfc3339(&value)?,
+    };
+    SystemTime::UNIX_EPOCH.checked_add(since_epoch)
+}
+
+/// Get the time since the previous record, and make now the time of the previous record
+///
+/// The lock is only held for the swap, never while writing, and a poisoned lock is still used.
+/// It's part of the public [`TimeConfig`], so it's always `std`'s lock, even with the `parking_lot` feature.
+fn lap(previous: &Mutex<Option<Instant>>, now: Instant) -> Duration {
+    let mut previous = previous.lock().unwrap_or_else(PoisonError::into_inner);
+    previous
+        .replace(now)
+        .map(|previous| now.saturating_duration_since(previous))
+        .unwrap_or_default()
+}
+
+/// Split the `span{fields}:span{fields}: ` context that `tracing` puts before a message
+///
+/// At least one of the spans has to have fields, so ordinary `word: message` messages aren't split
+pub(crate) fn split_spans(message: &str) -> Option<(&str, &str)> {
+    let (mut depth, mut fields) = (0_usize, false);
+    for (i, c) in message.char_indices() {
+        match c {
+            '{' => {
+                depth += 1;
+                fields = true;
+            }
+            '}' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            ':' if fields && message[i + 1..].starts_with(' ') => {
+                return Some((&message[..i], &message[i + 2..]))
+            }
+            ':' | '_' | '-' | '.' => {}
+            c if c.is_alphanumeric() => {}
+            _ => return None,
+        }
+    }
+    None
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(msg), ..) => msg,
+        (.., Some(msg)) => msg,
+        _ => "unknown",
+    }
+}
+
+/// Writes `prefix` at the start of every line written to `inner`
+struct LinePrefix<'a, W> {
+    inner: &'a mut W,
+    prefix: &'a str,
+    line_start: bool,
+}
+
+impl<'a, W: LayoutWriter> LinePrefix<'a, W> {
+    fn new(inner: &'a mut W, prefix: &'a str) -> Self {
+        Self {
+            inner,
+            prefix,
+            line_start: true,
+        }
+    }
+
+    /// Like `new`, but the first line isn't prefixed
+    fn continuation(inner: &'a mut W, prefix: &'a str) -> Self {
+        Self {
+            line_start: false,
+            ..Self::new(inner, prefix)
+        }
+    }
+
+    fn write_prefix(&mut self) -> core::fmt::Result {
+        if std::mem::take(&mut self.line_start) {
+            self.inner.write_str(self.prefix)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: LayoutWriter> Write for LinePrefix<'_, W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for line in s.split_inclusive('\n') {
+            self.write_prefix()?;
+            self.inner.write_str(line)?;
+            self.line_start = line.ends_with('\n');
+        }
+        Ok(())
+    }
+}
+
+impl<W: LayoutWriter> LayoutWriter for LinePrefix<'_, W> {
+    fn begin(&mut self, element: Element) {
+        // the prefix has to be the first thing on the line
+        let _ = self.write_prefix();
+        self.inner.begin(element)
+    }
+
+    fn end(&mut self) {
+        self.inner.end()
+    }
+}
+
+/// A writer which keeps track of the column (in characters) the next character is written at
+struct Column<'a, W> {
+    inner: &'a mut W,
+    column: usize,
+}
+
+impl<W: LayoutWriter> Write for Column<'_, W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        match s.rfind('\n') {
+            Some(i) => self.column = s[i + 1..].chars().count(),
+            None => self.column += s.chars().count(),
+        }
+        self.inner.write_str(s)
+    }
+}
+
+impl<W: LayoutWriter> LayoutWriter for Column<'_, W> {
+    fn begin(&mut self, element: Element) {
+        self.inner.begin(element)
+    }
+
+    fn end(&mut self) {
+        self.inner.end()
+    }
+}
+
+/// A writer which drops the space at the start of what is written to it, if `skip` is set
+struct SkipSpace<'a, W> {
+    inner: &'a mut W,
+    skip: bool,
+}
+
+impl<W: LayoutWriter> Write for SkipSpace<'_, W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let s = match std::mem::take(&mut self.skip) {
+            true if s.is_empty() => {
+                self.skip = true;
+                s
+            }
+            true => s.strip_prefix(' ').unwrap_or(s),
+            false => s,
+        };
+        self.inner.write_str(s)
+    }
+}
+
+impl<W: LayoutWriter> LayoutWriter for SkipSpace<'_, W> {
+    fn begin(&mut self, element: Element) {
+        self.inner.begin(element)
+    }
+
+    fn end(&mut self) {
+        self.inner.end()
+    }
+}
+
+/// Cut `s` short to `width` characters, marking where it was cut with a `…` at its start or end
+fn fit(s: &str, width: usize, keep_end: bool) -> Cow<'_, str> {
+    let len = s.chars().count();
+    if len <= width {
+        return Cow::Borrowed(s);
+    }
+    let Some(kept) = width.checked_sub(1) else {
+        return Cow::Borrowed("");
+    };
+    match keep_end {
+        true => Cow::Owned(format!(
+            "…{}",
+            s.chars().skip(len - kept).collect::<String>()
+        )),
+        false => Cow::Owned(format!("{}…", s.chars().take(kept).collect::<String>())),
+    }
+}
+
+/// Whether a target would be ambiguous inside of the brackets
+pub(crate) fn needs_quoting(target: &str) -> bool {
+    target.is_empty()
+        || target
+            .chars()
+            .any(|c| matches!(c, '[' | ']' | '"') || c.is_whitespace() || c.is_control())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::FieldsConfig;
+
+    #[derive(Default)]
+    struct Tagged(String);
+
+    impl Write for Tagged {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.0.write_str(s)
+        }
+    }
+
+    impl LayoutWriter for Tagged {
+        fn begin(&mut self, element: Element) {
+            let _ = write!(self.0, "<{:?}>", element);
+        }
+
+        fn end(&mut self) {
+            self.0.push_str("</>")
+        }
+    }
+
+    #[test]
+    fn clock_and_elements() {
+        let start = Instant::now();
+        let clock = FixedClock(
+            start + Duration::from_millis(1500),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_568_400),
+        );
+        let record = log::Record::builder()
+            .level(log::Level::Warn)
+            .target("foo")
+            .args(format_args!("hello"))
+            .build();
+
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::Relative(start));
+        let mut out = Tagged::default();
+        render(&options, &record, &clock, &mut out);
+        assert_eq!(
+            out.0,
+            "<Level(Warn)>WARN </><Timestamp> 0001.500000000s</> [<Target>foo</>] <Message>hello</>\n"
+        );
+
+        let options = options.with_time(TimeConfig::Unix);
+        let mut out = String::new();
+        render(&options, &record, &clock, &mut out);
+        assert_eq!(out, "WARN  1714568400 [foo] hello\n");
+
+        let options = options.with_time(TimeConfig::MonotonicNanos(start));
+        let mut out = String::new();
+        render(&options, &record, &clock, &mut out);
+        assert_eq!(out, "WARN  1500000000 [foo] hello\n");
+
+        let epoch = SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_568_390);
+        let options = options.with_time(TimeConfig::relative_to(epoch));
+        let mut out = String::new();
+        render(&options, &record, &clock, &mut out);
+        assert_eq!(out, "WARN  0010.000000000s [foo] hello\n");
+
+        let options = options.with_time(TimeConfig::Unix);
+        let before_epoch = FixedClock(start, SystemTime::UNIX_EPOCH - Duration::from_secs(5));
+        let mut out = String::new();
+        render(&options, &record, &before_epoch, &mut out);
+        assert_eq!(out, "WARN  0000 [foo] hello\n");
+    }
+
+    #[test]
+    fn aligned() {
+        let clock = FixedClock(Instant::now(), SystemTime::UNIX_EPOCH);
+        let render = |style, level, target: &str| {
+            let mut out = String::new();
+            let record = log::Record::builder()
+                .level(level)
+                .target(target)
+                .args(format_args!("hello"))
+                .build();
+            render(
+                &Options::default().with_style(style),
+                &record,
+                &clock,
+                &mut out,
+            );
+            out
+        };
+
+        let style = StyleConfig::Aligned {
+            target_width: 12,
+            level_width: 5,
+        };
+        let cases = [
+            (log::Level::Info, "my_app", "INFO  [my_app]       hello\n"),
+            (
+                log::Level::Warn,
+                "my_app::db::pool",
+                "WARN  […p::db::pool] hello\n",
+            ),
+            (
+                log::Level::Error,
+                "my app",
+                "ERROR [\"my app\"]     hello\n",
+            ),
+            (
+                log::Level::Debug,
+                "twelve_chars",
+                "DEBUG [twelve_chars] hello\n",
+            ),
+        ];
+        for (level, target, expected) in cases {
+            assert_eq!(render(style, level, target), expected);
+        }
+
+        let narrow = StyleConfig::Aligned {
+            target_width: 0,
+            level_width: 3,
+        };
+        assert_eq!(render(narrow, log::Level::Debug, "foo"), "DE… [] hello\n");
+        assert_eq!(render(narrow, log::Level::Info, "foo"), "IN… [] hello\n");
+        assert_eq!(fit("abc", 1, true), "…");
+    }
+
+    #[test]
+    fn hanging_indent() {
+        let clock = FixedClock(Instant::now(), SystemTime::UNIX_EPOCH);
+        let options = Options::default().with_hanging_indent(true);
+        let cases = [
+            (options.clone(), "ERROR [foo]\n⤷ a\n  b\n  c\n"),
+            (
+                options.clone().with_style(StyleConfig::SingleLine),
+                "ERROR [foo] a\n            b\n            c\n",
+            ),
+            (
+                options.clone().with_continuation_marker(Some("\t")),
+                "ERROR [foo]\n\t⤷ a\n\t  b\n\t  c\n",
+            ),
+            (
+                options.with_hanging_indent(false),
+                "ERROR [foo]\n⤷ a\nb\nc\n",
+            ),
+        ];
+        for (options, expected) in cases {
+            let mut out = String::new();
+            let record = log::Record::builder()
+                .level(log::Level::Error)
+                .target("foo")
+                .args(format_args!("a\nb\nc"))
+                .build();
+            render(&options, &record, &clock, &mut out);
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let options = Options::default().with_time(TimeConfig::Unix);
+        let cases = [
+            (FieldsConfig::default(), "WARN  0000 [foo]\n⤷ hello\n"),
+            (
+                FieldsConfig::all().with_time(false),
+                "WARN  [foo] (fields) src/foo.rs:12\n⤷ hello\n",
+            ),
+            (
+                FieldsConfig::default().with_level(false),
+                "0000 [foo]\n⤷ hello\n",
+            ),
+            (
+                FieldsConfig::default().with_level(false).with_time(false),
+                "[foo]\n⤷ hello\n",
+            ),
+            (
+                FieldsConfig::default()
+                    .with_level(false)
+                    .with_time(false)
+                    .with_target(false),
+                "hello\n",
+            ),
+            (
+                FieldsConfig::default().with_message(false),
+                "WARN  0000 [foo]\n",
+            ),
+            (
+                FieldsConfig::default().with_time_first(true),
+                "0000 WARN  [foo]\n⤷ hello\n",
+            ),
+            (
+                FieldsConfig::default()
+                    .with_time_first(true)
+                    .with_time(false),
+                "WARN  [foo]\n⤷ hello\n",
+            ),
+        ];
+
+        std::thread::Builder::new()
+            .name(String::from("fields"))
+            .spawn(move || {
+                let clock = FixedClock(Instant::now(), SystemTime::UNIX_EPOCH);
+                for (fields, expected) in cases {
+                    let mut out = String::new();
+                    let record = log::Record::builder()
+                        .level(log::Level::Warn)
+                        .target("foo")
+                        .file(Some("src/foo.rs"))
+                        .line(Some(12))
+                        .args(format_args!("hello"))
+                        .build();
+                    render(
+                        &options.clone().with_fields(fields),
+                        &record,
+                        &clock,
+                        &mut out,
+                    );
+                    assert_eq!(out, expected);
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn date_time_uptime() {
+        let start = Instant::now();
+        let format =
+            time::macros::format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::DateTimeUptime(start, format));
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .target("foo")
+            .args(format_args!("hello"))
+            .build();
+
+        for (uptime, expected) in [
+            (0, "(+00:00:00)"),
+            (3 * 3600 + 12 * 60 + 9, "(+03:12:09)"),
+            (100 * 3600 + 1, "(+100:00:01)"),
+        ] {
+            // 2024-05-02 10:03:22 UTC
+            let clock = FixedClock(
+                start + Duration::from_secs(uptime),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_644_202),
+            );
+            let mut out = String::new();
+            render(&options, &record, &clock, &mut out);
+            assert_eq!(
+                out,
+                format!("INFO  2024-05-02 10:03:22 {} [foo] hello\n", expected)
+            );
+        }
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn parsed_date_time() {
+        // a format that isn't `'static`, e.g. read from a config file
+        let format = String::from("[year]-[month]-[day] [hour]:[minute]");
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::parse_date_time_format(&format).unwrap());
+        drop(format);
+
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .target("foo")
+            .args(format_args!("hello"))
+            .build();
+        let clock = FixedClock(
+            Instant::now(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_568_400),
+        );
+        let mut out = String::new();
+        render(&options.clone(), &record, &clock, &mut out);
+        assert_eq!(out, "INFO  2024-05-01 13:00 [foo] hello\n");
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn date_time_in() {
+        let format =
+            time::macros::format_description!("[hour]:[minute] [offset_hour sign:mandatory]");
+        let tokyo = time::UtcOffset::from_hms(9, 0, 0).unwrap();
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::date_time_format_in(tokyo, format));
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .target("foo")
+            .args(format_args!("hello"))
+            .build();
+
+        // 2024-05-01 13:00:00 UTC
+        let clock = FixedClock(
+            Instant::now(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_568_400),
+        );
+        let mut out = String::new();
+        render(&options, &record, &clock, &mut out);
+        assert_eq!(out, "INFO  22:00 +09 [foo] hello\n");
+
+        let clock = FixedClock(
+            Instant::now(),
+            SystemTime::UNIX_EPOCH + Duration::from_micros(1_714_568_400_123_456),
+        );
+        for (time, expected) in [
+            (TimeConfig::iso8601_millis(), "2024-05-01T13:00:00.123Z"),
+            (TimeConfig::iso8601_micros(), "2024-05-01T13:00:00.123456Z"),
+        ] {
+            let mut out = String::new();
+            render(&options.clone().with_time(time), &record, &clock, &mut out);
+            assert_eq!(out, format!("INFO  {} [foo] hello\n", expected));
+        }
+    }
+
+    #[test]
+    fn monotonic_timestamps() {
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::Unix)
+            .with_monotonic_timestamps(true);
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .target("foo")
+            .args(format_args!("hello"))
+            .build();
+
+        let epoch = SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_568_400);
+        let mut lines = vec![];
+        for secs in [0, 10, 5, 11] {
+            let clock = FixedClock(Instant::now(), epoch + Duration::from_secs(secs));
+            let mut out = String::new();
+            render(&options, &record, &clock, &mut out);
+            lines.push(out);
+        }
+        assert_eq!(
+            lines,
+            [
+                "INFO  1714568400 [foo] hello\n",
+                "INFO  1714568410 [foo] hello\n",
+                "INFO  1714568410 [foo] hello\n",
+                "INFO  1714568411 [foo] hello\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn kv_timestamp() {
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::unix_timestamp())
+            .with_kv_timestamp(Some("timestamp"));
+        let clock = FixedClock(
+            Instant::now(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(99),
+        );
+
+        let cases = [
+            ("1714521600.5", "INFO  1714521600 [foo] hello\n"),
+            (
+                "2024-05-01T02:00:00+02:00",
+                "INFO  1714521600 [foo] hello\n",
+            ),
+            ("yesterday", "INFO  0099 [foo] hello\n"),
+        ];
+        for (timestamp, expected) in cases {
+            let (mut out, kv) = (String::new(), [("timestamp", timestamp)]);
+            let record = log::Record::builder()
+                .level(log::Level::Info)
+                .target("foo")
+                .args(format_args!("hello"))
+                .key_values(&kv)
+                .build();
+            render(&options, &record, &clock, &mut out);
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn replayed_thread() {
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::None)
+            .with_fields(FieldsConfig::default().with_thread(true));
+        let record = log::Record::builder()
+            .level(log::Level::Info)
+            .target("foo")
+            .args(format_args!("hello"))
+            .build();
+
+        let mut out = String::new();
+        crate::record::replaying(SystemTime::now(), Some("worker"), || {
+            render(&options, &record, &SystemClock, &mut out)
+        });
+        assert_eq!(out, "INFO  [foo] (worker) hello\n");
+    }
+
+    #[test]
+    fn relative_to_kv_timestamp() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1714521600);
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::relative_to(start))
+            .with_kv_timestamp(Some("timestamp"));
+        let clock = FixedClock(Instant::now(), start + Duration::from_secs(60));
+
+        let cases = [
+            ("1714521602.5", "INFO  0002.500000000s [foo] hello\n"),
+            ("yesterday", "INFO  0060.000000000s [foo] hello\n"),
+        ];
+        for (timestamp, expected) in cases {
+            let (mut out, kv) = (String::new(), [("timestamp", timestamp)]);
+            let record = log::Record::builder()
+                .level(log::Level::Info)
+                .target("foo")
+                .args(format_args!("hello"))
+                .key_values(&kv)
+                .build();
+            render(&options, &record, &clock, &mut out);
+            assert_eq!(out, expected);
+        }
+    }
+
+    // a threaded stress test, as the lock can't be poisoned in a loom model.
+    // `timing_model` checks every interleaving with `RUSTFLAGS="--cfg loom" cargo test --lib model`
+    #[cfg(not(loom))]
+    #[test]
+    fn timing_contention() {
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::relative_local());
+        let render = || {
+            let mut out = String::new();
+            let record = log::Record::builder()
+                .level(log::Level::Info)
+                .target("foo")
+                .args(format_args!("hello"))
+                .build();
+            render(&options, &record, &SystemClock, &mut out);
+            out
+        };
+
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        assert!(render().ends_with("s [foo] hello\n"));
+                    }
+                });
+            }
+        });
+
+        // a thread that panicked while holding the lock doesn't stop the timestamps
+        let TimeConfig::Timing(previous) = &options.time else {
+            unreachable!()
+        };
+        let poisoned = std::thread::scope(|s| {
+            s.spawn(|| {
+                let _guard = previous.lock().unwrap();
+                panic!("poison the lock")
+            })
+            .join()
+        });
+        assert!(poisoned.is_err() && previous.is_poisoned());
+
+        assert!(render().starts_with("INFO  0000."));
+    }
+
+    #[cfg(loom)]
+    #[test]
+    fn timing_model() {
+        loom::model(|| {
+            let previous = std::sync::Arc::new(Mutex::new(None));
+            let first = Instant::now();
+            let second = first + Duration::from_secs(1);
+
+            let other = loom::thread::spawn({
+                let previous = std::sync::Arc::clone(&previous);
+                move || lap(&previous, second)
+            });
+            let lapped = lap(&previous, first);
+            let other = other.join().unwrap();
+
+            // whichever is first sees no previous record, and the other sees the first
+            assert!(lapped.is_zero());
+            assert!(other.is_zero() || other == Duration::from_secs(1));
+            let latest = previous.lock().unwrap().unwrap();
+            assert!(latest == first || latest == second);
+        });
+    }
+}
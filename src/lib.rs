@@ -6,15 +6,50 @@ Use the ENV variable `RUST_LOG` with `module_name=level`
 
 A default level can be provided with just ***level***. e.g. `RUST_LOG=trace` will enable `trace` for all modules.
 
-You can disable specific modules/crates by using the `off` level
+You can disable specific modules/crates by using the `off` level, or by prefixing them with `!` or `-`.
+e.g. `RUST_LOG=debug,-hyper,-h2` is the same as `RUST_LOG=debug,hyper=off,h2=off`
+
+This disables the modules below them too, whatever the default level is, but a module below them can still be enabled.
+e.g. `RUST_LOG="trace,!h2,h2::client=debug"` drops everything from `h2` except `h2::client`.
+This also works with wildcards (`!*_sys`) and regexes (`!/^hyper::proto/`).
+
+Directives are applied in order, so a later directive for the same module (or a later default level)
+replaces an earlier one. e.g. `RUST_LOG=foo=trace,foo=warn` uses `warn` for `foo`.
+
+A module can contain `*` (any characters) and `?` (one character) wildcards, e.g. `RUST_LOG=my_app::net::*=trace,*_sys=off`.
+These apply to the modules below a match too, and a directive without wildcards for a module or its parents is used before them.
+
+A range of levels can be used instead of a level, e.g. `my_crate=warn..error` only enables `warn` and `error`,
+and `my_crate=only:debug` only enables `debug`.
+
+The filters can also be read from a file with [`Filters::from_file`](struct.Filters.html#method.from_file), which can be
+watched for changes, so the verbosity can be changed without restarting.
+
+Anything after a `/` filters the messages, e.g. `RUST_LOG="debug/connection reset"` only logs the records whose
+message contains `connection reset`. With the `regex` feature this is a regular expression.
+
+## colors
+Colors are disabled when `NO_COLOR` is set to a non-empty value, `CLICOLOR=0`, `TERM=dumb`, or a CI environment is detected.
+`CLICOLOR_FORCE=1` will force them on. See [`Detection`](options/enum.Detection.html) to override this.
 
 ## optional features
 * `time` allows formatting a UTC timestamp with the [`time`](time) crate.
     * see the formatting description [here](https://time-rs.github.io/book/api/format-description.html)
+    * [`time_format!`](macro.time_format.html) checks a format description at compile time.
+* `alert` provides the [`AlertLogger`](struct.AlertLogger.html), for delivering high-severity records as alerts.
+* `webhook` allows the `AlertLogger` to POST to a webhook, with [`ureq`](https://docs.rs/ureq).
+* `signal-hook` provides [`flush_on_signal`](fn.flush_on_signal.html), for flushing the logger when the process is stopped.
+* `interactive` provides [`ReloadHandle::cycle_on_key`](struct.ReloadHandle.html#method.cycle_on_key), for changing the verbosity from the terminal.
+* `regex` allows `/regex/=level` filter directives, e.g. `RUST_LOG=info,/^hyper::(client|proto)/=debug`,
+  and makes the message filter a regular expression.
+* `parking_lot` uses [`parking_lot`](https://docs.rs/parking_lot)'s locks in the loggers, rather than `std`'s.
+* `bevy` provides [`LogPlugin`](struct.LogPlugin.html), a Bevy plugin which installs the logger in place of Bevy's `LogPlugin`.
+* `test-util` provides [`force_reinit`](fn.force_reinit.html) for replacing the logger between tests.
+
 [time]: https://docs.rs/time
 */
 
-#[cfg(all(doctest))]
+#[cfg(doctest)]
 doc_comment::doctest!("../README.md");
 
 #[doc(inline)]
@@ -56,9 +91,38 @@ pub use termcolor::Color;
 /// ```
 ///
 pub fn init(logger: impl log::Log + 'static) -> Result<(), Error> {
-    // enables trace for all
-    log::set_max_level(log::LevelFilter::Trace);
-    log::set_boxed_logger(Box::new(logger)).map_err(Error::SetLogger)
+    let config = config::describe(&logger);
+    dispatch::install(Box::new(logger), &config)?;
+    config::set_current(config);
+    Ok(())
+}
+
+/// Initialize the logger, unless one has already been installed
+///
+/// This returns `Ok(false)` if a logger was already installed, and `logger` is dropped.
+pub fn try_init(logger: impl log::Log + 'static) -> Result<bool, Error> {
+    match init(logger) {
+        Ok(()) => Ok(true),
+        Err(Error::SetLogger(..)) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Replace the installed logger, or install it if there isn't one
+///
+/// The `log` crate only allows a logger to be installed once per process, so this lets
+/// integration tests install a different logger for each test. This is the same as [`swap`](fn.swap.html).
+///
+/// ***Note*** This requires the `test-util` feature
+///
+/// ```rust
+/// # use alto_logger::{Options, TermLogger};
+/// alto_logger::init_term_logger().unwrap();
+/// alto_logger::force_reinit(TermLogger::new(Options::default()).unwrap()).unwrap();
+/// ```
+#[cfg(feature = "test-util")]
+pub fn force_reinit(logger: impl log::Log + 'static) -> Result<(), Error> {
+    swap(logger)
 }
 
 /// Convenience function to create a default terminal logger
@@ -71,6 +135,13 @@ pub fn init_term_logger() -> Result<(), Error> {
     TermLogger::new(Options::default()).and_then(init)
 }
 
+/// Convenience function to create a default stderr logger, without colors
+///
+/// See [`StderrLogger`](struct.StderrLogger.html)
+pub fn init_stderr_logger() -> Result<(), Error> {
+    StderrLogger::default().init()
+}
+
 /// Convenience function to create a terminal logger that uses a single-line output, and unix timestamps.
 pub fn init_alt_term_logger() -> Result<(), Error> {
     TermLogger::new(
@@ -81,15 +152,74 @@ pub fn init_alt_term_logger() -> Result<(), Error> {
     .and_then(init)
 }
 
+mod banner;
+#[cfg(feature = "bevy")]
+mod bevy;
+mod config;
+mod date;
+mod dispatch;
 mod error;
+mod exit;
 mod filters;
+mod format;
+#[cfg(feature = "interactive")]
+mod interactive;
+mod latency;
 mod loggers;
+mod macros;
+mod pipe;
+mod record;
+mod reload;
+mod render;
+mod retention;
+mod scope;
+#[cfg(all(unix, feature = "signal-hook"))]
+mod signal;
+mod sink;
+mod sync;
+mod tee;
+mod tracker;
 
+pub mod fmt;
+#[doc(hidden)]
+pub mod fuzz;
+pub mod layout;
 pub mod options;
+pub mod reader;
 #[doc(inline)]
 pub use options::*;
 
 pub use loggers::*;
 
+pub use banner::init_with_banner;
+#[cfg(feature = "bevy")]
+pub use bevy::LogPlugin;
+pub use config::{current_config, EffectiveConfig, LoggerConfig, LoggerKind};
+pub use dispatch::{is_quiet, preinit, set_quiet, shutdown, swap, with_logger};
+pub use exit::flush_on_exit;
+pub use filters::{Filters, FiltersBuilder, Preset};
+pub use format::Format;
+pub use latency::{latency_summary, report_latency_every, set_latency_tracking, LatencySummary};
+pub use pipe::{pipe_child, LogWriter};
+pub use record::{replay, OwnedRecord};
+pub use reload::{
+    FileSource, FilterChange, PollHandle, ReloadHandle, RemoteFilterSource, TtlHandle,
+};
+pub use render::RecordDisplay;
+pub use retention::{Retention, RetentionHandle};
+pub use scope::{scoped, Scoped};
+#[cfg(all(unix, feature = "signal-hook"))]
+pub use signal::flush_on_signal;
+pub use sink::Sink;
+pub use tee::TeeWriter;
+pub use tracker::{emit_stats_every, exit_code, stats_record, track_targets, worst_level_seen};
+
 #[doc(inline)]
 pub use error::Error;
+
+#[doc(hidden)]
+pub use log as __log;
+
+#[cfg(feature = "time")]
+#[doc(hidden)]
+pub use time as __time;
@@ -1,7 +1,29 @@
+#[cfg(feature = "alert")]
+mod alert;
+mod buffer;
+mod channel;
+mod composed;
 mod file;
+mod frame;
+mod merge;
 mod multi;
+mod queue;
+mod rolling;
+mod summary;
 mod term;
+mod throttle;
 
+#[cfg(feature = "alert")]
+pub use alert::*;
+pub use buffer::*;
+pub use channel::*;
+pub use composed::*;
 pub use file::*;
+pub use frame::*;
+pub use merge::*;
 pub use multi::*;
+pub use queue::*;
+pub use rolling::*;
+pub use summary::*;
 pub use term::*;
+pub use throttle::*;
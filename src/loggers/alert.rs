@@ -0,0 +1,402 @@
+use crate::{
+    date::DateTime,
+    filters::SharedFilters,
+    format::json_string,
+    sync::{Condvar, Mutex},
+    OwnedRecord, ReloadHandle,
+};
+use std::{
+    sync::{Arc, Once},
+    time::{Duration, UNIX_EPOCH},
+};
+
+/// How many records are kept between deliveries, any more are dropped
+const MAX_PENDING: usize = 100;
+
+/// The target used for records about delivering alerts, these are never alerted on
+const TARGET: &str = "alto_logger::alert";
+
+type Deliver = dyn Fn(&[OwnedRecord]) + Send + Sync;
+
+/// Logger which delivers high-severity records as alerts, e.g. to a chat webhook or an email
+///
+/// Records at or above the alert level (`ERROR` by default) are batched on a background thread,
+/// and delivered at most once per interval (every minute by default). Only the first 100 records
+/// between deliveries are kept.
+///
+/// This is intended to be used alongside another logger in a [`MultiLogger`](struct.MultiLogger.html).
+///
+/// ***Note*** This requires the `alert` feature
+///
+/// ```rust,no_run
+/// # use alto_logger::{AlertLogger, MultiLogger, TermLogger};
+/// # fn send_email(_: &str) {}
+/// let alerts = AlertLogger::new(|records| {
+///     for record in records {
+///         send_email(&record.message);
+///     }
+/// });
+///
+/// MultiLogger::new()
+///     .with(TermLogger::default())
+///     .with(alerts)
+///     .init()
+///     .unwrap();
+/// ```
+pub struct AlertLogger {
+    filters: SharedFilters,
+    level: log::Level,
+    interval: Duration,
+    deliver: Arc<Deliver>,
+    shared: Arc<Shared>,
+    started: Once,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    pending: Vec<OwnedRecord>,
+    closed: bool,
+}
+
+impl AlertLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Create a new alert logger which calls `deliver` with each batch of records
+    pub fn new(deliver: impl Fn(&[OwnedRecord]) + Send + Sync + 'static) -> Self {
+        Self {
+            filters: SharedFilters::from_env(),
+            level: log::Level::Error,
+            interval: Duration::from_secs(60),
+            deliver: Arc::new(deliver),
+            shared: Arc::default(),
+            started: Once::new(),
+        }
+    }
+
+    /// Create a new alert logger which POSTs each batch of records to this webhook
+    ///
+    /// The body is JSON with the records, one per line, in a `text` field. e.g. `{"text":"ERROR [my_app] disk full"}`
+    ///
+    /// ***Note*** This requires the `webhook` feature
+    #[cfg(feature = "webhook")]
+    pub fn webhook(url: impl Into<String>) -> Self {
+        Self::webhook_with(url, WebhookFormat::Text)
+    }
+
+    /// Create a new alert logger which POSTs each batch of records to this webhook, with this payload format
+    ///
+    /// ```rust,no_run
+    /// # use alto_logger::{AlertLogger, WebhookFormat};
+    /// let alerts = AlertLogger::webhook_with(
+    ///     "https://hooks.slack.com/services/...",
+    ///     WebhookFormat::Slack,
+    /// );
+    /// ```
+    ///
+    /// ***Note*** This requires the `webhook` feature
+    #[cfg(feature = "webhook")]
+    pub fn webhook_with(url: impl Into<String>, format: WebhookFormat) -> Self {
+        let url = url.into();
+        Self::new(move |records| {
+            if let Err(err) = post_json(&url, &format.payload(records)) {
+                log::warn!(target: TARGET, "cannot deliver alert: {}", err)
+            }
+        })
+    }
+
+    /// Alert on records at or above this `level`
+    ///
+    /// ***Note*** Defaults to `Level::Error`
+    pub fn with_level(mut self, level: log::Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Deliver at most one batch of records per `interval`
+    ///
+    /// ***Note*** Defaults to 60 seconds
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Get a handle for replacing this logger's filters at runtime
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle::new(self.filters.clone())
+    }
+
+    fn spawn(&self) {
+        let (shared, deliver) = (Arc::clone(&self.shared), Arc::clone(&self.deliver));
+        let interval = self.interval;
+
+        std::thread::Builder::new()
+            .name(String::from("alto_logger alerts"))
+            .spawn(move || loop {
+                let batch = {
+                    let state = crate::sync::lock(&shared.state);
+                    let mut state = crate::sync::wait_while(&shared.changed, state, |state| {
+                        state.pending.is_empty() && !state.closed
+                    });
+                    if state.pending.is_empty() {
+                        return;
+                    }
+                    std::mem::take(&mut state.pending)
+                };
+
+                deliver(&batch);
+
+                // records during this interval will be batched for the next delivery
+                let state = crate::sync::lock(&shared.state);
+                let _state =
+                    crate::sync::wait_timeout_while(&shared.changed, state, interval, |state| {
+                        !state.closed
+                    });
+            })
+            .expect("spawn alert thread");
+    }
+}
+
+impl log::Log for AlertLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+            && metadata.target() != TARGET
+            && self.filters.is_enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) || !self.filters.matches_message(record) {
+            return;
+        }
+
+        crate::tracker::record(record.metadata());
+        self.started.call_once(|| self.spawn());
+
+        let mut state = crate::sync::lock(&self.shared.state);
+        if state.pending.len() < MAX_PENDING {
+            state.pending.push(OwnedRecord::from_record(record));
+            self.shared.changed.notify_all();
+        }
+    }
+
+    /// Deliver any pending records now, ignoring the interval
+    fn flush(&self) {
+        let batch = std::mem::take(&mut crate::sync::lock(&self.shared.state).pending);
+        if !batch.is_empty() {
+            (self.deliver)(&batch)
+        }
+    }
+}
+
+impl Drop for AlertLogger {
+    fn drop(&mut self) {
+        crate::sync::lock(&self.shared.state).closed = true;
+        self.shared.changed.notify_all();
+    }
+}
+
+/// The JSON payload POSTed to a webhook by an [`AlertLogger`](struct.AlertLogger.html)
+///
+/// ***Note*** Defaults to `Text`
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// The records, one per line, in a `text` field
+    #[default]
+    Text,
+    /// A Slack message with an attachment per record, colored by its level
+    Slack,
+    /// A Discord message with an embed per record, colored by its level
+    ///
+    /// Discord allows 10 embeds per message, so any others are counted in the `content` of the message
+    Discord,
+}
+
+impl WebhookFormat {
+    /// Create the payload for these records
+    ///
+    /// This can be used to deliver alerts with your own HTTP client
+    pub fn payload(self, records: &[OwnedRecord]) -> String {
+        let title =
+            |record: &OwnedRecord| json_string(&format!("{} [{}]", record.level, record.target));
+        let secs = |record: &OwnedRecord| {
+            record
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        };
+
+        match self {
+            Self::Text => {
+                let text = records
+                    .iter()
+                    .map(|record| {
+                        format!("{} [{}] {}", record.level, record.target, record.message)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!(r#"{{"text":{}}}"#, json_string(&text))
+            }
+
+            Self::Slack => {
+                let attachments = records
+                    .iter()
+                    .map(|record| {
+                        format!(
+                            r##"{{"color":"#{:06X}","title":{},"text":{},"ts":{}}}"##,
+                            level_color(record.level),
+                            title(record),
+                            json_string(&record.message),
+                            secs(record),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!(r#"{{"attachments":[{}]}}"#, attachments)
+            }
+
+            Self::Discord => {
+                const MAX_EMBEDS: usize = 10;
+
+                let embeds = records
+                    .iter()
+                    .take(MAX_EMBEDS)
+                    .map(|record| {
+                        format!(
+                            r#"{{"color":{},"title":{},"description":{},"timestamp":"{}"}}"#,
+                            level_color(record.level),
+                            title(record),
+                            json_string(&record.message),
+                            DateTime::from_unix(secs(record)).rfc3339(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+
+                match records.len().saturating_sub(MAX_EMBEDS) {
+                    0 => format!(r#"{{"embeds":[{}]}}"#, embeds),
+                    more => format!(r#"{{"content":"and {} more","embeds":[{}]}}"#, more, embeds),
+                }
+            }
+        }
+    }
+}
+
+/// The color for a level, as `0xRRGGBB`
+const fn level_color(level: log::Level) -> u32 {
+    match level {
+        log::Level::Error => 0xD5_02_00,
+        log::Level::Warn => 0xF2_C7_44,
+        log::Level::Info => 0x2E_B6_7D,
+        log::Level::Debug => 0x36_C5_F0,
+        log::Level::Trace => 0x8F_8F_8F,
+    }
+}
+
+#[cfg(feature = "webhook")]
+fn post_json(url: &str, body: &str) -> Result<(), crate::Error> {
+    ureq::post(url)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+        .map(drop)
+        .map_err(|err| crate::Error::NetworkError {
+            url: url.to_string(),
+            reason: err.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Log as _;
+    use std::sync::{mpsc::channel, Mutex};
+
+    #[test]
+    fn batches() {
+        let (tx, rx) = channel();
+        let tx = Mutex::new(tx);
+        let logger = AlertLogger::new(move |records| {
+            let messages = records
+                .iter()
+                .map(|r| r.message.clone())
+                .collect::<Vec<_>>();
+            tx.lock().unwrap().send(messages).unwrap();
+        })
+        .with_interval(Duration::from_millis(200));
+        logger.reload_handle().set("trace");
+
+        let log = |level, msg: &str| {
+            logger.log(
+                &log::Record::builder()
+                    .level(level)
+                    .args(format_args!("{}", msg))
+                    .build(),
+            )
+        };
+
+        log(log::Level::Error, "a");
+        assert_eq!(rx.recv().unwrap(), ["a"]);
+
+        // these are all within the interval of the first delivery
+        log(log::Level::Warn, "ignored");
+        log(log::Level::Error, "b");
+        log(log::Level::Error, "c");
+        assert_eq!(rx.recv().unwrap(), ["b", "c"]);
+
+        log(log::Level::Error, "d");
+        logger.flush();
+        assert_eq!(rx.recv().unwrap(), ["d"]);
+    }
+
+    #[test]
+    fn payloads() {
+        let record = |message: &str| OwnedRecord {
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            ..OwnedRecord::from_record(
+                &log::Record::builder()
+                    .level(log::Level::Error)
+                    .target("my_app")
+                    .args(format_args!("{}", message))
+                    .build(),
+            )
+        };
+        let records = [record("disk full"), record("\"quoted\"")];
+
+        assert_eq!(
+            WebhookFormat::Text.payload(&records),
+            r#"{"text":"ERROR [my_app] disk full\nERROR [my_app] \"quoted\""}"#
+        );
+        assert_eq!(
+            WebhookFormat::Slack.payload(&records[..1]),
+            r##"{"attachments":[{"color":"#D50200","title":"ERROR [my_app]","text":"disk full","ts":1700000000}]}"##
+        );
+        assert_eq!(
+            WebhookFormat::Discord.payload(&records[..1]),
+            r#"{"embeds":[{"color":13959680,"title":"ERROR [my_app]","description":"disk full","timestamp":"2023-11-14T22:13:20Z"}]}"#
+        );
+
+        let many = vec![record("a"); 12];
+        assert!(WebhookFormat::Discord
+            .payload(&many)
+            .starts_with(r#"{"content":"and 2 more","embeds":[{"#));
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(json_string("plain"), r#""plain""#);
+        assert_eq!(
+            json_string("a \"quote\"\n\tand \\ \u{1}"),
+            r#""a \"quote\"\n\tand \\ \u0001""#
+        );
+    }
+}
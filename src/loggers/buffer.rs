@@ -0,0 +1,132 @@
+use crate::{filters::SharedFilters, OwnedRecord, ReloadHandle};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// The records kept by a [`SharedBufferLogger`](struct.SharedBufferLogger.html), oldest first
+///
+/// This is always `std`'s `RwLock`, even with the `parking_lot` feature.
+pub type SharedRecords = Arc<RwLock<VecDeque<OwnedRecord>>>;
+
+/// Logger which keeps the most recent records in a shared buffer
+///
+/// This is intended for rendering a live log pane in a GUI or TUI (e.g. with egui or iced),
+/// which can read the records each frame.
+///
+/// ```rust
+/// # use alto_logger::SharedBufferLogger;
+/// let logger = SharedBufferLogger::new(1000);
+/// let records = logger.records();
+/// logger.init().unwrap();
+///
+/// log::info!("hello");
+/// for record in records.read().unwrap().iter() {
+///     // draw `record`
+/// }
+/// ```
+pub struct SharedBufferLogger {
+    filters: SharedFilters,
+    records: SharedRecords,
+    capacity: usize,
+    notify: Option<Box<dyn Fn() + Send + Sync>>,
+}
+
+impl SharedBufferLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Create a new logger which keeps up to `capacity` records, dropping the oldest when its full
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            filters: SharedFilters::from_env(),
+            records: Arc::new(RwLock::new(VecDeque::with_capacity(capacity))),
+            capacity,
+            notify: None,
+        }
+    }
+
+    /// Call `notify` after each record is added to the buffer
+    ///
+    /// e.g. this can be used to request a repaint of the log pane.
+    pub fn with_notify(mut self, notify: impl Fn() + Send + Sync + 'static) -> Self {
+        self.notify.replace(Box::new(notify));
+        self
+    }
+
+    /// Get the shared buffer of records
+    pub fn records(&self) -> SharedRecords {
+        Arc::clone(&self.records)
+    }
+
+    /// Get a handle for replacing this logger's filters at runtime
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle::new(self.filters.clone())
+    }
+}
+
+impl log::Log for SharedBufferLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.filters.is_enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata())
+            || !self.filters.matches_message(record)
+            || self.capacity == 0
+        {
+            return;
+        }
+
+        crate::tracker::record(record.metadata());
+        let record = OwnedRecord::from_record(record);
+        {
+            let mut records = self.records.write().unwrap_or_else(PoisonError::into_inner);
+            if records.len() == self.capacity {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+
+        if let Some(notify) = &self.notify {
+            notify()
+        }
+    }
+
+    #[inline]
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Log as _;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn capacity() {
+        static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+
+        let logger = SharedBufferLogger::new(2).with_notify(|| {
+            NOTIFIED.fetch_add(1, Ordering::SeqCst);
+        });
+        logger.reload_handle().set("trace");
+
+        let records = logger.records();
+        for msg in ["a", "b", "c"] {
+            logger.log(&log::Record::builder().args(format_args!("{}", msg)).build());
+        }
+
+        let messages = records
+            .read()
+            .unwrap()
+            .iter()
+            .map(|record| record.message.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["b", "c"]);
+        assert_eq!(NOTIFIED.load(Ordering::SeqCst), 3);
+    }
+}
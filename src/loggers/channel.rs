@@ -0,0 +1,184 @@
+use crate::{filters::SharedFilters, options::Options, OwnedRecord, RecordDisplay, ReloadHandle};
+use std::sync::mpsc::Sender;
+
+/// Logger which sends each record over a channel
+///
+/// This lets e.g. a GUI display the records in a window, without implementing `log::Log` itself.
+///
+/// Records can either be sent formatted, without colors and without the trailing newline,
+/// or as an [`OwnedRecord`](struct.OwnedRecord.html). They are dropped once the receiver has been dropped.
+///
+/// ```rust
+/// # use alto_logger::{options::StyleConfig, ChannelLogger};
+/// let (tx, rx) = std::sync::mpsc::channel();
+/// ChannelLogger::new(StyleConfig::SingleLine, tx).init().unwrap();
+///
+/// log::info!("hello");
+/// for line in rx.try_iter() {
+///     // display `line` somewhere
+/// }
+/// ```
+pub struct ChannelLogger {
+    options: Options,
+    filters: SharedFilters,
+    sender: Channel,
+}
+
+enum Channel {
+    Formatted(Sender<String>),
+    Records(Sender<OwnedRecord>),
+}
+
+impl ChannelLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Create a new channel logger which sends formatted records to `sender`
+    pub fn new(options: impl Into<Options>, sender: Sender<String>) -> Self {
+        let options = options.into();
+        Self {
+            filters: SharedFilters::from_options(&options),
+            options,
+            sender: Channel::Formatted(sender),
+        }
+    }
+
+    /// Create a new channel logger which sends a copy of each record to `sender`
+    pub fn records(sender: Sender<OwnedRecord>) -> Self {
+        Self {
+            options: Options::default(),
+            filters: SharedFilters::from_env(),
+            sender: Channel::Records(sender),
+        }
+    }
+
+    /// Get a handle for replacing this logger's filters at runtime
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle::new(self.filters.clone())
+    }
+
+    /// Log this record, returning an error if the receiver has been dropped
+    ///
+    /// Records that aren't enabled, or that are dropped by middleware, aren't an error.
+    ///
+    /// ```rust
+    /// # use alto_logger::{options::StyleConfig, ChannelLogger};
+    /// let (tx, rx) = std::sync::mpsc::channel();
+    /// let logger = ChannelLogger::new(StyleConfig::SingleLine, tx);
+    /// logger.reload_handle().set("error");
+    /// drop(rx);
+    ///
+    /// let record = log::Record::builder().level(log::Level::Error).build();
+    /// assert!(logger.try_log(&record).unwrap_err().is_channel_closed());
+    /// ```
+    pub fn try_log(&self, record: &log::Record<'_>) -> Result<(), crate::Error> {
+        use log::Log as _;
+        if !self.enabled(record.metadata()) || !self.filters.matches_message(record) {
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+        self.options.middleware.apply(record, |record| {
+            crate::tracker::record(record.metadata());
+            result = self.send(record);
+        });
+        result
+    }
+
+    fn send(&self, record: &log::Record<'_>) -> Result<(), crate::Error> {
+        let result = match &self.sender {
+            Channel::Formatted(sender) => sender
+                .send(RecordDisplay::new(&self.options, record).to_string())
+                .map_err(drop),
+            Channel::Records(sender) => sender.send(OwnedRecord::from_record(record)).map_err(drop),
+        };
+        result.map_err(|()| crate::Error::ChannelClosed)
+    }
+}
+
+#[cfg(test)]
+impl ChannelLogger {
+    /// A logger which sends a copy of every record, for testing the loggers which wrap another logger
+    pub(crate) fn capture() -> (Self, std::sync::mpsc::Receiver<OwnedRecord>) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let logger = Self::records(tx);
+        logger.filters.replace(crate::Filters::parse("trace"));
+        (logger, rx)
+    }
+}
+
+impl log::Log for ChannelLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.options.allows(&self.filters, metadata)
+    }
+
+    #[inline]
+    fn log(&self, record: &log::Record<'_>) {
+        // the records are dropped once the receiver is gone
+        let _ = self.try_log(record);
+    }
+
+    #[inline]
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::StyleConfig;
+    use log::Log as _;
+
+    #[test]
+    fn formatted() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let logger = ChannelLogger::new(StyleConfig::SingleLine, tx);
+        logger.reload_handle().set("trace");
+
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Warn)
+                .target("foo")
+                .args(format_args!("hello\nworld"))
+                .build(),
+        );
+        assert_eq!(rx.try_recv().unwrap(), "WARN  [foo] hello\nworld");
+
+        drop(rx);
+        logger.log(&log::Record::builder().build());
+        assert!(logger
+            .try_log(&log::Record::builder().build())
+            .unwrap_err()
+            .is_channel_closed());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let logger = ChannelLogger::records(tx);
+        logger.reload_handle().set("trace");
+        logger.log(&log::Record::builder().args(format_args!("hello")).build());
+        assert_eq!(rx.try_recv().unwrap().message, "hello");
+    }
+
+    #[test]
+    fn middleware() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_middleware(|mut record: OwnedRecord| {
+                record.message = record.message.replace("hunter2", "***");
+                Some(record)
+            });
+        let logger = ChannelLogger::new(options, tx);
+        logger.reload_handle().set("trace");
+
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target("my_app")
+                .args(format_args!("password is hunter2"))
+                .build(),
+        );
+        assert_eq!(rx.try_recv().unwrap(), "INFO  [my_app] password is ***");
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{filters::SharedFilters, Format, Options, ReloadHandle, Sink};
+
+/// A logger made from any [`Format`](struct.Format.html) and any [`Sink`](struct.Sink.html)
+///
+/// This is for the formats and destinations the other loggers don't have, it doesn't replace them:
+/// colors, rotation, lazily created files and file headers are only in [`TermLogger`](struct.TermLogger.html),
+/// [`FileLogger`](struct.FileLogger.html) and [`RollingLogger`](struct.RollingLogger.html).
+///
+/// ```rust,no_run
+/// # use alto_logger::{Format, Logger, Sink};
+/// Logger::new(Format::json(), Sink::file("a.log").unwrap())
+///     .init()
+///     .unwrap();
+/// ```
+pub struct Logger {
+    format: Format,
+    sink: Sink,
+    options: Options,
+    filters: SharedFilters,
+}
+
+impl Logger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Create a new logger, writing records in this format to this sink
+    pub fn new(format: Format, sink: Sink) -> Self {
+        Self {
+            format,
+            sink,
+            options: Options::default(),
+            filters: SharedFilters::from_env(),
+        }
+    }
+
+    /// Use the filters, enable predicate, target rewrite and middleware of these `Options`
+    ///
+    /// A [`Format::text`](struct.Format.html#method.text) format renders with the options it was given.
+    ///
+    /// ```rust
+    /// # use alto_logger::{Format, Logger, Options, OwnedRecord, Sink};
+    /// let options = Options::default().with_middleware(|mut record: OwnedRecord| {
+    ///     record.message = record.message.replace("hunter2", "*******");
+    ///     Some(record)
+    /// });
+    /// let logger = Logger::new(Format::json(), Sink::stdout()).with_options(options);
+    /// ```
+    ///
+    /// ***Note*** Defaults to `Options::default()`
+    pub fn with_options(self, options: impl Into<Options>) -> Self {
+        let options = options.into();
+        Self {
+            filters: SharedFilters::from_options(&options),
+            options,
+            ..self
+        }
+    }
+
+    /// Get a handle for replacing this logger's filters at runtime
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle::new(self.filters.clone())
+    }
+}
+
+impl log::Log for Logger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.options.allows(&self.filters, metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) || !self.filters.matches_message(record) {
+            return;
+        }
+
+        self.options.middleware.apply(record, |record| {
+            crate::tracker::record(record.metadata());
+            let mut line = Vec::new();
+            match self.format.is_text() {
+                true => self.format.write(record, &mut line),
+                false => {
+                    let target = self.options.target_rewrite.rewrite(record.target());
+                    let record = record.to_builder().target(&target).build();
+                    self.format.write(&record, &mut line)
+                }
+            }
+            let _ = self.sink.write(&line);
+        });
+    }
+
+    #[inline]
+    fn flush(&self) {
+        let _ = self.sink.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{LevelCase, StyleConfig, TargetRewrite};
+    use log::Log as _;
+
+    #[test]
+    fn formats() {
+        let record = |logger: &Logger| {
+            logger.reload_handle().set("trace");
+            logger.log(
+                &log::Record::builder()
+                    .level(log::Level::Warn)
+                    .target("my_app")
+                    .args(format_args!("disk is \"full\""))
+                    .key_values(&[("free", "0 B"), ("disk", "sda1")])
+                    .build(),
+            );
+        };
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        for format in [
+            Format::text(StyleConfig::SingleLine),
+            Format::json(),
+            Format::logfmt(),
+            Format::pretty_json("---\n"),
+        ] {
+            record(&Logger::new(format, Sink::channel(tx.clone())));
+        }
+
+        let lines = rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(lines[0], r#"WARN  [my_app] disk is "full""#);
+
+        let (time, json) = lines[1].split_once(r#"","level""#).unwrap();
+        assert!(time.starts_with(r#"{"schema_version":1,"time":""#) && time.ends_with('Z'));
+        assert_eq!(
+            json,
+            r#":"WARN","target":"my_app","message":"disk is \"full\"","fields":{"free":"0 B","disk":"sda1"}}"#
+        );
+
+        let (time, logfmt) = lines[2].split_once(' ').unwrap();
+        assert!(time.starts_with("time=") && time.ends_with('Z'));
+        assert_eq!(
+            logfmt,
+            r#"level=warn target=my_app msg="disk is \"full\"" free="0 B" disk=sda1"#
+        );
+
+        let (time, json) = lines[3]
+            .split_once(
+                r#"",
+"#,
+            )
+            .unwrap();
+        assert!(
+            time.starts_with("{\n  \"schema_version\": 1,\n  \"time\": \"") && time.ends_with('Z')
+        );
+        assert_eq!(
+            json,
+            r#"  "level": "WARN",
+  "target": "my_app",
+  "message": "disk is \"full\"",
+  "fields": {
+    "free": "0 B",
+    "disk": "sda1"
+  }
+}
+---"#
+        );
+    }
+    #[test]
+    fn middleware() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let options = Options::default().with_middleware(|mut record: crate::OwnedRecord| {
+            record.message = record.message.replace("hunter2", "***");
+            Some(record)
+        });
+        let logger = Logger::new(Format::logfmt(), Sink::channel(tx)).with_options(options);
+        logger.reload_handle().set("trace");
+
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target("my_app")
+                .args(format_args!("password is hunter2"))
+                .build(),
+        );
+        let line = rx.try_recv().unwrap();
+        assert!(line.ends_with(r#"msg="password is ***""#), "{}", line);
+    }
+
+    #[test]
+    fn options() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let options = Options::default()
+            .with_target_rewrite(TargetRewrite::new(&[("hyper::proto", "http")]))
+            .with_enable_predicate(|metadata| metadata.level() <= log::Level::Info);
+        let format = Format::logfmt().with_level_case(LevelCase::Upper);
+        let logger = Logger::new(format, Sink::channel(tx)).with_options(options);
+        logger.reload_handle().set("trace");
+
+        for level in [log::Level::Debug, log::Level::Info] {
+            logger.log(
+                &log::Record::builder()
+                    .level(level)
+                    .target("hyper::proto::h1")
+                    .args(format_args!("hello"))
+                    .build(),
+            );
+        }
+        let lines = rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+        assert!(
+            lines[0].ends_with("level=INFO target=http::h1 msg=hello"),
+            "{}",
+            lines[0]
+        );
+    }
+}
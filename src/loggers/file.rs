@@ -1,36 +1,69 @@
 use crate::{
-    filters::Filters,
-    options::{Options, StyleConfig, TimeConfig},
+    config::{LoggerConfig, LoggerKind},
+    date::DateTime,
+    filters::SharedFilters,
+    options::{ColorDepth, Options},
+    render::{Renderer, StderrMirror},
+    sync::Mutex,
+    ReloadHandle, Retention,
+};
+use std::{
+    ffi::{OsStr, OsString},
+    io::Write,
+    path::Path,
 };
-use std::{io::Write, path::Path, sync::Mutex};
 
 /// `std::io::Write` based logger, intended for `std::fs::File`
 pub struct FileLogger<W: Send + 'static> {
     options: Options,
-    filters: Filters,
+    filters: SharedFilters,
     path: Option<std::path::PathBuf>,
-    write: Mutex<W>,
+    write: Mutex<Writer<W>>,
+    // writes the footer of a file this logger opened, when it is dropped
+    close: Option<fn(&mut W) -> std::io::Result<()>>,
+    mirror: StderrMirror,
+}
+
+type Open<W> = Box<dyn FnOnce() -> std::io::Result<W> + Send>;
+
+/// The writer of a file logger, which may not have been created yet
+enum Writer<W> {
+    Ready(W),
+    Deferred(Open<W>),
+    Failed,
+}
+
+impl<W> Writer<W> {
+    /// Get the writer, creating it if it was deferred
+    fn get(&mut self) -> Option<&mut W> {
+        if matches!(self, Self::Deferred(..)) {
+            if let Self::Deferred(open) = std::mem::replace(self, Self::Failed) {
+                if let Ok(writer) = open() {
+                    *self = Self::Ready(writer)
+                }
+            }
+        }
+
+        match self {
+            Self::Ready(writer) => Some(writer),
+            _ => None,
+        }
+    }
 }
 
 impl FileLogger<std::fs::File> {
     /// Create a new file logger that truncates the log file before starting.
+    #[allow(clippy::suspicious_open_options)]
     pub fn truncate(
         options: impl Into<Options>,
         path: impl AsRef<Path>,
     ) -> Result<Self, crate::Error> {
-        let options = options.into();
-
-        let path = path.as_ref();
-        std::fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .open(path)
-            .map(|file| {
-                let mut this = Self::new(options, file);
-                this.path.replace(path.into());
-                this
-            })
-            .map_err(crate::Error::FileLogger)
+        Self::open(options.into(), path.as_ref().into(), |path| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .open(path)
+        })
     }
 
     /// Create a new file logger that appends to the log file.
@@ -38,33 +71,57 @@ impl FileLogger<std::fs::File> {
         options: impl Into<Options>,
         path: impl AsRef<Path>,
     ) -> Result<Self, crate::Error> {
-        let options = options.into();
-
-        let path = path.as_ref();
-        std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .write(true)
-            .open(&path)
-            .map(|file| {
-                let mut this = Self::new(options, file);
-                this.path.replace(path.into());
-                this
-            })
-            .map_err(crate::Error::FileLogger)
+        Self::open(options.into(), path.as_ref().into(), |path| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+        })
     }
 
     /// Create a new file logger with a timestamp appended to its name.
     ///
     /// Example:
     /// * `out.log` will become `out_1587429534.log`
+    /// * `out.log.gz` will become `out_1587429534.log.gz`
+    /// * `app.v2.log` will become `app.v2_1587429534.log`
     /// * `out` will become `out_1587429534`
     pub fn timestamp(
         options: impl Into<Options>,
         path: impl AsRef<Path>,
+    ) -> Result<Self, crate::Error> {
+        Self::timestamp_with(options, path, timestamp_name)
+    }
+
+    /// Create a new file logger with a timestamp in its name, using the provided `naming` function.
+    ///
+    /// The function is given the file stem, the current UNIX timestamp and the extension (if any),
+    /// and it should return the new file name. The file is created next to the provided `path`.
+    ///
+    /// The extension is everything after the last `.` in the file name, e.g. `log` for `app.v2.log`,
+    /// except that a compressed file's includes the one before it, e.g. `log.gz` for `out.log.gz`.
+    ///
+    /// ```rust,no_run
+    /// # use alto_logger::{FileLogger, Options};
+    /// let logger = FileLogger::timestamp_with(Options::default(), "logs/out.log", |stem, ts, ext| {
+    ///     let mut name = std::ffi::OsString::from(format!("{}-", ts));
+    ///     name.push(stem);
+    ///     if let Some(ext) = ext {
+    ///         name.push(".");
+    ///         name.push(ext);
+    ///     }
+    ///     name
+    /// });
+    /// // will become logs/1587429534-out.log
+    /// ```
+    pub fn timestamp_with(
+        options: impl Into<Options>,
+        path: impl AsRef<Path>,
+        naming: impl FnOnce(&OsStr, u64, Option<&OsStr>) -> OsString,
     ) -> Result<Self, crate::Error> {
         let options = options.into();
 
+        #[allow(clippy::io_other_error)]
         fn io_err(reason: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> crate::Error {
             crate::Error::FileLogger(std::io::Error::new(std::io::ErrorKind::Other, reason))
         }
@@ -74,49 +131,130 @@ impl FileLogger<std::fs::File> {
             .map_err(io_err)?
             .as_secs();
 
-        // Working with paths is Rust isn't the most fun thing
         let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io_err("no file name provided"))?;
 
-        let file_stem = path
-            .file_stem()
-            .ok_or_else(|| io_err("no file stem provided"))?;
-
-        let file_ext = path
-            .extension()
-            .and_then(|s| s.to_str())
-            .unwrap_or_default();
-
-        let new_name = file_stem
-            .to_str()
-            .map(|s| {
-                let mut file = s.to_string() + "_" + &now.to_string();
-                if !file_ext.is_empty() {
-                    file.push('.');
-                    file.push_str(file_ext);
-                }
-                file
-            })
-            .map(std::path::PathBuf::from)
-            .ok_or_else(|| io_err("cannot create new name"))?;
+        let (stem, ext) = split_extensions(file_name);
+        let path = path.with_file_name(naming(stem, now, ext.as_deref()));
+
+        Self::open(options, path, |path| {
+            std::fs::OpenOptions::new()
+                .create_new(true)
+                .write(true)
+                .open(path)
+        })
+    }
+
+    fn open(
+        options: Options,
+        path: std::path::PathBuf,
+        open: fn(&Path) -> std::io::Result<std::fs::File>,
+    ) -> Result<Self, crate::Error> {
+        let header = options.file_header;
+        let open = move |path: &Path| {
+            let mut file = open(path)?;
+            write_header(header, &mut file)?;
+            Ok(file)
+        };
+
+        let writer = if options.lazy_create {
+            let path = path.clone();
+            Writer::Deferred(Box::new(move || open(&path)))
+        } else {
+            Writer::Ready(open(&path).map_err(crate::Error::FileLogger)?)
+        };
+
+        fn close(file: &mut std::fs::File) -> std::io::Result<()> {
+            write_footer(true, file)?;
+            file.flush()
+        }
 
-        let path = path.with_file_name(new_name);
+        Ok(Self {
+            filters: SharedFilters::from_options(&options),
+            mirror: StderrMirror::new(&options),
+            close: options.file_footer.then_some(close as _),
+            options,
+            path: Some(path),
+            write: Mutex::new(writer),
+        })
+    }
+
+    /// Create a new file logger in `dir`, named after the current executable and today's (UTC) date.
+    ///
+    /// e.g. `logs/my_app-2024-05-01.log`
+    ///
+    /// The directory is created if it doesn't exist, and the file is appended to if it already exists.
+    ///
+    /// Old files can be removed with [`FileLogger::clean_directory`](#method.clean_directory)
+    pub fn in_directory(
+        options: impl Into<Options>,
+        dir: impl AsRef<Path>,
+    ) -> Result<Self, crate::Error> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).map_err(crate::Error::FileLogger)?;
+
+        let name = format!("{}-{}.log", bin_name(), DateTime::now().date());
+        Self::append(options, dir.join(name))
+    }
+
+    /// Remove the log files in `dir` created by [`FileLogger::in_directory`](#method.in_directory) that
+    /// were last modified more than `max_age` ago.
+    ///
+    /// Only files named after the current executable are considered.
+    ///
+    /// This returns how many files were removed.
+    pub fn clean_directory(
+        dir: impl AsRef<Path>,
+        max_age: std::time::Duration,
+    ) -> Result<usize, crate::Error> {
+        Retention::new(dir.as_ref())
+            .prefix(format!("{}-", bin_name()))
+            .max_age(max_age)
+            .apply()
+    }
+
+    /// Apply this retention policy now, never removing this logger's file.
+    ///
+    /// The policy can also be applied periodically with [`Retention::spawn`](struct.Retention.html#method.spawn)
+    pub fn with_retention(self, retention: Retention) -> Self {
+        let retention = match &self.path {
+            Some(path) => retention.keep(path),
+            None => retention,
+        };
+        let _ = retention.apply();
+        self
+    }
 
-        std::fs::OpenOptions::new()
-            .create_new(true)
-            .write(true)
-            .open(&path)
-            .map(|file| {
-                let mut this = Self::new(options, file);
-                this.path.replace(path);
-                this
-            })
-            .map_err(crate::Error::FileLogger)
+    /// Maintain a link named `name` next to this logger's file, pointing at it
+    ///
+    /// e.g. with `latest.log`, `tail -F logs/latest.log` follows the newest file from
+    /// [`FileLogger::timestamp`](#method.timestamp) or [`FileLogger::in_directory`](#method.in_directory).
+    ///
+    /// This is a symlink on unix. Elsewhere it's a hard link, or a copy of the file if that isn't supported.
+    pub fn with_latest_link(self, name: impl AsRef<Path>) -> Self {
+        if let Some(path) = &self.path {
+            let _ = link_latest(&path.with_file_name(name.as_ref()), path);
+        }
+        self
     }
 
     /// Get the path if one was created/provided
     pub fn file_name(&self) -> Option<&Path> {
         self.path.as_deref()
     }
+
+    pub(crate) fn describe(&self) -> LoggerConfig {
+        LoggerConfig {
+            kind: LoggerKind::File(self.path.clone()),
+            options: Some(self.options.clone()),
+            filters: Some(self.filters.to_string()),
+            console: None,
+            shared: Some(self.filters.clone()),
+            parents: Vec::new(),
+        }
+    }
 }
 
 impl<W: Write + Send + 'static> FileLogger<W> {
@@ -125,99 +263,304 @@ impl<W: Write + Send + 'static> FileLogger<W> {
         crate::init(self)
     }
 
+    /// Get a handle for replacing this logger's filters at runtime
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle::new(self.filters.clone())
+    }
+
     /// Create a new file logger for this writer
+    ///
+    /// ***Note*** The file header and footer of the options aren't written, as this logger doesn't open a file
     pub fn new(options: impl Into<Options>, writer: W) -> Self {
         let options = options.into();
         Self {
+            filters: SharedFilters::from_options(&options),
+            mirror: StderrMirror::new(&options),
             options,
-            filters: Filters::from_env(),
-            write: Mutex::new(writer),
+            write: Mutex::new(Writer::Ready(writer)),
             path: None,
+            close: None,
         }
     }
 
     fn print(&self, record: &log::Record<'_>) {
-        let Options {
-            time: timestamp,
-            style,
-            ..
-        } = &self.options;
-
-        let mut file = self.write.lock().unwrap();
-        let _ = write!(file, "{:<5}", record.level());
-
-        match timestamp {
-            TimeConfig::None => {}
-            TimeConfig::Unix => {
-                let elapsed = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .expect("time should not go backwards");
-                let _ = write!(file, " {:04}", elapsed.as_secs(),);
-            }
-
-            TimeConfig::Relative(start) => {
-                let elapsed = start.elapsed();
-                let _ = write!(
-                    file,
-                    " {:04}.{:09}s",
-                    elapsed.as_secs(),
-                    elapsed.subsec_nanos()
-                );
-            }
-
-            TimeConfig::Timing(inner) => {
-                let inner = &mut *inner.lock().unwrap();
-                if let Some(start) = &*inner {
-                    let elapsed = start.elapsed();
-                    let _ = write!(
-                        file,
-                        " {:04}.{:09}s",
-                        elapsed.as_secs(),
-                        elapsed.subsec_nanos()
-                    );
-                } else {
-                    let _ = write!(file, " {:04}.{:09}s", 0, 0);
-                }
-                inner.replace(std::time::Instant::now());
-            }
-
-            #[cfg(feature = "time")]
-            TimeConfig::DateTime(format) => {
-                if let Ok(now) = time::OffsetDateTime::now_utc().format(&format) {
-                    let _ = write!(file, " {}", now);
-                }
-            }
+        let mut write = crate::sync::lock(&self.write);
+        if let Some(file) = write.get() {
+            Renderer::new(&self.options, ColorDepth::TrueColor)
+                .render(record, &mut termcolor::NoColor::new(file));
         }
-
-        let _ = write!(file, " [");
-        let _ = write!(file, "{}", record.target());
-        let _ = write!(file, "]");
-
-        if let StyleConfig::MultiLine = style {
-            let _ = writeln!(file);
-            let _ = write!(file, "⤷");
-        }
-
-        let _ = write!(file, " {}", record.args());
-        let _ = writeln!(file);
+        drop(write);
+        self.mirror.write(&self.options, record);
     }
 }
 
 impl<W: Write + Send + 'static> log::Log for FileLogger<W> {
     #[inline]
     fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
-        self.filters.is_enabled(metadata)
+        self.options.allows(&self.filters, metadata)
     }
 
     #[inline]
     fn log(&self, record: &log::Record<'_>) {
-        if self.enabled(record.metadata()) {
-            self.print(record);
+        if self.enabled(record.metadata()) && self.filters.matches_message(record) {
+            self.options.middleware.apply(record, |record| {
+                crate::tracker::record(record.metadata());
+                self.print(record);
+            });
         }
     }
 
     #[inline]
     fn flush(&self) {
-        let _ = self.write.lock().unwrap().flush();
+        if let Writer::Ready(file) = &mut *crate::sync::lock(&self.write) {
+            let _ = file.flush();
+        }
+    }
+}
+
+impl<W: Send + 'static> Drop for FileLogger<W> {
+    fn drop(&mut self) {
+        let write = crate::sync::get_mut(&mut self.write);
+        if let (Some(close), Writer::Ready(file)) = (self.close, write) {
+            let _ = close(file);
+        }
+    }
+}
+
+/// Write the header line for a file, if there is a `version`
+pub(crate) fn write_header(version: Option<&str>, out: &mut impl Write) -> std::io::Result<()> {
+    let Some(version) = version else {
+        return Ok(());
+    };
+
+    let cmdline = std::env::args_os()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    writeln!(
+        out,
+        "-- started {} UTC version={} pid={} cmdline={:?}",
+        DateTime::now(),
+        version,
+        std::process::id(),
+        cmdline
+    )
+}
+
+/// Write the footer line for a file, if enabled
+pub(crate) fn write_footer(footer: bool, out: &mut impl Write) -> std::io::Result<()> {
+    if footer {
+        writeln!(out, "-- stopped {} UTC", DateTime::now())?;
+    }
+    Ok(())
+}
+
+/// Atomically replace `link` with a link to `target`
+pub(crate) fn link_latest(link: &Path, target: &Path) -> std::io::Result<()> {
+    let mut tmp = OsString::from(".");
+    tmp.push(link.file_name().unwrap_or_default());
+    tmp.push(".tmp");
+    let tmp = link.with_file_name(tmp);
+    let _ = std::fs::remove_file(&tmp);
+
+    #[cfg(unix)]
+    {
+        // a relative link keeps working if the directory is moved
+        let target = match (link.parent(), target.parent(), target.file_name()) {
+            (Some(l), Some(r), Some(name)) if l == r => Path::new(name),
+            _ => target,
+        };
+        std::os::unix::fs::symlink(target, &tmp)?;
+    }
+
+    #[cfg(not(unix))]
+    std::fs::hard_link(target, &tmp).or_else(|_| std::fs::copy(target, &tmp).map(drop))?;
+
+    std::fs::rename(&tmp, link)
+}
+
+/// The file stem of the current executable
+fn bin_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| String::from("log"))
+}
+
+/// The default naming for [`FileLogger::timestamp`]: `stem_timestamp.ext`
+fn timestamp_name(stem: &OsStr, timestamp: u64, ext: Option<&OsStr>) -> OsString {
+    let mut name = stem.to_os_string();
+    name.push(format!("_{}", timestamp));
+    if let Some(ext) = ext {
+        name.push(".");
+        name.push(ext);
+    }
+    name
+}
+
+/// Extensions of compressed files, which are kept with the extension before them (e.g. `log.gz`)
+const COMPRESSED: &[&str] = &["gz", "bz2", "xz", "zst", "lz4"];
+
+/// Split a file name into its stem and its extension (e.g. `app.v2` and `log`)
+///
+/// The extension of a compressed file includes the one before it (e.g. `out` and `log.gz`).
+///
+/// This works on the `OsStr` so non-UTF-8 names are preserved
+fn split_extensions(file_name: &OsStr) -> (&OsStr, Option<OsString>) {
+    let path = Path::new(file_name);
+    let (Some(stem), Some(ext)) = (path.file_stem(), path.extension()) else {
+        return (file_name, None);
+    };
+
+    let compressed = ext
+        .to_str()
+        .is_some_and(|ext| COMPRESSED.iter().any(|c| ext.eq_ignore_ascii_case(c)));
+    let inner = Path::new(stem);
+    if let (true, Some(inner_stem), Some(inner_ext)) =
+        (compressed, inner.file_stem(), inner.extension())
+    {
+        let mut compound = inner_ext.to_os_string();
+        compound.push(".");
+        compound.push(ext);
+        return (inner_stem, Some(compound));
+    }
+
+    (stem, Some(ext.to_os_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_names() {
+        let cases = [
+            ("out.log", "out_1234.log"),
+            ("out.log.gz", "out_1234.log.gz"),
+            ("out", "out_1234"),
+            (".hidden", ".hidden_1234"),
+            (".hidden.log", ".hidden_1234.log"),
+            ("app.v2.log", "app.v2_1234.log"),
+            ("app.v2.log.gz", "app.v2_1234.log.gz"),
+            ("archive.gz", "archive_1234.gz"),
+        ];
+
+        for (input, expected) in cases {
+            let (stem, ext) = split_extensions(OsStr::new(input));
+            let name = timestamp_name(stem, 1234, ext.as_deref());
+            assert_eq!(name, OsStr::new(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn lazy_create() {
+        use log::Log as _;
+
+        let path =
+            std::env::temp_dir().join(format!("alto_logger_lazy_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let logger = FileLogger::append(Options::default().with_lazy_create(true), &path).unwrap();
+        logger.reload_handle().set("info");
+        assert_eq!(logger.file_name(), Some(&*path));
+
+        let log = |level| logger.log(&log::Record::builder().level(level).build());
+        log(log::Level::Debug);
+        logger.flush();
+        assert!(!path.exists());
+
+        log(log::Level::Info);
+        logger.flush();
+        assert!(path.exists());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn header_and_footer() {
+        let path =
+            std::env::temp_dir().join(format!("alto_logger_header_{}.log", std::process::id()));
+        let options = Options::default()
+            .with_file_header(Some("1.2.3"))
+            .with_file_footer(true);
+        drop(FileLogger::truncate(options.clone(), &path).unwrap());
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        let lines = data.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("-- started "));
+        assert!(lines[0].contains(&format!(
+            " version=1.2.3 pid={} cmdline=",
+            std::process::id()
+        )));
+        assert!(lines[1].starts_with("-- stopped ") && lines[1].ends_with(" UTC"));
+
+        // a given writer isn't a file that was opened
+        let file = std::fs::File::create(&path).unwrap();
+        drop(FileLogger::new(options, file));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn poisoned() {
+        use log::Log as _;
+        use std::sync::{Arc, Mutex};
+
+        // panics while the writer is locked, on the first record
+        #[derive(Clone, Default)]
+        struct Panics(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Panics {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                let mut data = self.0.lock().unwrap();
+                if data.is_empty() {
+                    data.push(b'!');
+                    drop(data);
+                    panic!("poison the writer")
+                }
+                data.write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let sink = Panics::default();
+        let logger = FileLogger::new(crate::options::StyleConfig::SingleLine, sink.clone());
+        logger.reload_handle().set("trace");
+        let log = |msg: &str| {
+            logger.log(
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target("foo")
+                    .args(format_args!("{}", msg))
+                    .build(),
+            )
+        };
+
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| log("boom")));
+        assert!(panicked.is_err());
+        #[cfg(not(any(loom, feature = "parking_lot")))]
+        assert!(logger.write.is_poisoned());
+
+        log("still logging");
+        assert_eq!(&*sink.0.lock().unwrap(), b"!INFO  [foo] still logging\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_names() {
+        use std::os::unix::ffi::OsStrExt as _;
+
+        let input = OsStr::from_bytes(b"out\xFF.log");
+        let (stem, ext) = split_extensions(input);
+        let name = timestamp_name(stem, 1234, ext.as_deref());
+        assert_eq!(name.as_bytes(), b"out\xFF_1234.log");
     }
 }
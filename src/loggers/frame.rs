@@ -0,0 +1,213 @@
+use crate::{sync::Mutex, OwnedRecord};
+use std::sync::Arc;
+
+/// A logger that holds records until the end of a frame, so logging never does I/O in the middle of one
+///
+/// At most `budget` records are kept per frame. Repeats of the previous record are compacted into it,
+/// e.g. `hello (x3)`, and records over the budget are dropped and counted in a record written after them.
+///
+/// ```rust,no_run
+/// # use alto_logger::{FrameLogger, TermLogger};
+/// let logger = FrameLogger::new(TermLogger::default(), 256);
+/// let frames = logger.handle();
+/// logger.init().unwrap();
+///
+/// loop {
+///     // update and render, logging as usual
+///     log::debug!("frame done");
+///     frames.end_frame();
+///     # break;
+/// }
+/// ```
+pub struct FrameLogger {
+    frame: Arc<Frame>,
+}
+
+/// A handle for ending the frames of a [`FrameLogger`](struct.FrameLogger.html), which can be kept after it is installed
+#[derive(Clone)]
+pub struct FrameHandle {
+    frame: Arc<Frame>,
+}
+
+struct Frame {
+    inner: Box<dyn log::Log>,
+    budget: usize,
+    pending: Mutex<Pending>,
+}
+
+#[derive(Default)]
+struct Pending {
+    records: Vec<(OwnedRecord, u64)>,
+    dropped: u64,
+}
+
+impl FrameLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Wrap `inner`, keeping at most `budget` records per frame
+    pub fn new(inner: impl log::Log + 'static, budget: usize) -> Self {
+        Self {
+            frame: Arc::new(Frame {
+                inner: Box::new(inner),
+                budget,
+                pending: Mutex::default(),
+            }),
+        }
+    }
+
+    /// Get a handle for ending frames, which can be kept after this logger is installed
+    pub fn handle(&self) -> FrameHandle {
+        FrameHandle {
+            frame: Arc::clone(&self.frame),
+        }
+    }
+
+    /// Write the records of this frame to the wrapped logger
+    pub fn end_frame(&self) {
+        self.frame.end()
+    }
+}
+
+impl FrameHandle {
+    /// Write the records of this frame to the wrapped logger
+    pub fn end_frame(&self) {
+        self.frame.end()
+    }
+
+    /// How many records are waiting for the end of the frame
+    pub fn pending(&self) -> usize {
+        crate::sync::lock(&self.frame.pending).records.len()
+    }
+}
+
+impl Frame {
+    fn push(&self, record: &log::Record<'_>) {
+        let mut pending = crate::sync::lock(&self.pending);
+        if let Some((last, count)) = pending.records.last_mut() {
+            if last.level == record.level()
+                && last.target == record.target()
+                && record.args().to_string() == last.message
+            {
+                *count += 1;
+                return;
+            }
+        }
+
+        match pending.records.len() < self.budget {
+            true => pending.records.push((OwnedRecord::from_record(record), 1)),
+            false => pending.dropped += 1,
+        }
+    }
+
+    fn end(&self) {
+        let Pending { records, dropped } = std::mem::take(&mut *crate::sync::lock(&self.pending));
+
+        for (mut record, count) in records {
+            if count > 1 {
+                record.message = format!("{} (x{})", record.message, count);
+            }
+            // the record is written with the time it was logged, rather than the end of the frame
+            crate::record::replaying(record.timestamp, record.thread.as_deref(), || {
+                record.with_record(|record| self.inner.log(record))
+            });
+        }
+
+        if dropped > 0 {
+            self.inner.log(
+                &log::Record::builder()
+                    .level(log::Level::Warn)
+                    .target("alto_logger::frame")
+                    .args(format_args!(
+                        "dropped {} records over the budget of {} for this frame",
+                        dropped, self.budget
+                    ))
+                    .build(),
+            );
+        }
+    }
+}
+
+impl log::Log for FrameLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.frame.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.frame.push(record)
+        }
+    }
+
+    fn flush(&self) {
+        self.frame.end();
+        self.frame.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        options::{StyleConfig, TimeConfig},
+        ChannelLogger, Filters, Options,
+    };
+    use log::Log as _;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn frames() {
+        let (capture, rx) = ChannelLogger::capture();
+        let logger = FrameLogger::new(capture, 3);
+        let handle = logger.handle();
+        let log =
+            |msg: &str| logger.log(&log::Record::builder().args(format_args!("{}", msg)).build());
+
+        for msg in ["a", "b", "b", "b", "c", "d", "e", "e"] {
+            log(msg);
+        }
+        assert_eq!(handle.pending(), 3);
+        assert!(rx.try_recv().is_err());
+
+        handle.end_frame();
+        assert_eq!(
+            rx.try_iter().map(|r| r.message).collect::<Vec<_>>(),
+            [
+                "a",
+                "b (x3)",
+                "c",
+                "dropped 3 records over the budget of 3 for this frame"
+            ]
+        );
+
+        log("f");
+        logger.end_frame();
+        assert_eq!(rx.try_iter().map(|r| r.message).collect::<Vec<_>>(), ["f"]);
+    }
+
+    #[test]
+    fn timestamps() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_time(TimeConfig::relative_local())
+            .with_filters(Filters::parse("trace"));
+        let logger = FrameLogger::new(ChannelLogger::new(options, tx), 3);
+
+        let start = SystemTime::now() - Duration::from_secs(10);
+        for (millis, msg) in [(0, "a"), (2500, "b")] {
+            crate::record::replaying(start + Duration::from_millis(millis), None, || {
+                logger.log(&log::Record::builder().args(format_args!("{}", msg)).build())
+            });
+        }
+        logger.end_frame();
+
+        // the time between the records is kept, rather than both having the end of the frame as their time
+        let lines = rx.try_iter().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("INFO  0000."), "{:?}", lines);
+        assert!(lines[1].starts_with("INFO  0002."), "{:?}", lines);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{record::OwnedRecord, sync::Mutex};
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+    time::Duration,
+};
+
+type Buffer = Arc<Mutex<Vec<(u64, OwnedRecord)>>>;
+
+thread_local! {
+    // the buffers for this thread, keyed by the logger that they belong to
+    static BUFFERS: RefCell<Vec<(usize, Buffer)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A logger that buffers records on each thread, and merges them in order on a collector thread
+///
+/// This avoids contention on the wrapped logger when many threads are logging.
+/// Records are written by the collector thread every `interval`, or when this logger is flushed.
+///
+/// ```rust,no_run
+/// # use alto_logger::{FileLogger, MergeLogger, Options};
+/// # use std::time::Duration;
+/// let file = FileLogger::append(Options::default(), "out.log").unwrap();
+/// MergeLogger::new(file, Duration::from_millis(50)).init().unwrap();
+/// ```
+pub struct MergeLogger {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    id: usize,
+    sequence: AtomicU64,
+    buffers: Mutex<Vec<Buffer>>,
+    inner: Box<dyn log::Log>,
+}
+
+impl MergeLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Wrap `inner`, writing the buffered records to it every `interval`
+    pub fn new(inner: impl log::Log + 'static, interval: Duration) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let shared = Arc::new(Shared {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            sequence: AtomicU64::new(0),
+            buffers: Mutex::default(),
+            inner: Box::new(inner),
+        });
+
+        let weak = Arc::downgrade(&shared);
+        std::thread::Builder::new()
+            .name(String::from("alto_logger collector"))
+            .spawn(move || collect(weak, interval))
+            .expect("spawn collector thread");
+
+        Self { shared }
+    }
+
+    fn buffer(&self) -> Buffer {
+        BUFFERS.with(|buffers| {
+            let mut buffers = buffers.borrow_mut();
+            if let Some((_, buffer)) = buffers.iter().find(|(id, _)| *id == self.shared.id) {
+                return Arc::clone(buffer);
+            }
+
+            let buffer = Buffer::default();
+            crate::sync::lock(&self.shared.buffers).push(Arc::clone(&buffer));
+            buffers.push((self.shared.id, Arc::clone(&buffer)));
+            buffer
+        })
+    }
+}
+
+impl Shared {
+    fn drain(&self) {
+        let mut buffers = crate::sync::lock(&self.buffers);
+
+        // every buffer is locked at the same time, so any record not yet buffered
+        // will get a sequence number after the records drained here
+        let mut locked = buffers
+            .iter()
+            .map(|buffer| crate::sync::lock(buffer))
+            .collect::<Vec<_>>();
+
+        let mut records = locked
+            .iter_mut()
+            .flat_map(|buffer| buffer.drain(..))
+            .collect::<Vec<_>>();
+        drop(locked);
+
+        // the thread that owned the buffer has exited
+        buffers.retain(|buffer| Arc::strong_count(buffer) > 1);
+
+        records.sort_unstable_by_key(|(seq, _)| *seq);
+        for (_, record) in records {
+            // the record is written with the time it was logged, rather than when it was merged
+            crate::record::replaying(record.timestamp, record.thread.as_deref(), || {
+                record.with_record(|record| self.inner.log(record))
+            });
+        }
+    }
+}
+
+fn collect(shared: Weak<Shared>, interval: Duration) {
+    loop {
+        std::thread::sleep(interval);
+        match shared.upgrade() {
+            Some(shared) => shared.drain(),
+            None => break,
+        }
+    }
+}
+
+impl log::Log for MergeLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.shared.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let record = OwnedRecord::from_record(record);
+        let buffer = self.buffer();
+        let mut buffer = crate::sync::lock(&buffer);
+        // the sequence has to be taken while the buffer is locked
+        let seq = self.shared.sequence.fetch_add(1, Ordering::Relaxed);
+        buffer.push((seq, record));
+    }
+
+    fn flush(&self) {
+        self.shared.drain();
+        self.shared.inner.flush();
+    }
+}
+
+impl Drop for MergeLogger {
+    fn drop(&mut self) {
+        self.shared.drain();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChannelLogger;
+    use log::Log as _;
+
+    #[test]
+    fn ordered_merge() {
+        let (capture, rx) = ChannelLogger::capture();
+        let logger = Arc::new(MergeLogger::new(capture, Duration::from_millis(1)));
+
+        let threads = (0..4)
+            .map(|thread| {
+                let logger = Arc::clone(&logger);
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        logger.log(
+                            &log::Record::builder()
+                                .args(format_args!("{} {}", thread, i))
+                                .build(),
+                        );
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        logger.flush();
+
+        let messages = rx.try_iter().map(|r| r.message).collect::<Vec<_>>();
+        assert_eq!(messages.len(), 400);
+
+        // each thread's records are in the order they were logged
+        let mut last = [None; 4];
+        for msg in messages.iter() {
+            let (thread, i) = msg.split_once(' ').unwrap();
+            let (thread, i) = (thread.parse::<usize>().unwrap(), i.parse::<u32>().unwrap());
+            assert!(last[thread] < Some(i));
+            last[thread] = Some(i);
+        }
+    }
+
+    #[test]
+    fn timestamps() {
+        let (capture, rx) = ChannelLogger::capture();
+        let logger = MergeLogger::new(capture, Duration::from_secs(60));
+
+        let logged = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_568_400);
+        crate::record::replaying(logged, None, || {
+            logger.log(&log::Record::builder().args(format_args!("a")).build())
+        });
+        logger.flush();
+        assert_eq!(rx.try_recv().unwrap().timestamp, logged);
+    }
+}
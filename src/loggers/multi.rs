@@ -1,11 +1,13 @@
-use crate::filters::Filters;
+use crate::{config::LoggerConfig, filters::SharedFilters, Filters, ReloadHandle};
 
 /// A multi-logger
 ///
 /// This allows for multiple loggers to be used
 pub struct MultiLogger {
-    filters: Filters,
-    loggers: Vec<Box<dyn log::Log>>,
+    filters: SharedFilters,
+    // the loggers, with the filters added by `with_filtered`
+    loggers: Vec<(Box<dyn log::Log>, Option<Filters>)>,
+    configs: Vec<LoggerConfig>,
 }
 
 impl MultiLogger {
@@ -15,19 +17,65 @@ impl MultiLogger {
     }
 
     /// Create a new Multilogger without any loggers
+    ///
+    /// Its own filters enable everything, until they're replaced with its [`ReloadHandle`](#method.reload_handle).
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self {
-            filters: Filters::from_env(),
+            filters: SharedFilters::new(Filters::parse("trace")),
             loggers: Vec::new(),
+            configs: Vec::new(),
         }
     }
 
     /// Add a logger to this multilogger
     pub fn with(mut self, logger: impl log::Log + 'static) -> Self {
-        self.loggers.push(Box::new(logger));
+        self.add(&logger);
+        self.loggers.push((Box::new(logger), None));
         self
     }
+
+    /// Add a logger to this multilogger, which only gets the records these filter directives also enable
+    ///
+    /// The directives use the same syntax as `RUST_LOG`. They apply on top of this logger's filters,
+    /// and the added logger's own filters, so those have to enable the records too.
+    ///
+    /// ```rust,no_run
+    /// # use alto_logger::{FileLogger, Filters, MultiLogger, Options, TermLogger};
+    /// let everything = || Options::default().with_filters(Filters::parse("trace"));
+    /// let logger = MultiLogger::new()
+    ///     .with(FileLogger::append(everything(), "trace.log").unwrap())
+    ///     .with_filtered(TermLogger::new(everything()).unwrap(), "info");
+    /// logger.reload_handle().set("trace");
+    /// logger.init().unwrap();
+    /// ```
+    pub fn with_filtered(mut self, logger: impl log::Log + 'static, directives: &str) -> Self {
+        self.add(&logger);
+        self.loggers
+            .push((Box::new(logger), Some(Filters::parse(directives))));
+        self
+    }
+
+    /// Get a handle for replacing this logger's filters at runtime
+    ///
+    /// These filters apply before the filters of the loggers it contains,
+    /// so a record is only written by a logger that both of them enable.
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle::new(self.filters.clone())
+    }
+
+    /// Describe `logger`, whose records are also limited by this logger's filters
+    fn add<L: log::Log + 'static>(&mut self, logger: &L) {
+        let configs = crate::config::describe(logger);
+        self.configs.extend(configs.into_iter().map(|mut config| {
+            config.parents.push(self.filters.clone());
+            config
+        }));
+    }
+
+    pub(crate) fn describe(&self) -> Vec<LoggerConfig> {
+        self.configs.clone()
+    }
 }
 
 impl log::Log for MultiLogger {
@@ -38,15 +86,109 @@ impl log::Log for MultiLogger {
 
     #[inline]
     fn log(&self, record: &log::Record<'_>) {
-        for logger in &self.loggers {
-            logger.log(record);
+        // the `log` macros don't check `enabled`
+        if !self.filters.is_enabled(record.metadata()) || !self.filters.matches_message(record) {
+            return;
+        }
+
+        for (logger, filters) in &self.loggers {
+            if filters
+                .as_ref()
+                .is_none_or(|filters| filters.is_record_enabled(record))
+            {
+                logger.log(record);
+            }
         }
     }
 
     #[inline]
     fn flush(&self) {
-        for logger in &self.loggers {
+        for (logger, _) in &self.loggers {
             logger.flush();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChannelLogger, Options};
+    use log::Log as _;
+
+    #[test]
+    fn filtered() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let options = || Options::default().with_filters(Filters::parse("trace"));
+        let logger = MultiLogger::new()
+            .with(ChannelLogger::new(options(), tx.clone()))
+            .with_filtered(ChannelLogger::new(options(), tx), "info,noisy=off");
+
+        for (level, target) in [
+            (log::Level::Trace, "my_app"),
+            (log::Level::Info, "my_app"),
+            (log::Level::Error, "noisy"),
+        ] {
+            logger.log(
+                &log::Record::builder()
+                    .level(level)
+                    .target(target)
+                    .args(format_args!("{}", level))
+                    .build(),
+            );
+        }
+
+        let lines = rx.try_iter().collect::<Vec<_>>();
+        let messages = lines
+            .iter()
+            .map(|line| line.rsplit(' ').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["TRACE", "INFO", "INFO", "ERROR"]);
+    }
+
+    #[test]
+    fn reload() {
+        let logger = MultiLogger::new();
+        let handle = logger.reload_handle();
+        let enabled = |level| {
+            logger.enabled(
+                &log::Metadata::builder()
+                    .target("my_app")
+                    .level(level)
+                    .build(),
+            )
+        };
+
+        handle.set("my_app=info");
+        assert!(enabled(log::Level::Info));
+        assert!(!enabled(log::Level::Debug));
+
+        handle.set("my_app=trace");
+        assert!(enabled(log::Level::Trace));
+    }
+
+    #[test]
+    fn reload_log() {
+        let (channel, rx) = ChannelLogger::capture();
+        let logger = MultiLogger::new().with(channel);
+        let log = |level| {
+            logger.log(
+                &log::Record::builder()
+                    .level(level)
+                    .target("my_app")
+                    .args(format_args!("{}", level))
+                    .build(),
+            )
+        };
+
+        // everything is enabled until its filters are replaced
+        log(log::Level::Trace);
+        logger.reload_handle().set("my_app=info");
+        log(log::Level::Debug);
+        log(log::Level::Info);
+        logger.reload_handle().set("off");
+        log(log::Level::Error);
+
+        let messages = rx.try_iter().map(|r| r.message).collect::<Vec<_>>();
+        assert_eq!(messages, ["TRACE", "INFO"]);
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{
+    record::OwnedRecord,
+    sync::{Condvar, Mutex},
+};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// What an [`AsyncLogger`](struct.AsyncLogger.html) does when its queue is full
+///
+/// ***Note*** Defaults to `Block`
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wait for room in the queue. No records are lost, but logging can stall
+    #[default]
+    Block,
+    /// Drop the record being logged
+    DropNewest,
+    /// Drop the oldest record in the queue to make room for the record being logged
+    DropOldest,
+}
+
+/// A logger that queues records, writing them to the wrapped logger on a background thread
+///
+/// ```rust,no_run
+/// # use alto_logger::{AsyncLogger, FileLogger, Options, Overflow};
+/// let file = FileLogger::append(Options::default(), "out.log").unwrap();
+/// AsyncLogger::new(file, 1024, Overflow::DropOldest).init().unwrap();
+/// ```
+pub struct AsyncLogger {
+    queue: Arc<Queue>,
+}
+
+struct Queue {
+    state: Mutex<State>,
+    // signaled when a record is queued or the logger is closed
+    pushed: Condvar,
+    // signaled when a record is written
+    popped: Condvar,
+    capacity: usize,
+    overflow: Overflow,
+    dropped: AtomicU64,
+    inner: Box<dyn log::Log>,
+}
+
+#[derive(Default)]
+struct State {
+    records: VecDeque<OwnedRecord>,
+    writing: bool,
+    closed: bool,
+}
+
+impl AsyncLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Wrap `inner`, queueing at most `capacity` records and using `overflow` when the queue is full
+    pub fn new(inner: impl log::Log + 'static, capacity: usize, overflow: Overflow) -> Self {
+        let queue = Arc::new(Queue {
+            state: Mutex::default(),
+            pushed: Condvar::new(),
+            popped: Condvar::new(),
+            capacity: capacity.max(1),
+            overflow,
+            dropped: AtomicU64::new(0),
+            inner: Box::new(inner),
+        });
+
+        let worker = Arc::clone(&queue);
+        std::thread::Builder::new()
+            .name(String::from("alto_logger writer"))
+            .spawn(move || worker.run())
+            .expect("spawn writer thread");
+
+        Self { queue }
+    }
+
+    /// How many records have been dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Get a handle for observing the queue, which can be kept after this logger is installed
+    pub fn handle(&self) -> AsyncHandle {
+        AsyncHandle {
+            queue: Arc::clone(&self.queue),
+        }
+    }
+}
+
+/// A handle for observing the queue of an [`AsyncLogger`](struct.AsyncLogger.html)
+///
+/// Applications can use this to shed their own load, or log less, when logging can't keep up.
+///
+/// ```rust,no_run
+/// # use alto_logger::{AsyncLogger, FileLogger, Options, Overflow};
+/// # let file = FileLogger::append(Options::default(), "out.log").unwrap();
+/// let logger = AsyncLogger::new(file, 1024, Overflow::Block);
+/// let handle = logger.handle();
+/// logger.init().unwrap();
+///
+/// if handle.pending() > handle.capacity() / 2 {
+///     // slow down
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AsyncHandle {
+    queue: Arc<Queue>,
+}
+
+impl AsyncHandle {
+    /// How many records are waiting to be written
+    pub fn pending(&self) -> usize {
+        crate::sync::lock(&self.queue.state).records.len()
+    }
+
+    /// How many records can be queued before the overflow policy is used
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity
+    }
+
+    /// Whether the queue is full
+    pub fn is_saturated(&self) -> bool {
+        self.pending() >= self.capacity()
+    }
+
+    /// How many records have been dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Queue {
+    fn push(&self, record: OwnedRecord) {
+        let mut state = crate::sync::lock(&self.state);
+        if state.records.len() >= self.capacity {
+            match self.overflow {
+                Overflow::Block => {
+                    state = crate::sync::wait_while(&self.popped, state, |state| {
+                        state.records.len() >= self.capacity
+                    });
+                }
+                Overflow::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                Overflow::DropOldest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    state.records.pop_front();
+                }
+            }
+        }
+
+        state.records.push_back(record);
+        self.pushed.notify_one();
+    }
+
+    fn run(&self) {
+        let mut state = crate::sync::lock(&self.state);
+        loop {
+            state = crate::sync::wait_while(&self.pushed, state, |state| {
+                state.records.is_empty() && !state.closed
+            });
+
+            let Some(record) = state.records.pop_front() else {
+                break;
+            };
+
+            state.writing = true;
+            drop(state);
+            self.popped.notify_all();
+
+            // a panicking logger mustn't stop the worker, or leave `writing` set, or everything waiting on it would hang
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                // the record is written with the time it was logged, rather than when it was taken off the queue
+                crate::record::replaying(record.timestamp, record.thread.as_deref(), || {
+                    record.with_record(|record| self.inner.log(record))
+                })
+            }));
+
+            state = crate::sync::lock(&self.state);
+            state.writing = false;
+            self.popped.notify_all();
+        }
+    }
+
+    /// Wait until every queued record has been written
+    fn wait(&self) {
+        let state = crate::sync::lock(&self.state);
+        let _state = crate::sync::wait_while(&self.popped, state, |state| {
+            !state.records.is_empty() || state.writing
+        });
+    }
+}
+
+impl log::Log for AsyncLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.queue.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.queue.push(OwnedRecord::from_record(record));
+        }
+    }
+
+    fn flush(&self) {
+        self.queue.wait();
+        self.queue.inner.flush();
+    }
+}
+
+impl Drop for AsyncLogger {
+    fn drop(&mut self) {
+        self.queue.wait();
+        crate::sync::lock(&self.queue.state).closed = true;
+        self.queue.pushed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Log as _;
+    use std::sync::{
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Mutex,
+    };
+
+    // blocks on each record until the test allows it through
+    struct Gate {
+        entered: SyncSender<String>,
+        proceed: Mutex<Receiver<()>>,
+    }
+
+    impl log::Log for Gate {
+        fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+            true
+        }
+        fn log(&self, record: &log::Record<'_>) {
+            self.entered.send(record.args().to_string()).unwrap();
+            self.proceed.lock().unwrap().recv().unwrap();
+        }
+        fn flush(&self) {}
+    }
+
+    fn log(logger: &AsyncLogger, msg: &str) {
+        logger.log(&log::Record::builder().args(format_args!("{}", msg)).build());
+    }
+
+    fn run(overflow: Overflow) -> (Vec<String>, u64) {
+        let (entered, rx) = sync_channel(16);
+        let (proceed, gate) = sync_channel(16);
+        let logger = AsyncLogger::new(
+            Gate {
+                entered,
+                proceed: Mutex::new(gate),
+            },
+            2,
+            overflow,
+        );
+
+        let handle = logger.handle();
+
+        // the writer is now stuck on the first record, so the queue can fill up
+        log(&logger, "a");
+        assert_eq!(rx.recv().unwrap(), "a");
+        assert_eq!(handle.pending(), 0);
+        for msg in ["b", "c", "d"] {
+            log(&logger, msg);
+        }
+        assert_eq!(handle.pending(), 2);
+        assert!(handle.is_saturated());
+
+        for _ in 0..3 {
+            proceed.send(()).unwrap();
+        }
+        logger.flush();
+        assert_eq!(handle.pending(), 0);
+
+        let mut written = vec![String::from("a")];
+        written.extend(rx.try_iter());
+        (written, logger.dropped())
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(
+            run(Overflow::DropNewest),
+            (vec!["a".into(), "b".into(), "c".into()], 1)
+        );
+        assert_eq!(
+            run(Overflow::DropOldest),
+            (vec!["a".into(), "c".into(), "d".into()], 1)
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let inner = crate::ChannelLogger::records(tx);
+        inner.reload_handle().set("trace");
+        let logger = AsyncLogger::new(inner, 4, Overflow::Block);
+
+        let logged =
+            std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_714_568_400);
+        crate::record::replaying(logged, None, || log(&logger, "a"));
+        logger.flush();
+        assert_eq!(rx.recv().unwrap().timestamp, logged);
+    }
+
+    #[test]
+    fn threads() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let inner = crate::ChannelLogger::records(tx);
+        inner.reload_handle().set("trace");
+        let logger = AsyncLogger::new(inner, 4, Overflow::Block);
+
+        std::thread::scope(|s| {
+            std::thread::Builder::new()
+                .name(String::from("producer"))
+                .spawn_scoped(s, || log(&logger, "a"))
+                .unwrap();
+        });
+        logger.flush();
+        assert_eq!(rx.recv().unwrap().thread.as_deref(), Some("producer"));
+    }
+
+    // a threaded stress test rather than a loom model, as the worker thread is spawned with `std::thread`
+    #[test]
+    fn panicking_logger() {
+        struct Panics(Mutex<Vec<String>>);
+
+        impl log::Log for Panics {
+            fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record<'_>) {
+                let msg = record.args().to_string();
+                assert_ne!(msg, "boom");
+                self.0.lock().unwrap().push(msg);
+            }
+            fn flush(&self) {}
+        }
+
+        let written = Arc::new(Panics(Mutex::default()));
+        let logger = AsyncLogger::new(Forward(Arc::clone(&written)), 1, Overflow::Block);
+        std::thread::scope(|s| {
+            for i in 0..4 {
+                let logger = &logger;
+                s.spawn(move || {
+                    log(logger, "boom");
+                    log(logger, &i.to_string());
+                });
+            }
+        });
+        logger.flush();
+
+        let mut written = written.0.lock().unwrap().clone();
+        written.sort();
+        assert_eq!(written, ["0", "1", "2", "3"]);
+    }
+
+    struct Forward<T>(Arc<T>);
+
+    impl<T: log::Log> log::Log for Forward<T> {
+        fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+            self.0.enabled(metadata)
+        }
+        fn log(&self, record: &log::Record<'_>) {
+            self.0.log(record)
+        }
+        fn flush(&self) {}
+    }
+}
@@ -0,0 +1,434 @@
+use super::file::{link_latest, write_footer, write_header};
+use crate::{
+    date::DateTime,
+    filters::SharedFilters,
+    options::{ColorDepth, Options},
+    render::{Renderer, StderrMirror},
+    sync::Mutex,
+    ReloadHandle, Retention,
+};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// When a [`RollingLogger`](struct.RollingLogger.html) starts a new file, and how its files are named
+///
+/// ***Note*** Defaults to `Daily`
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// A file per (UTC) day, e.g. `app-2024-05-01.log`
+    #[default]
+    Daily,
+    /// A file per (UTC) hour, e.g. `app-2024-05-01-13.log`
+    Hourly,
+    /// A new file once the current one would exceed this many bytes, e.g. `app.1.log`, `app.2.log`
+    ///
+    /// The index continues from the highest existing file when the logger is created.
+    Size(u64),
+}
+
+/// What part of a rotation a file belongs to
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Period {
+    Day(String),
+    Hour(String, u8),
+    Index(u64),
+}
+
+impl Rotation {
+    fn period(self, now: &DateTime, index: u64) -> Period {
+        match self {
+            Self::Daily => Period::Day(now.date()),
+            Self::Hourly => Period::Hour(now.date(), now.hour),
+            Self::Size(..) => Period::Index(index),
+        }
+    }
+}
+
+impl Period {
+    fn file_name(&self, name: &str) -> String {
+        match self {
+            Self::Day(date) => format!("{}-{}.log", name, date),
+            Self::Hour(date, hour) => format!("{}-{}-{:02}.log", name, date, hour),
+            Self::Index(index) => format!("{}.{}.log", name, index),
+        }
+    }
+}
+
+/// File logger which starts a new file each day, hour or once the file reaches a size
+///
+/// The files are created in a directory, named after `name` and the [`Rotation`](enum.Rotation.html).
+///
+/// The period is checked for each record, so the first record after midnight
+/// goes to the new day's file, even if nothing was logged exactly at midnight.
+///
+/// ```rust,no_run
+/// # use alto_logger::{Options, RollingLogger, Rotation};
+/// RollingLogger::new(Options::default(), "logs", "my_app", Rotation::Hourly)
+///     .unwrap()
+///     .init()
+///     .unwrap();
+/// ```
+pub struct RollingLogger {
+    options: Options,
+    filters: SharedFilters,
+    dir: PathBuf,
+    name: String,
+    rotation: Rotation,
+    retention: Option<Retention>,
+    latest: Option<PathBuf>,
+    state: Mutex<State>,
+    mirror: StderrMirror,
+}
+
+struct State {
+    file: File,
+    path: PathBuf,
+    period: Period,
+    size: u64,
+    buf: Vec<u8>,
+    // a new file isn't tried again until then, after it couldn't be opened
+    retry: Option<Instant>,
+}
+
+/// How long to keep writing to the current file after a new one couldn't be opened
+const ROLL_RETRY: Duration = Duration::from_secs(1);
+
+impl RollingLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Create a new rolling logger, writing files in `dir` named after `name`
+    ///
+    /// The directory is created if it doesn't exist, and the current file is appended to if it already exists.
+    pub fn new(
+        options: impl Into<Options>,
+        dir: impl Into<PathBuf>,
+        name: impl Into<String>,
+        rotation: Rotation,
+    ) -> Result<Self, crate::Error> {
+        let (dir, name) = (dir.into(), name.into());
+        std::fs::create_dir_all(&dir).map_err(crate::Error::FileLogger)?;
+
+        let index = match rotation {
+            Rotation::Size(..) => last_index(&dir, &name).unwrap_or(1),
+            _ => 0,
+        };
+        let period = rotation.period(&DateTime::now(), index);
+        let options = options.into();
+        let path = dir.join(period.file_name(&name));
+        let file = open(&path, &options).map_err(|source| crate::Error::RotationFailed {
+            path: path.clone(),
+            source,
+        })?;
+
+        Ok(Self {
+            filters: SharedFilters::from_options(&options),
+            mirror: StderrMirror::new(&options),
+            options,
+            state: Mutex::new(State {
+                size: file.metadata().map(|md| md.len()).unwrap_or_default(),
+                file,
+                path,
+                period,
+                buf: Vec::new(),
+                retry: None,
+            }),
+            dir,
+            name,
+            rotation,
+            retention: None,
+            latest: None,
+        })
+    }
+
+    /// Maintain a link named `name` in the directory, pointing at the current file
+    ///
+    /// e.g. with `latest.log`, `tail -F logs/latest.log` always follows the current file.
+    ///
+    /// This is a symlink on unix. Elsewhere it's a hard link, or a copy of the file if that isn't supported.
+    pub fn with_latest_link(mut self, name: impl AsRef<Path>) -> Self {
+        let latest = self.dir.join(name);
+        let _ = link_latest(&latest, &crate::sync::get_mut(&mut self.state).path);
+        self.latest.replace(latest);
+        self
+    }
+
+    /// Apply this retention policy now and whenever a new file is started, never removing the current file
+    ///
+    /// Only the files for this logger are considered, unless the policy has its own prefix.
+    ///
+    /// ```rust,no_run
+    /// # use alto_logger::{Options, Retention, RollingLogger, Rotation};
+    /// // keep at most 10 files, and at most 100MiB of logs
+    /// let logger = RollingLogger::new(Options::default(), "logs", "my_app", Rotation::Size(10 << 20))
+    ///     .unwrap()
+    ///     .with_retention(Retention::new("logs").max_files(10).max_total_size(100 << 20));
+    /// ```
+    pub fn with_retention(mut self, retention: Retention) -> Self {
+        let retention = match retention.has_prefix() {
+            true => retention,
+            false => retention.prefix(&*self.name),
+        };
+        let _ = retention
+            .clone()
+            .keep(&crate::sync::get_mut(&mut self.state).path)
+            .apply();
+        self.retention.replace(retention);
+        self
+    }
+
+    /// Get the path of the file currently being written to
+    pub fn current_file(&self) -> PathBuf {
+        crate::sync::lock(&self.state).path.clone()
+    }
+
+    /// Get a handle for replacing this logger's filters at runtime
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle::new(self.filters.clone())
+    }
+
+    fn print(&self, record: &log::Record<'_>) {
+        self.write(record);
+        self.mirror.write(&self.options, record);
+    }
+
+    fn write(&self, record: &log::Record<'_>) {
+        let state = &mut *crate::sync::lock(&self.state);
+
+        state.buf.clear();
+        Renderer::new(&self.options, ColorDepth::TrueColor)
+            .render(record, &mut termcolor::NoColor::new(&mut state.buf));
+
+        let index = match (&state.period, self.rotation) {
+            (Period::Index(index), Rotation::Size(max))
+                if state.size > 0 && state.size + state.buf.len() as u64 > max =>
+            {
+                index + 1
+            }
+            (Period::Index(index), ..) => *index,
+            _ => 0,
+        };
+
+        let period = self.rotation.period(&DateTime::now(), index);
+        let now = Instant::now();
+        if period != state.period && state.retry.is_none_or(|retry| now >= retry) {
+            // the record is written to the current file until a new one can be opened
+            state.retry = self.roll(state, period).err().map(|_| now + ROLL_RETRY);
+        }
+
+        if state.file.write_all(&state.buf).is_ok() {
+            state.size += state.buf.len() as u64;
+        }
+    }
+
+    fn roll(&self, state: &mut State, period: Period) -> std::io::Result<()> {
+        let path = self.dir.join(period.file_name(&self.name));
+        let file = open(&path, &self.options)?;
+        let _ = write_footer(self.options.file_footer, &mut state.file);
+        let _ = state.file.flush();
+
+        state.size = file.metadata().map(|md| md.len()).unwrap_or_default();
+        state.file = file;
+        state.path = path;
+        state.period = period;
+
+        if let Some(latest) = &self.latest {
+            let _ = link_latest(latest, &state.path);
+        }
+        if let Some(retention) = &self.retention {
+            let _ = retention.clone().keep(&state.path).apply();
+        }
+        Ok(())
+    }
+}
+
+impl log::Log for RollingLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.options.allows(&self.filters, metadata)
+    }
+
+    #[inline]
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) && self.filters.matches_message(record) {
+            self.options.middleware.apply(record, |record| {
+                crate::tracker::record(record.metadata());
+                self.print(record);
+            });
+        }
+    }
+
+    #[inline]
+    fn flush(&self) {
+        let _ = crate::sync::lock(&self.state).file.flush();
+    }
+}
+
+impl Drop for RollingLogger {
+    fn drop(&mut self) {
+        let state = crate::sync::get_mut(&mut self.state);
+        let _ = write_footer(self.options.file_footer, &mut state.file);
+        let _ = state.file.flush();
+    }
+}
+
+fn open(path: &Path, options: &Options) -> std::io::Result<File> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    write_header(options.file_header, &mut file)?;
+    Ok(file)
+}
+
+/// Find the highest index of the `name.N.log` files in `dir`
+fn last_index(dir: &Path, name: &str) -> Option<u64> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let index = file_name
+                .to_str()?
+                .strip_prefix(name)?
+                .strip_prefix('.')?
+                .strip_suffix(".log")?;
+            index.parse().ok()
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn naming() {
+        let now = DateTime::from_unix(1_714_571_999); // 2024-05-01 13:59:59
+        let cases = [
+            (Rotation::Daily, "app-2024-05-01.log"),
+            (Rotation::Hourly, "app-2024-05-01-13.log"),
+            (Rotation::Size(1024), "app.3.log"),
+        ];
+        for (rotation, expected) in cases {
+            assert_eq!(rotation.period(&now, 3).file_name("app"), expected);
+        }
+
+        let later = DateTime::from_unix(1_714_571_999 + 1);
+        assert_eq!(
+            Rotation::Hourly.period(&later, 0).file_name("app"),
+            "app-2024-05-01-14.log"
+        );
+        assert_eq!(
+            Rotation::Daily.period(&later, 0),
+            Rotation::Daily.period(&now, 0)
+        );
+    }
+
+    #[test]
+    fn size() {
+        use log::Log as _;
+
+        let dir = std::env::temp_dir().join(format!("alto_logger_rolling_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app.4.log"), "").unwrap();
+
+        let logger = RollingLogger::new(
+            crate::options::StyleConfig::SingleLine,
+            &dir,
+            "app",
+            Rotation::Size(64),
+        )
+        .unwrap()
+        .with_latest_link("latest.log");
+        logger.reload_handle().set("trace");
+        assert_eq!(logger.current_file(), dir.join("app.4.log"));
+
+        // each record is 29 bytes
+        for _ in 0..5 {
+            logger.log(
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target("foo")
+                    .args(format_args!("0123456789abcdef"))
+                    .build(),
+            );
+        }
+        logger.flush();
+
+        let len = |index| {
+            std::fs::metadata(dir.join(format!("app.{}.log", index)))
+                .unwrap()
+                .len()
+        };
+        assert_eq!((len(4), len(5), len(6)), (58, 58, 29));
+        assert_eq!(logger.current_file(), dir.join("app.6.log"));
+        assert_eq!(
+            std::fs::read(dir.join("latest.log")).unwrap(),
+            std::fs::read(dir.join("app.6.log")).unwrap()
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            std::fs::read_link(dir.join("latest.log")).unwrap(),
+            Path::new("app.6.log")
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    // the current file can still be written to after its directory is removed on unix
+    #[cfg(unix)]
+    #[test]
+    fn roll_retry() {
+        use log::Log as _;
+
+        let dir =
+            std::env::temp_dir().join(format!("alto_logger_roll_retry_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let logger = RollingLogger::new(
+            crate::options::StyleConfig::SingleLine,
+            &dir,
+            "app",
+            Rotation::Size(1),
+        )
+        .unwrap();
+        logger.reload_handle().set("trace");
+        let log = || {
+            logger.log(
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .args(format_args!("hello"))
+                    .build(),
+            )
+        };
+
+        log();
+        std::fs::remove_dir_all(&dir).unwrap();
+        log();
+        let retry = crate::sync::lock(&logger.state).retry;
+        assert!(retry.is_some());
+        assert_eq!(logger.current_file(), dir.join("app.1.log"));
+
+        // the new file isn't tried again until the retry time
+        std::fs::create_dir_all(&dir).unwrap();
+        log();
+        assert_eq!(crate::sync::lock(&logger.state).retry, retry);
+        assert_eq!(logger.current_file(), dir.join("app.1.log"));
+
+        crate::sync::lock(&logger.state).retry = Some(Instant::now());
+        log();
+        assert_eq!(crate::sync::lock(&logger.state).retry, None);
+        assert_eq!(logger.current_file(), dir.join("app.2.log"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{
+    sync::{Condvar, Mutex},
+    OwnedRecord,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A logger that collapses bursts of records from the same target and level into a single record
+///
+/// The first record from a target and level starts a window. Records from the same target and level
+/// during the window are counted, and when it ends a record is written with the first message, the count,
+/// and the last message. A record without any repeats is written as it was.
+///
+/// This is meant for chatty retry loops. Records are delayed by up to the window, so the order of records from
+/// different targets may change. Flushing writes every pending record.
+///
+/// ```rust,no_run
+/// # use alto_logger::{SummaryLogger, TermLogger};
+/// # use std::time::Duration;
+/// SummaryLogger::new(TermLogger::default(), Duration::from_secs(5))
+///     .init()
+///     .unwrap();
+/// for attempt in 0..100 {
+///     log::warn!("connection refused, attempt {}", attempt);
+/// }
+/// // written as one record: connection refused, attempt 0 (100 records, last: connection refused, attempt 99)
+/// ```
+pub struct SummaryLogger {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    inner: Box<dyn log::Log>,
+    window: Duration,
+    bursts: Mutex<HashMap<(String, log::Level), Burst>>,
+    // orders the bursts, so they're written in the order they started
+    sequence: AtomicU64,
+    // set when the logger is dropped, so the worker stops
+    closed: Mutex<bool>,
+    closing: Condvar,
+}
+
+struct Burst {
+    sequence: u64,
+    start: Instant,
+    first: OwnedRecord,
+    last: Option<OwnedRecord>,
+    count: u64,
+}
+
+impl SummaryLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Wrap `inner`, collapsing the records in each `window`
+    pub fn new(inner: impl log::Log + 'static, window: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            inner: Box::new(inner),
+            window,
+            bursts: Mutex::default(),
+            sequence: AtomicU64::new(0),
+            closed: Mutex::new(false),
+            closing: Condvar::new(),
+        });
+
+        let worker = Arc::clone(&shared);
+        std::thread::Builder::new()
+            .name(String::from("alto_logger summary"))
+            .spawn(move || worker.run())
+            .expect("spawn summary thread");
+
+        Self { shared }
+    }
+}
+
+impl Shared {
+    fn run(&self) {
+        let mut closed = crate::sync::lock(&self.closed);
+        while !*closed {
+            let timeout = (self.window / 2).max(Duration::from_millis(1));
+            closed = crate::sync::wait_timeout(&self.closing, closed, timeout);
+            self.write(Some(Instant::now()));
+        }
+    }
+
+    /// Write the bursts whose window ended before `now`, or every burst
+    fn write(&self, now: Option<Instant>) {
+        let ended = {
+            let mut bursts = crate::sync::lock(&self.bursts);
+            let keys = bursts
+                .iter()
+                .filter(|(_, burst)| now.is_none_or(|now| now >= burst.start + self.window))
+                .map(|(key, _)| key.clone())
+                .collect::<Vec<_>>();
+            let mut ended = keys
+                .into_iter()
+                .filter_map(|key| bursts.remove(&key))
+                .collect::<Vec<_>>();
+            ended.sort_by_key(|burst| burst.sequence);
+            ended
+        };
+
+        for burst in ended {
+            // the summary is written with the time of the burst's first record
+            let summary = burst.summary();
+            crate::record::replaying(summary.timestamp, summary.thread.as_deref(), || {
+                summary.with_record(|record| self.inner.log(record))
+            })
+        }
+    }
+}
+
+impl Burst {
+    fn summary(self) -> OwnedRecord {
+        let Some(last) = self.last else {
+            return self.first;
+        };
+        let mut summary = self.first;
+        summary.message = format!(
+            "{} ({} records, last: {})",
+            summary.message, self.count, last.message
+        );
+        summary
+            .kv
+            .push((String::from("count"), self.count.to_string()));
+        summary
+    }
+}
+
+impl log::Log for SummaryLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.shared.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let key = (record.target().to_string(), record.level());
+        let mut bursts = crate::sync::lock(&self.shared.bursts);
+        match bursts.get_mut(&key) {
+            Some(burst) => {
+                burst.count += 1;
+                burst.last.replace(OwnedRecord::from_record(record));
+            }
+            None => {
+                let burst = Burst {
+                    sequence: self.shared.sequence.fetch_add(1, Ordering::Relaxed),
+                    start: Instant::now(),
+                    first: OwnedRecord::from_record(record),
+                    last: None,
+                    count: 1,
+                };
+                bursts.insert(key, burst);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.shared.write(None);
+        self.shared.inner.flush();
+    }
+}
+
+impl Drop for SummaryLogger {
+    fn drop(&mut self) {
+        *crate::sync::lock(&self.shared.closed) = true;
+        self.shared.closing.notify_all();
+        self.shared.write(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChannelLogger;
+    use log::Log as _;
+    use std::time::SystemTime;
+
+    fn message(record: &OwnedRecord) -> String {
+        format!("{} {}: {}", record.level, record.target, record.message)
+    }
+
+    #[test]
+    fn summary() {
+        let (capture, rx) = ChannelLogger::capture();
+        let logger = SummaryLogger::new(capture, Duration::from_secs(60));
+
+        let log = |level, target: &str, msg: &str| {
+            logger.log(
+                &log::Record::builder()
+                    .level(level)
+                    .target(target)
+                    .args(format_args!("{}", msg))
+                    .build(),
+            )
+        };
+
+        let first = SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_568_400);
+        crate::record::replaying(first, None, || log(log::Level::Warn, "retry", "attempt 0"));
+        for attempt in 1..3 {
+            log(log::Level::Warn, "retry", &format!("attempt {}", attempt));
+        }
+        log(log::Level::Error, "retry", "gave up");
+        log(log::Level::Info, "other", "hello");
+        assert!(rx.try_recv().is_err());
+
+        logger.flush();
+        let records = rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(records[0].timestamp, first);
+        assert_eq!(
+            records.iter().map(message).collect::<Vec<_>>(),
+            [
+                "WARN retry: attempt 0 (3 records, last: attempt 2)",
+                "ERROR retry: gave up",
+                "INFO other: hello",
+            ]
+        );
+
+        // the worker writes a burst once its window has ended
+        let (capture, rx) = ChannelLogger::capture();
+        let logger = SummaryLogger::new(capture, Duration::from_millis(20));
+        logger.log(&log::Record::builder().args(format_args!("tick")).build());
+        let record = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(message(&record), "INFO : tick");
+    }
+}
@@ -1,25 +1,40 @@
 use crate::{
-    filters::Filters,
-    options::{Options, StyleConfig, TimeConfig},
+    config::{LoggerConfig, LoggerKind},
+    filters::SharedFilters,
+    options::{ColorDepth, Detection, Options},
+    render::Renderer,
+    sync::Mutex,
+    ReloadHandle,
+};
+use std::{
+    cell::RefCell,
+    io::Write,
+    sync::atomic::{AtomicUsize, Ordering},
 };
-use termcolor::ColorSpec;
 
 /// Stdout logger which supports colors
 ///
-/// If 'NO_COLOR' env var is set, it'll override and disable any color configurations.
+/// Whether colors are used is determined by the [`Detection`](options/enum.Detection.html) in its `Options`.
+///
+/// By default, `NO_COLOR`, `CLICOLOR`, `TERM=dumb` and common CI environment variables will disable any color configurations.
 pub struct TermLogger {
     options: Options,
-    filters: Filters,
-    color_choice: termcolor::ColorChoice,
+    filters: SharedFilters,
+    color_depth: ColorDepth,
+    writer: termcolor::BufferWriter,
+    tee: Option<Mutex<Box<dyn Write + Send>>>,
+    stderr: bool,
+    id: usize,
+}
+
+thread_local! {
+    // the buffer is reused for each record on a thread, its keyed by the logger that created it
+    static BUFFER: RefCell<Option<(usize, termcolor::Buffer)>> = const { RefCell::new(None) };
 }
 
 impl Default for TermLogger {
     fn default() -> Self {
-        Self {
-            options: Options::default(),
-            filters: Filters::from_env(),
-            color_choice: determine_color_choice(),
-        }
+        Self::from_options(Options::default())
     }
 }
 
@@ -31,166 +46,304 @@ impl TermLogger {
 
     /// Create a new terminal logger
     pub fn new(options: impl Into<Options>) -> Result<Self, crate::Error> {
-        let options = options.into();
+        Ok(Self::from_options(options.into()))
+    }
 
-        Ok(Self {
-            options,
-            filters: Filters::from_env(),
-            color_choice: determine_color_choice(),
-        })
+    fn from_options(options: Options) -> Self {
+        Self::from_stream(options, false)
     }
 
-    fn print(&self, record: &log::Record<'_>) {
-        let buf_writer = termcolor::BufferWriter::stdout(self.color_choice);
-        let mut buffer = buf_writer.buffer();
-
-        self.render_level(&record, &mut buffer);
-        self.render_timestamp(&record, &mut buffer);
-        self.render_target(&record, &mut buffer);
-        self.render_payload(&record, &mut buffer);
-
-        let _ = buf_writer.print(&buffer);
-    }
-
-    fn render_level(
-        &self,
-        record: &log::Record<'_>,
-        buffer: &mut (impl std::io::Write + termcolor::WriteColor),
-    ) {
-        let color = &self.options.color;
-
-        let level_color = match record.level() {
-            log::Level::Error => color.level_error,
-            log::Level::Warn => color.level_warn,
-            log::Level::Info => color.level_info,
-            log::Level::Debug => color.level_debug,
-            log::Level::Trace => color.level_trace,
+    fn from_stream(options: Options, stderr: bool) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let writer = match stderr {
+            true => termcolor::BufferWriter::stderr(options.color_choice(true)),
+            false => termcolor::BufferWriter::stdout(options.color_choice(false)),
         };
+        Self {
+            writer,
+            color_depth: options.color_depth.resolve(),
+            filters: SharedFilters::from_options(&options),
+            options,
+            tee: None,
+            stderr,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
 
-        let _ = buffer.set_color(ColorSpec::new().set_fg(level_color.into()));
-        let _ = write!(buffer, "{:<5}", record.level());
-        let _ = buffer.reset();
+    /// Copy everything printed to stdout into this sink, e.g. a file
+    ///
+    /// Unlike combining this with a [`FileLogger`](struct.FileLogger.html) in a [`MultiLogger`](struct.MultiLogger.html),
+    /// the sink gets exactly the same bytes as the terminal, including any color escape sequences.
+    ///
+    /// ```rust,no_run
+    /// # use alto_logger::TermLogger;
+    /// let file = std::fs::File::create("session.log").unwrap();
+    /// TermLogger::default().with_tee(file).init().unwrap();
+    /// ```
+    pub fn with_tee(mut self, sink: impl Write + Send + 'static) -> Self {
+        self.tee.replace(Mutex::new(Box::new(sink)));
+        self
     }
 
-    fn render_timestamp(
-        &self,
-        _record: &log::Record<'_>,
-        buffer: &mut (impl std::io::Write + termcolor::WriteColor),
-    ) {
-        let Options { color, time, .. } = &self.options;
+    /// Get a handle for replacing this logger's filters at runtime
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle::new(self.filters.clone())
+    }
 
-        match time {
-            TimeConfig::None => {}
+    /// Determine how colors will be written to stdout (or stderr, for a [`StderrLogger`](struct.StderrLogger.html))
+    ///
+    /// This is included in the banner logged by [`init_with_banner`](fn.init_with_banner.html)
+    pub fn console_probe(&self) -> ConsoleProbe {
+        use termcolor::WriteColor as _;
+        let stream = match self.stderr {
+            true => termcolor::StandardStream::stderr(self.options.color_choice(true)),
+            false => termcolor::StandardStream::stdout(self.options.color_choice(false)),
+        };
+        match (stream.supports_color(), stream.is_synchronous()) {
+            (false, ..) => ConsoleProbe::NoColor,
+            (true, true) => ConsoleProbe::ConsoleApi,
+            (true, false) => ConsoleProbe::Ansi,
+        }
+    }
 
-            TimeConfig::Unix => {
-                let elapsed = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .expect("time should not go backwards");
-                let _ = buffer.set_color(ColorSpec::new().set_fg(color.timestamp.into()));
-                let _ = write!(buffer, " {:04}", elapsed.as_secs());
-                let _ = buffer.reset();
-            }
+    pub(crate) fn describe(&self) -> LoggerConfig {
+        LoggerConfig {
+            kind: LoggerKind::Term,
+            options: Some(self.options.clone()),
+            filters: Some(self.filters.to_string()),
+            console: Some(self.console_probe()),
+            shared: Some(self.filters.clone()),
+            parents: Vec::new(),
+        }
+    }
 
-            TimeConfig::Relative(start) => {
-                let elapsed = start.elapsed();
-                let _ = buffer.set_color(ColorSpec::new().set_fg(color.timestamp.into()));
-
-                let _ = write!(
-                    buffer,
-                    " {:04}.{:09}s",
-                    elapsed.as_secs(),
-                    elapsed.subsec_nanos()
-                );
-                let _ = buffer.reset();
+    fn print(&self, record: &log::Record<'_>) {
+        BUFFER.with(|cell| match cell.try_borrow_mut() {
+            Ok(mut cell) => {
+                let buffer = match &mut *cell {
+                    Some((id, buffer)) if *id == self.id => {
+                        buffer.clear();
+                        buffer
+                    }
+                    cell => &mut cell.insert((self.id, self.writer.buffer())).1,
+                };
+                self.render(record, buffer);
+                self.write(buffer);
             }
 
-            TimeConfig::Timing(inner) => {
-                let inner = &mut *inner.lock().unwrap();
-                if let Some(start) = &*inner {
-                    let elapsed = start.elapsed();
-                    let _ = buffer.set_color(ColorSpec::new().set_fg(color.timestamp.into()));
-                    let _ = write!(
-                        buffer,
-                        " {:04}.{:09}s",
-                        elapsed.as_secs(),
-                        elapsed.subsec_nanos()
-                    );
-                    let _ = buffer.reset();
-                } else {
-                    let _ = buffer.set_color(ColorSpec::new().set_fg(color.timestamp.into()));
-                    let _ = write!(buffer, " {:04}.{:09}s", 0, 0);
-                    let _ = buffer.reset();
-                }
-                inner.replace(std::time::Instant::now());
+            // something logged while a record was being rendered on this thread
+            Err(..) => {
+                let mut buffer = self.writer.buffer();
+                self.render(record, &mut buffer);
+                self.write(&buffer);
             }
+        });
+    }
 
-            #[cfg(feature = "time")]
-            TimeConfig::DateTime(format) => {
-                if let Ok(now) = time::OffsetDateTime::now_utc().format(&&format) {
-                    let _ = buffer.set_color(ColorSpec::new().set_fg(color.timestamp.into()));
-                    let _ = write!(buffer, " {}", now);
-                    let _ = buffer.reset();
-                }
-            }
-        }
+    fn write(&self, buffer: &termcolor::Buffer) {
+        let Some(tee) = &self.tee else {
+            let _ = self.writer.print(buffer);
+            return;
+        };
+
+        // the tee is locked across both writes, so records are in the same order in both
+        let mut tee = crate::sync::lock(tee);
+        let _ = self.writer.print(buffer);
+        let _ = tee.write_all(buffer.as_slice());
+    }
+
+    fn render(&self, record: &log::Record<'_>, buffer: &mut termcolor::Buffer) {
+        Renderer::new(&self.options, self.color_depth).render(record, buffer)
+    }
+}
+
+/// Stderr logger, for programs which keep stdout for their own output (e.g. JSON for a pipeline)
+///
+/// This is the same as a [`TermLogger`](struct.TermLogger.html), except it writes to stderr,
+/// and its default `Options` don't use colors.
+///
+/// ```rust,no_run
+/// # use alto_logger::StderrLogger;
+/// StderrLogger::default().init().unwrap();
+/// log::info!("this doesn't end up in stdout");
+/// ```
+pub struct StderrLogger(TermLogger);
+
+impl Default for StderrLogger {
+    fn default() -> Self {
+        Self(TermLogger::from_stream(
+            Options::default().with_color_detection(Detection::Never),
+            true,
+        ))
     }
+}
 
-    fn render_target(
-        &self,
-        record: &log::Record<'_>,
-        buffer: &mut (impl std::io::Write + termcolor::WriteColor),
-    ) {
-        let color = &self.options.color;
-
-        let _ = write!(buffer, " [");
-        let _ = buffer.set_color(ColorSpec::new().set_fg(color.target.into()));
-        let _ = write!(buffer, "{}", record.target());
-        let _ = buffer.reset();
-        let _ = write!(buffer, "]");
-    }
-
-    fn render_payload(
-        &self,
-        record: &log::Record<'_>,
-        buffer: &mut (impl std::io::Write + termcolor::WriteColor),
-    ) {
-        let Options { style, color, .. } = &self.options;
-
-        if let StyleConfig::MultiLine = style {
-            let _ = writeln!(buffer);
-            let _ = buffer.set_color(ColorSpec::new().set_fg(color.continuation.into()));
-            let _ = write!(buffer, "⤷");
-            let _ = buffer.reset();
+impl StderrLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Create a new stderr logger
+    ///
+    /// ***Note*** Colors are used as the [`Detection`](options/enum.Detection.html) of the options allows
+    pub fn new(options: impl Into<Options>) -> Result<Self, crate::Error> {
+        Ok(Self(TermLogger::from_stream(options.into(), true)))
+    }
+
+    /// Get a handle for replacing this logger's filters at runtime
+    pub fn reload_handle(&self) -> ReloadHandle {
+        self.0.reload_handle()
+    }
+
+    /// Determine how colors will be written to stderr
+    pub fn console_probe(&self) -> ConsoleProbe {
+        self.0.console_probe()
+    }
+
+    pub(crate) fn describe(&self) -> LoggerConfig {
+        LoggerConfig {
+            kind: LoggerKind::Stderr,
+            ..self.0.describe()
         }
+    }
+}
+
+impl log::Log for StderrLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.0.enabled(metadata)
+    }
+
+    #[inline]
+    fn log(&self, record: &log::Record<'_>) {
+        self.0.log(record)
+    }
+
+    #[inline]
+    fn flush(&self) {
+        self.0.flush()
+    }
+}
+
+/// How a [`TermLogger`](struct.TermLogger.html) writes colors
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConsoleProbe {
+    /// Colors are written as ANSI escape sequences
+    Ansi,
+    /// Colors are set with the Windows console API
+    ConsoleApi,
+    /// Colors aren't written
+    NoColor,
+}
 
-        let _ = buffer.set_color(ColorSpec::new().set_fg(color.message.into()));
-        let _ = write!(buffer, " {}", record.args());
-        let _ = buffer.reset();
-        let _ = writeln!(buffer);
+impl std::fmt::Display for ConsoleProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Ansi => "ansi",
+            Self::ConsoleApi => "console api",
+            Self::NoColor => "none",
+        })
     }
 }
 
 impl log::Log for TermLogger {
     #[inline]
     fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
-        self.filters.is_enabled(metadata)
+        self.options.allows(&self.filters, metadata)
     }
 
     #[inline]
     fn log(&self, record: &log::Record<'_>) {
-        if self.enabled(record.metadata()) {
-            self.print(record);
+        if self.enabled(record.metadata()) && self.filters.matches_message(record) {
+            self.options.middleware.apply(record, |record| {
+                crate::tracker::record(record.metadata());
+                self.print(record);
+            });
         }
     }
 
     #[inline]
-    fn flush(&self) {}
+    fn flush(&self) {
+        if let Some(tee) = &self.tee {
+            let _ = crate::sync::lock(tee).flush();
+        }
+    }
 }
 
-fn determine_color_choice() -> termcolor::ColorChoice {
-    if std::env::var("NO_COLOR").is_ok() {
-        termcolor::ColorChoice::Never
-    } else {
-        termcolor::ColorChoice::Auto
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::StyleConfig;
+    use log::Log as _;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tee() {
+        let sink = Shared::default();
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_color_detection(Detection::Never);
+        let logger = TermLogger::new(options).unwrap().with_tee(sink.clone());
+        logger.reload_handle().set("trace");
+
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target("alto_logger::tee")
+                .args(format_args!("tee test"))
+                .build(),
+        );
+        assert_eq!(
+            &*sink.0.lock().unwrap(),
+            b"INFO  [alto_logger::tee] tee test\n"
+        );
+    }
+
+    #[test]
+    fn middleware() {
+        let sink = Shared::default();
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_color_detection(Detection::Never)
+            .with_middleware(|record: crate::OwnedRecord| {
+                Some(record).filter(|record| !record.message.starts_with("sampled"))
+            })
+            .with_middleware(|mut record: crate::OwnedRecord| {
+                record.message = record.message.replace("hunter2", "***");
+                record.target = record.target.replace("app", "my_app");
+                Some(record)
+            });
+        let logger = TermLogger::new(options).unwrap().with_tee(sink.clone());
+        logger.reload_handle().set("trace");
+
+        for msg in ["sampled out", "password is hunter2"] {
+            logger.log(
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target("app")
+                    .args(format_args!("{}", msg))
+                    .build(),
+            );
+        }
+        assert_eq!(
+            String::from_utf8(sink.0.lock().unwrap().clone()).unwrap(),
+            "INFO  [my_app] password is ***\n"
+        );
     }
 }
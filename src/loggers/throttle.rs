@@ -0,0 +1,207 @@
+use crate::sync::Mutex;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// A logger that lets at most one record through per interval from specific noisy targets
+///
+/// Records from other targets are always passed to the wrapped logger. A target also covers the modules below it.
+///
+/// When a record is let through after some were dropped, a record saying how many were dropped is written before it.
+///
+/// ```rust,no_run
+/// # use alto_logger::{ThrottleLogger, TermLogger};
+/// # use std::time::Duration;
+/// ThrottleLogger::new(TermLogger::default())
+///     .with_interval("poller::heartbeat", Duration::from_millis(500))
+///     .init()
+///     .unwrap();
+/// ```
+pub struct ThrottleLogger {
+    inner: Box<dyn log::Log>,
+    intervals: Vec<(String, Duration)>,
+    slots: Mutex<HashMap<usize, Slot>>,
+    dropped: AtomicU64,
+}
+
+struct Slot {
+    last: Instant,
+    dropped: u64,
+}
+
+impl ThrottleLogger {
+    /// Use this logger as the 'installed' logger (same as `alto_logger::init(this);`)
+    pub fn init(self) -> Result<(), crate::Error> {
+        crate::init(self)
+    }
+
+    /// Wrap `inner`, without throttling any targets yet
+    pub fn new(inner: impl log::Log + 'static) -> Self {
+        Self {
+            inner: Box::new(inner),
+            intervals: Vec::new(),
+            slots: Mutex::default(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Let at most one record through per `interval` from this target, and the modules below it
+    ///
+    /// The first matching target is used, so more specific targets should be added first.
+    pub fn with_interval(mut self, target: impl Into<String>, interval: Duration) -> Self {
+        self.intervals.push((target.into(), interval));
+        self
+    }
+
+    /// How many records have been dropped, from every throttled target
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn find(&self, target: &str) -> Option<(usize, &str, Duration)> {
+        self.intervals
+            .iter()
+            .enumerate()
+            .find(|(_, (prefix, _))| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map(|(i, (prefix, interval))| (i, prefix.as_str(), *interval))
+    }
+}
+
+impl log::Log for ThrottleLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        let Some((index, prefix, interval)) = self.find(record.target()) else {
+            return self.inner.log(record);
+        };
+        // a record the wrapped logger would drop doesn't use up the target's slot
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+
+        let now = Instant::now();
+        let dropped = {
+            let mut slots = crate::sync::lock(&self.slots);
+            match slots.get_mut(&index) {
+                Some(slot) if now.saturating_duration_since(slot.last) < interval => {
+                    slot.dropped += 1;
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                Some(slot) => {
+                    slot.last = now;
+                    std::mem::take(&mut slot.dropped)
+                }
+                None => {
+                    slots.insert(
+                        index,
+                        Slot {
+                            last: now,
+                            dropped: 0,
+                        },
+                    );
+                    0
+                }
+            }
+        };
+
+        if dropped > 0 {
+            self.inner.log(
+                &log::Record::builder()
+                    .level(record.level())
+                    .target(prefix)
+                    .args(format_args!(
+                        "throttled {} records from {} (at most one per {})",
+                        dropped,
+                        prefix,
+                        crate::fmt::duration(interval)
+                    ))
+                    .build(),
+            );
+        }
+        self.inner.log(record)
+    }
+
+    #[inline]
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChannelLogger;
+    use log::Log as _;
+
+    #[test]
+    fn throttle() {
+        let (capture, rx) = ChannelLogger::capture();
+        let logger =
+            ThrottleLogger::new(capture).with_interval("poller", Duration::from_millis(100));
+
+        let log = |target: &str, msg: &str| {
+            logger.log(
+                &log::Record::builder()
+                    .target(target)
+                    .args(format_args!("{}", msg))
+                    .build(),
+            )
+        };
+
+        log("poller::heartbeat", "a");
+        log("poller::heartbeat", "b");
+        log("poller", "c");
+        log("pollers", "d");
+        log("other", "e");
+        assert_eq!(logger.dropped(), 2);
+
+        std::thread::sleep(Duration::from_millis(150));
+        log("poller::heartbeat", "f");
+
+        assert_eq!(
+            rx.try_iter()
+                .map(|r| format!("{}: {}", r.target, r.message))
+                .collect::<Vec<_>>(),
+            [
+                "poller::heartbeat: a",
+                "pollers: d",
+                "other: e",
+                "poller: throttled 2 records from poller (at most one per 100ms)",
+                "poller::heartbeat: f",
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled() {
+        let (capture, rx) = ChannelLogger::capture();
+        capture.reload_handle().set("info");
+        let logger = ThrottleLogger::new(capture).with_interval("poller", Duration::from_secs(60));
+
+        let log = |level, msg: &str| {
+            logger.log(
+                &log::Record::builder()
+                    .level(level)
+                    .target("poller")
+                    .args(format_args!("{}", msg))
+                    .build(),
+            )
+        };
+        log(log::Level::Debug, "a");
+        log(log::Level::Info, "b");
+        log(log::Level::Info, "c");
+        assert_eq!(logger.dropped(), 1);
+
+        assert_eq!(rx.try_iter().map(|r| r.message).collect::<Vec<_>>(), ["b"]);
+    }
+}
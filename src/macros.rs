@@ -0,0 +1,76 @@
+/// Log an `INFO` record, with the same arguments as `println!`
+///
+/// This eases moving code that debugs with `println!` to the logger, one call at a time.
+///
+/// ```rust
+/// alto_logger::init_term_logger().unwrap();
+///
+/// let (x, y) = (1, 2);
+/// alto_logger::alto_println!("x = {}, y = {}", x, y);
+/// alto_logger::alto_println!();
+/// ```
+#[macro_export]
+macro_rules! alto_println {
+    () => {
+        $crate::__log::info!("")
+    };
+    ($($arg:tt)+) => {
+        $crate::__log::info!($($arg)+)
+    };
+}
+
+/// Log an `ERROR` record, with the same arguments as `eprintln!`
+///
+/// ```rust
+/// alto_logger::init_term_logger().unwrap();
+///
+/// # let path = "config.toml";
+/// alto_logger::alto_eprintln!("cannot read {}", path);
+/// ```
+#[macro_export]
+macro_rules! alto_eprintln {
+    () => {
+        $crate::__log::error!("")
+    };
+    ($($arg:tt)+) => {
+        $crate::__log::error!($($arg)+)
+    };
+}
+
+/// Initialize the logger, and then log a banner with the name and version of the crate this is used in
+///
+/// See [`init_with_banner`](fn.init_with_banner.html)
+///
+/// ```rust
+/// # use alto_logger::TermLogger;
+/// alto_logger::banner!(TermLogger::default()).unwrap();
+/// ```
+#[macro_export]
+macro_rules! banner {
+    ($logger:expr) => {
+        $crate::init_with_banner(
+            $logger,
+            ::core::env!("CARGO_PKG_NAME"),
+            ::core::env!("CARGO_PKG_VERSION"),
+        )
+    };
+}
+
+/// Create a `DateTime` [`TimeConfig`](enum.TimeConfig.html) from a format description checked at compile time
+///
+/// This wraps `time::macros::format_description!`, so an invalid description is a compile error.
+/// Its expansion refers to the `time` crate, so it has to be a dependency as well.
+///
+/// See the formatting description [here](https://time-rs.github.io/book/api/format-description.html)
+///
+/// ```rust
+/// # use alto_logger::Options;
+/// let opts = Options::default().with_time(alto_logger::time_format!("[hour]:[minute]:[second]"));
+/// ```
+#[cfg(feature = "time")]
+#[macro_export]
+macro_rules! time_format {
+    ($($format:tt)+) => {
+        $crate::TimeConfig::date_time_format($crate::__time::macros::format_description!($($format)+))
+    };
+}
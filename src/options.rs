@@ -3,6 +3,8 @@
 * [`StyleConfig`](enum.StyleConfig.html) allows you to choose which line-formating you want.
 * [`ColorConfig`](struct.ColorConfig.html) allows you to choose colors per element of the terminal logger.
 * [`TimeConfig`](enum.TimeConfig.html) allows you to choose which timestamp format to use.
+* [`Detection`](enum.Detection.html) allows you to choose how colors are enabled for the terminal logger.
+* [`FieldsConfig`](struct.FieldsConfig.html) allows you to choose which fields of a record are rendered.
 
 An example:
 ```rust
@@ -15,15 +17,28 @@ let opts = Options::default()
 */
 
 mod color;
+mod detection;
+mod fields;
+mod middleware;
 mod style;
+mod target;
 mod time;
 
 #[doc(inline)]
-pub use self::time::TimeConfig;
+pub use self::time::{Epoch, TimeConfig};
+pub(crate) use color::color_spec;
 #[doc(inline)]
-pub use color::ColorConfig;
+pub use color::{Attributes, ColorConfig, ColorDepth};
 #[doc(inline)]
-pub use style::StyleConfig;
+pub use detection::{ConsoleMode, Detection};
+#[doc(inline)]
+pub use fields::FieldsConfig;
+#[doc(inline)]
+pub use middleware::{Middleware, Pipeline};
+#[doc(inline)]
+pub use style::{LevelCase, StyleConfig};
+#[doc(inline)]
+pub use target::TargetRewrite;
 
 #[non_exhaustive]
 #[derive(Default, Clone, Debug)]
@@ -35,6 +50,48 @@ pub struct Options {
     pub color: ColorConfig,
     /// The time configuration
     pub time: TimeConfig,
+    /// The color detection configuration
+    pub detection: Detection,
+    /// How colors are written to a Windows console
+    pub console: ConsoleMode,
+    /// The supported color depth
+    pub color_depth: ColorDepth,
+    /// The letter case of the level
+    pub level_case: LevelCase,
+    /// Prefix each line with a `<N>` syslog priority, as understood by systemd's journal
+    pub priority_prefix: bool,
+    /// A marker written at the start of every line of a record after the first line
+    pub continuation_marker: Option<&'static str>,
+    /// Style overrides for specific levels, indexed from `Error` to `Trace`
+    pub level_styles: [Option<StyleConfig>; 5],
+    /// A predicate that has to allow a record, after the filters have
+    pub enable_predicate: Option<fn(&log::Metadata<'_>) -> bool>,
+    /// Resume a panic from formatting a message in debug builds, rather than writing a placeholder
+    pub repanic_in_debug: bool,
+    /// Style the span context of records forwarded from `tracing`
+    pub tracing_spans: bool,
+    /// Only create the file of a file logger once a record is logged to it
+    pub lazy_create: bool,
+    /// Write a header line with this application version when a file logger opens a file
+    pub file_header: Option<&'static str>,
+    /// Write a footer line when a file logger closes a file
+    pub file_footer: bool,
+    /// Rules for rewriting targets before filtering and rendering
+    pub target_rewrite: TargetRewrite,
+    /// Never render a wall-clock timestamp earlier than a previously rendered one
+    pub monotonic_timestamps: bool,
+    /// Also write records at or above this level to stderr, from file loggers
+    pub stderr_mirror: Option<log::Level>,
+    /// Middleware that transforms or drops records before they are written
+    pub middleware: Pipeline,
+    /// Render wall-clock timestamps from this key-value of a record, when it has one
+    pub kv_timestamp: Option<&'static str>,
+    /// Indent the lines of a message after its first line, to line up with its first character
+    pub hanging_indent: bool,
+    /// Which fields of a record are rendered
+    pub fields: FieldsConfig,
+    /// The filters for a logger, instead of the `RUST_LOG` environment variable
+    pub filters: Option<crate::Filters>,
 }
 
 impl Options {
@@ -44,12 +101,282 @@ impl Options {
         self
     }
 
+    /// Use this `StyleConfig` for records of this `Level`, instead of the default style
+    ///
+    /// ```rust
+    /// # use alto_logger::{Options, options::StyleConfig};
+    /// let opts = Options::default()
+    ///     .with_style(StyleConfig::SingleLine)
+    ///     .with_level_style(log::Level::Error, StyleConfig::MultiLine);
+    /// ```
+    pub const fn with_level_style(mut self, level: log::Level, style: StyleConfig) -> Self {
+        self.level_styles[level as usize - 1] = Some(style);
+        self
+    }
+
+    /// Get the `StyleConfig` used for records of this `Level`
+    pub const fn style_for(&self, level: log::Level) -> StyleConfig {
+        match self.level_styles[level as usize - 1] {
+            Some(style) => style,
+            None => self.style,
+        }
+    }
+
+    /// Use this predicate to decide whether a record should be logged.
+    ///
+    /// This is checked after the filters, so it can only further restrict what is logged.
+    ///
+    /// ```rust
+    /// # use alto_logger::Options;
+    /// fn quiet_hours(metadata: &log::Metadata<'_>) -> bool {
+    ///     # let business_hours = || false;
+    ///     metadata.level() <= log::Level::Info || !business_hours()
+    /// }
+    /// let opts = Options::default().with_enable_predicate(quiet_hours);
+    /// ```
+    pub const fn with_enable_predicate(
+        mut self,
+        predicate: fn(&log::Metadata<'_>) -> bool,
+    ) -> Self {
+        self.enable_predicate = Some(predicate);
+        self
+    }
+
+    /// How colors are written to stderr (if `stderr` is true) or stdout
+    pub(crate) fn color_choice(&self, stderr: bool) -> termcolor::ColorChoice {
+        use std::io::IsTerminal as _;
+
+        self.console
+            .color_choice(self.detection.color_choice(), || match stderr {
+                true => std::io::stderr().is_terminal(),
+                false => std::io::stdout().is_terminal(),
+            })
+    }
+
+    /// Never render a wall-clock timestamp (`Unix` or `DateTime`) earlier than one rendered before it in this process
+    ///
+    /// If the system clock is stepped backwards (e.g. by NTP), timestamps stay at the latest
+    /// rendered time until the clock catches up, so tools that sort or merge logs by time keep working.
+    ///
+    /// ***Note*** Defaults to `false`
+    pub const fn with_monotonic_timestamps(mut self, monotonic: bool) -> Self {
+        self.monotonic_timestamps = monotonic;
+        self
+    }
+
+    /// Also write records at or above this level to stderr, from a [`FileLogger`](../struct.FileLogger.html)
+    /// or a [`RollingLogger`](../struct.RollingLogger.html)
+    ///
+    /// Container orchestrators often only watch stderr, so this lets them see failures from a file-only setup.
+    ///
+    /// ```rust
+    /// # use alto_logger::Options;
+    /// let opts = Options::default().with_stderr_mirror(Some(log::Level::Error));
+    /// ```
+    ///
+    /// ***Note*** Defaults to `None`
+    pub const fn with_stderr_mirror(mut self, level: Option<log::Level>) -> Self {
+        self.stderr_mirror = level;
+        self
+    }
+
+    /// Render wall-clock timestamps from this key-value of a record, when it has a valid one, instead of now
+    ///
+    /// This keeps the original times of replayed or imported records. The value can be seconds since the UNIX epoch
+    /// (e.g. `1714521600` or `1714521600.25`), or an RFC 3339 date and time (e.g. `2024-05-01T00:00:00Z`).
+    ///
+    /// ```rust
+    /// # use alto_logger::{Options, TimeConfig};
+    /// let opts = Options::default()
+    ///     .with_time(TimeConfig::unix_timestamp())
+    ///     .with_kv_timestamp(Some("timestamp"));
+    /// ```
+    ///
+    /// ***Note*** This doesn't apply to relative timestamps. Defaults to `None`
+    pub const fn with_kv_timestamp(mut self, key: Option<&'static str>) -> Self {
+        self.kv_timestamp = key;
+        self
+    }
+
+    /// Add this `Middleware` to the end of the pipeline records go through before they are written
+    ///
+    /// See [`Middleware`](trait.Middleware.html)
+    ///
+    /// ***Note*** Defaults to no middleware
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Indent the lines of a message after its first line, so they line up with its first character
+    ///
+    /// e.g. in the `MultiLine` style, they're indented under the `⤷ ` marker:
+    /// ```text
+    /// ERROR [my_app]
+    /// ⤷ request failed:
+    ///   connection reset
+    /// ```
+    ///
+    /// ***Note*** Defaults to `false`
+    pub const fn with_hanging_indent(mut self, hanging_indent: bool) -> Self {
+        self.hanging_indent = hanging_indent;
+        self
+    }
+
+    /// Use this `FieldsConfig` with these `Options`
+    ///
+    /// ***Note*** Defaults to the level, time, target and message
+    pub const fn with_fields(mut self, fields: FieldsConfig) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Use these filters, instead of the `RUST_LOG` environment variable
+    ///
+    /// See [`Filters::builder`](../struct.Filters.html#method.builder)
+    ///
+    /// ***Note*** Defaults to `None`, which uses `RUST_LOG`
+    pub fn with_filters(mut self, filters: crate::Filters) -> Self {
+        self.filters.replace(filters);
+        self
+    }
+
+    /// Use these `TargetRewrite` rules with these `Options`
+    ///
+    /// The rewritten target is what the filters match against, and what is rendered.
+    pub const fn with_target_rewrite(mut self, target_rewrite: TargetRewrite) -> Self {
+        self.target_rewrite = target_rewrite;
+        self
+    }
+
+    /// Check the filters, with the rewritten target, and then the predicate
+    pub(crate) fn allows(
+        &self,
+        filters: &crate::filters::SharedFilters,
+        metadata: &log::Metadata<'_>,
+    ) -> bool {
+        let target = self.target_rewrite.rewrite(metadata.target());
+        filters.is_target_enabled(&target, metadata.level()) && self.predicate_allows(metadata)
+    }
+
+    pub(crate) fn predicate_allows(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.enable_predicate
+            .is_none_or(|predicate| predicate(metadata))
+    }
+
     /// Use this `ColorConfig` with these `Options`
     pub const fn with_color(mut self, color: ColorConfig) -> Self {
         self.color = color;
         self
     }
 
+    /// Use this `Detection` with these `Options`
+    pub const fn with_color_detection(mut self, detection: Detection) -> Self {
+        self.detection = detection;
+        self
+    }
+
+    /// Use this `ConsoleMode` with these `Options`
+    ///
+    /// [`TermLogger::console_probe`](../struct.TermLogger.html#method.console_probe) reports which was chosen
+    pub const fn with_console_mode(mut self, console: ConsoleMode) -> Self {
+        self.console = console;
+        self
+    }
+
+    /// Use this `ColorDepth` with these `Options`
+    pub const fn with_color_depth(mut self, color_depth: ColorDepth) -> Self {
+        self.color_depth = color_depth;
+        self
+    }
+
+    /// Use this `LevelCase` with these `Options`
+    pub const fn with_level_case(mut self, level_case: LevelCase) -> Self {
+        self.level_case = level_case;
+        self
+    }
+
+    /// Prefix each line with a `<N>` syslog priority for the level of the record
+    ///
+    /// This lets systemd determine the severity of each line when running with
+    /// `StandardOutput=journal`. e.g. `<3>ERROR [my_app] something failed`
+    pub const fn with_priority_prefix(mut self, priority_prefix: bool) -> Self {
+        self.priority_prefix = priority_prefix;
+        self
+    }
+
+    /// Write this marker at the start of every line of a record after its first line
+    ///
+    /// This applies to the `MultiLine` continuation and to any newlines in the message itself.
+    ///
+    /// Log collectors (e.g. Docker's or Fluent Bit's multi-line settings) can use a leading
+    /// marker, such as a tab, to group the lines of a record into a single entry.
+    pub const fn with_continuation_marker(mut self, marker: Option<&'static str>) -> Self {
+        self.continuation_marker = marker;
+        self
+    }
+
+    /// Resume a panic from formatting a message, in debug builds.
+    ///
+    /// By default, if a `Display` impl in a message panics, the panic is caught and
+    /// a placeholder is written in place of the message, e.g. `<message panicked: oops>`.
+    ///
+    /// This makes those panics visible during development, while release builds keep logging.
+    ///
+    /// ***Note*** Defaults to `false`
+    pub const fn with_repanic_in_debug(mut self, repanic: bool) -> Self {
+        self.repanic_in_debug = repanic;
+        self
+    }
+
+    /// Style the span context of records forwarded from `tracing`.
+    ///
+    /// Messages starting with `span{fields}:` segments, e.g. `request{id=1}:db: query done`,
+    /// have their spans written with the `span` color, separately from the message.
+    ///
+    /// The span lifecycle records from the `tracing::span` targets are written entirely with the `span` color.
+    ///
+    /// ***Note*** Defaults to `false`. This formats each message before writing it.
+    pub const fn with_tracing_spans(mut self, tracing_spans: bool) -> Self {
+        self.tracing_spans = tracing_spans;
+        self
+    }
+
+    /// Only create the file of a [`FileLogger`](../struct.FileLogger.html) once the first record passes its filters
+    ///
+    /// This avoids leaving empty log files behind for quiet runs, especially with timestamped names.
+    ///
+    /// ***Note*** Defaults to `false`. Errors creating the file can't be reported, and any records are dropped.
+    pub const fn with_lazy_create(mut self, lazy_create: bool) -> Self {
+        self.lazy_create = lazy_create;
+        self
+    }
+
+    /// Write a header line when a file logger opens a file, with this application version
+    ///
+    /// e.g. `-- started 2024-05-01 13:00:00 UTC version=1.2.3 pid=1234 cmdline="my_app --verbose"`
+    ///
+    /// A [`FileLogger::new`](../struct.FileLogger.html#method.new) is given a writer rather than opening a file,
+    /// so it doesn't write the header or the footer.
+    ///
+    /// ```rust
+    /// # use alto_logger::Options;
+    /// let opts = Options::default().with_file_header(Some(env!("CARGO_PKG_VERSION")));
+    /// ```
+    pub const fn with_file_header(mut self, version: Option<&'static str>) -> Self {
+        self.file_header = version;
+        self
+    }
+
+    /// Write a footer line when a file logger closes a file, e.g. `-- stopped 2024-05-01 14:00:00 UTC`
+    ///
+    /// This is written when the logger is dropped, so an installed logger has to be removed with
+    /// [`shutdown`](../fn.shutdown.html). A missing footer shows that a run didn't end cleanly.
+    pub const fn with_file_footer(mut self, footer: bool) -> Self {
+        self.file_footer = footer;
+        self
+    }
+
     /// Use this `TimeConfig` with these `Options`
     // NOTE this cannot be const until const dtors are stablized (the 'String' may be dropped)
     pub fn with_time(mut self, time: TimeConfig) -> Self {
@@ -70,6 +397,12 @@ impl From<ColorConfig> for Options {
     }
 }
 
+impl From<Detection> for Options {
+    fn from(conf: Detection) -> Self {
+        Self::default().with_color_detection(conf)
+    }
+}
+
 impl From<StyleConfig> for Options {
     fn from(conf: StyleConfig) -> Self {
         Self::default().with_style(conf)
@@ -1,7 +1,66 @@
 use crate::Color;
 
+/// Text attributes applied alongside a color
+///
+/// ***Note*** Defaults to no attributes
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Attributes {
+    /// Use a bold font
+    pub bold: bool,
+    /// Use a dimmed (faint) font
+    pub dimmed: bool,
+    /// Use an italic font
+    pub italic: bool,
+    /// Underline the text
+    pub underline: bool,
+}
+
+impl Attributes {
+    /// No attributes
+    pub const NONE: Self = Self {
+        bold: false,
+        dimmed: false,
+        italic: false,
+        underline: false,
+    };
+
+    /// Enable the bold attribute
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Enable the dimmed attribute
+    pub const fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    /// Enable the italic attribute
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Enable the underline attribute
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+}
+
 /// Color configuration for the logger
+///
+/// Start from one of the presets (or the default) and change the fields that should differ:
+/// ```rust
+/// # use alto_logger::{options::{Attributes, ColorConfig}, Color};
+/// let mut color = ColorConfig::only_levels();
+/// color.target = Color::Magenta;
+/// color.level_attributes = Attributes::NONE.bold();
+/// ```
 #[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
 pub struct ColorConfig {
     /// Color for the `TRACE` level. Default: `Blue`
     pub level_trace: Color,
@@ -22,6 +81,21 @@ pub struct ColorConfig {
     pub continuation: Color,
     /// Color for the message field. Default: `#FFFFFF`
     pub message: Color,
+    /// Color for the span context of records from `tracing`. Default: `#87AFAF`
+    pub span: Color,
+
+    /// Attributes for the level field. Default: none
+    pub level_attributes: Attributes,
+    /// Attributes for the timestamp field. Default: none
+    pub timestamp_attributes: Attributes,
+    /// Attributes for the target field. Default: none
+    pub target_attributes: Attributes,
+    /// Attributes for the continuation field. Default: none
+    pub continuation_attributes: Attributes,
+    /// Attributes for the message field. Default: none
+    pub message_attributes: Attributes,
+    /// Attributes for the span context of records from `tracing`. Default: none
+    pub span_attributes: Attributes,
 }
 
 impl ColorConfig {
@@ -37,6 +111,14 @@ impl ColorConfig {
             target: Color::White,
             continuation: Color::White,
             message: Color::White,
+            span: Color::White,
+
+            level_attributes: Attributes::NONE,
+            timestamp_attributes: Attributes::NONE,
+            target_attributes: Attributes::NONE,
+            continuation_attributes: Attributes::NONE,
+            message_attributes: Attributes::NONE,
+            span_attributes: Attributes::NONE,
         }
     }
 
@@ -51,10 +133,18 @@ impl ColorConfig {
             ..Self::monochrome()
         }
     }
-}
 
-impl Default for ColorConfig {
-    fn default() -> Self {
+    /// The default colors with bold levels, dimmed timestamps and italic targets
+    pub const fn modern() -> Self {
+        Self {
+            level_attributes: Attributes::NONE.bold(),
+            timestamp_attributes: Attributes::NONE.dimmed(),
+            target_attributes: Attributes::NONE.italic(),
+            ..Self::const_default()
+        }
+    }
+
+    const fn const_default() -> Self {
         Self {
             level_trace: Color::Blue,
             level_debug: Color::Cyan,
@@ -66,6 +156,248 @@ impl Default for ColorConfig {
             target: Color::Ansi256(131),
             continuation: Color::Ansi256(237),
             message: Color::Ansi256(231),
+            span: Color::Ansi256(109),
+
+            level_attributes: Attributes::NONE,
+            timestamp_attributes: Attributes::NONE,
+            target_attributes: Attributes::NONE,
+            continuation_attributes: Attributes::NONE,
+            message_attributes: Attributes::NONE,
+            span_attributes: Attributes::NONE,
+        }
+    }
+}
+
+/// How many colors the terminal supports
+///
+/// Colors which cannot be displayed are downgraded to the nearest supported color.
+///
+/// ***Note*** Defaults to `Auto`
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// Inspect `COLORTERM` and `TERM` to determine the supported colors
+    ///
+    /// * `COLORTERM=truecolor` or `COLORTERM=24bit` allows RGB colors
+    /// * a `TERM` containing `256color` allows `Ansi256` colors
+    /// * otherwise, only the basic colors are used
+    #[default]
+    Auto,
+    /// Only the 8 basic ANSI colors
+    Basic,
+    /// The 256-color ANSI palette
+    Ansi256,
+    /// 24-bit RGB colors
+    TrueColor,
+}
+
+impl ColorDepth {
+    pub(crate) fn resolve(self) -> Self {
+        self.resolve_with(|key| std::env::var(key).ok())
+    }
+
+    fn resolve_with(self, var: impl Fn(&str) -> Option<String>) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+
+        if var("COLORTERM")
+            .filter(|s| s == "truecolor" || s == "24bit")
+            .is_some()
+        {
+            return Self::TrueColor;
+        }
+
+        match var("TERM") {
+            Some(term) if term.contains("truecolor") || term.contains("direct") => Self::TrueColor,
+            Some(term) if term.contains("256color") => Self::Ansi256,
+            // the windows console doesn't set TERM, but modern versions support all colors
+            None if cfg!(windows) => Self::TrueColor,
+            _ => Self::Basic,
+        }
+    }
+
+    pub(crate) fn downgrade(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Auto | Self::TrueColor, color) => color,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Ansi256(rgb_to_ansi256(r, g, b)),
+            (Self::Basic, Color::Ansi256(n)) => {
+                let (r, g, b) = ansi256_to_rgb(n);
+                rgb_to_basic(r, g, b)
+            }
+            (Self::Basic, Color::Rgb(r, g, b)) => rgb_to_basic(r, g, b),
+            (_, color) => color,
+        }
+    }
+}
+
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+const BASIC: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (128, 0, 0),
+    (0, 128, 0),
+    (128, 128, 0),
+    (0, 0, 128),
+    (128, 0, 128),
+    (0, 128, 128),
+    (192, 192, 192),
+    (128, 128, 128),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (0, 0, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+fn ansi256_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => BASIC[n as usize],
+        16..=231 => {
+            let n = n - 16;
+            let index = |i: u8| CUBE[i as usize];
+            (index(n / 36), index((n / 6) % 6), index(n % 6))
+        }
+        _ => {
+            let v = 8 + (n - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..=3 => 16,
+            249..=255 => 231,
+            v => 232 + ((v.max(8) - 8 + 5) / 10).min(23),
+        };
+    }
+
+    let index = |v: u8| {
+        CUBE.iter()
+            .enumerate()
+            .min_by_key(|(_, &c)| (c as i16 - v as i16).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or_default()
+    };
+    16 + 36 * index(r) + 6 * index(g) + index(b)
+}
+
+fn rgb_to_basic(r: u8, g: u8, b: u8) -> Color {
+    let max = r.max(g).max(b) as u16;
+    if max < 32 {
+        return Color::Black;
+    }
+
+    // a channel is 'on' if its close to the brightest channel
+    let on = |v: u8| v as u16 * 4 >= max * 3;
+    match (on(r), on(g), on(b)) {
+        (true, false, false) => Color::Red,
+        (false, true, false) => Color::Green,
+        (true, true, false) => Color::Yellow,
+        (false, false, true) => Color::Blue,
+        (true, false, true) => Color::Magenta,
+        (false, true, true) => Color::Cyan,
+        _ => Color::White,
+    }
+}
+
+pub(crate) fn color_spec(
+    color: Color,
+    attributes: Attributes,
+    depth: ColorDepth,
+) -> termcolor::ColorSpec {
+    let mut spec = termcolor::ColorSpec::new();
+    spec.set_fg(Some(depth.downgrade(color)))
+        .set_bold(attributes.bold)
+        .set_dimmed(attributes.dimmed)
+        .set_italic(attributes.italic)
+        .set_underline(attributes.underline);
+    spec
+}
+
+impl Default for ColorConfig {
+    fn default() -> Self {
+        Self::const_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_depth() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find_map(|(k, v)| Some(v.to_string()).filter(|_| *k == key))
+            }
+        };
+
+        let cases: &[(&[(&str, &str)], ColorDepth)] = &[
+            (&[("TERM", "xterm")], ColorDepth::Basic),
+            (&[("TERM", "xterm-256color")], ColorDepth::Ansi256),
+            (
+                &[("TERM", "xterm"), ("COLORTERM", "truecolor")],
+                ColorDepth::TrueColor,
+            ),
+            (
+                &[("TERM", "screen"), ("COLORTERM", "24bit")],
+                ColorDepth::TrueColor,
+            ),
+        ];
+
+        for (vars, expected) in cases {
+            assert_eq!(
+                ColorDepth::Auto.resolve_with(env(vars)),
+                *expected,
+                "{:?}",
+                vars
+            );
+        }
+
+        let basic = env(&[("COLORTERM", "truecolor")]);
+        assert_eq!(ColorDepth::Basic.resolve_with(basic), ColorDepth::Basic);
+    }
+
+    #[test]
+    fn downgrade() {
+        let cases = [
+            (ColorDepth::Basic, Color::Ansi256(131), Color::Red),
+            (ColorDepth::Basic, Color::Ansi256(243), Color::White),
+            (ColorDepth::Basic, Color::Ansi256(16), Color::Black),
+            (ColorDepth::Basic, Color::Ansi256(2), Color::Green),
+            (ColorDepth::Basic, Color::Rgb(0, 200, 220), Color::Cyan),
+            (ColorDepth::Basic, Color::Rgb(255, 220, 0), Color::Yellow),
+            (ColorDepth::Basic, Color::Blue, Color::Blue),
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(175, 95, 95),
+                Color::Ansi256(131),
+            ),
+            (
+                ColorDepth::Ansi256,
+                Color::Rgb(118, 118, 118),
+                Color::Ansi256(243),
+            ),
+            (
+                ColorDepth::Ansi256,
+                Color::Ansi256(131),
+                Color::Ansi256(131),
+            ),
+            (
+                ColorDepth::TrueColor,
+                Color::Rgb(1, 2, 3),
+                Color::Rgb(1, 2, 3),
+            ),
+        ];
+
+        for (depth, input, expected) in cases {
+            assert_eq!(depth.downgrade(input), expected, "{:?} {:?}", depth, input);
         }
     }
 }
@@ -0,0 +1,179 @@
+/// How the terminal logger decides whether to emit colors
+///
+/// ***Note*** Defaults to `Auto`
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Detection {
+    /// Inspect the environment to determine whether colors should be used.
+    ///
+    /// This is checked in the following order:
+    /// * `CLICOLOR_FORCE` set to anything other than `0` enables colors
+    /// * `NO_COLOR` set to anything other than an empty string disables colors
+    /// * `CLICOLOR=0` disables colors
+    /// * `TERM=dumb` disables colors
+    /// * a known CI environment variable (e.g. `CI`, `GITHUB_ACTIONS`, `JENKINS_URL`) disables colors, unless it's `false` or `0`
+    /// * otherwise, colors are used if the output is a terminal
+    #[default]
+    Auto,
+    /// Always emit colors, regardless of the environment
+    Always,
+    /// Never emit colors, regardless of the environment
+    Never,
+}
+
+/// Environment variables set by common CI providers
+const CI_VARS: &[&str] = &[
+    "CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "JENKINS_URL",
+    "TEAMCITY_VERSION",
+    "TF_BUILD",
+    "TRAVIS",
+];
+
+impl Detection {
+    pub(crate) fn color_choice(self) -> termcolor::ColorChoice {
+        self.color_choice_with(|key| std::env::var(key).ok())
+    }
+
+    fn color_choice_with(self, var: impl Fn(&str) -> Option<String>) -> termcolor::ColorChoice {
+        use termcolor::ColorChoice;
+
+        match self {
+            Self::Always => return ColorChoice::Always,
+            Self::Never => return ColorChoice::Never,
+            Self::Auto => {}
+        }
+
+        if var("CLICOLOR_FORCE").filter(|s| s != "0").is_some() {
+            return ColorChoice::Always;
+        }
+
+        // e.g. `CI=false` is the same as not being in CI
+        let ci = |s: &String| !s.eq_ignore_ascii_case("false") && s != "0";
+
+        if var("NO_COLOR").filter(|s| !s.is_empty()).is_some()
+            || var("CLICOLOR").filter(|s| s == "0").is_some()
+            || var("TERM").filter(|s| s == "dumb").is_some()
+            || CI_VARS.iter().any(|key| var(key).filter(ci).is_some())
+        {
+            return ColorChoice::Never;
+        }
+
+        ColorChoice::Auto
+    }
+}
+
+/// How colors are written to a Windows console
+///
+/// Other platforms always use ANSI escape sequences, so this only changes whether
+/// colors are forced on for a terminal.
+///
+/// ***Note*** Defaults to `Auto`
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ConsoleMode {
+    /// Use ANSI escape sequences if the console supports them,
+    /// otherwise fallback to the Windows console API
+    #[default]
+    Auto,
+    /// Always use ANSI escape sequences when writing to a terminal
+    ///
+    /// This is useful for terminals (e.g. ConEmu or mintty) where the console API misbehaves
+    Ansi,
+}
+
+impl ConsoleMode {
+    /// Adjust the `choice` for the stream being written to, which is a terminal if `is_terminal` returns true
+    pub(crate) fn color_choice(
+        self,
+        choice: termcolor::ColorChoice,
+        is_terminal: impl FnOnce() -> bool,
+    ) -> termcolor::ColorChoice {
+        use termcolor::ColorChoice;
+
+        match (self, choice) {
+            (Self::Auto, choice) | (Self::Ansi, choice @ ColorChoice::Never) => choice,
+            (Self::Ansi, ColorChoice::Auto) if !is_terminal() => ColorChoice::Never,
+            (Self::Ansi, ..) => ColorChoice::AlwaysAnsi,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use termcolor::ColorChoice;
+
+    #[test]
+    fn detection_chain() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find_map(|(k, v)| Some(v.to_string()).filter(|_| *k == key))
+            }
+        };
+
+        let cases: &[(&[(&str, &str)], ColorChoice)] = &[
+            (&[], ColorChoice::Auto),
+            (&[("TERM", "xterm-256color")], ColorChoice::Auto),
+            (&[("NO_COLOR", "1")], ColorChoice::Never),
+            (&[("NO_COLOR", "")], ColorChoice::Auto),
+            (&[("CLICOLOR", "0")], ColorChoice::Never),
+            (&[("CLICOLOR", "1")], ColorChoice::Auto),
+            (&[("TERM", "dumb")], ColorChoice::Never),
+            (&[("CI", "true")], ColorChoice::Never),
+            (&[("CI", "false")], ColorChoice::Auto),
+            (&[("CI", "FALSE")], ColorChoice::Auto),
+            (&[("CI", "0")], ColorChoice::Auto),
+            (&[("CI", "0"), ("TRAVIS", "true")], ColorChoice::Never),
+            (&[("GITHUB_ACTIONS", "true")], ColorChoice::Never),
+            (
+                &[("CI", "true"), ("CLICOLOR_FORCE", "1")],
+                ColorChoice::Always,
+            ),
+            (
+                &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "0")],
+                ColorChoice::Never,
+            ),
+        ];
+
+        for (vars, expected) in cases {
+            let got = Detection::Auto.color_choice_with(env(vars));
+            assert_eq!(got, *expected, "{:?}", vars);
+        }
+
+        let ci = env(&[("CI", "true")]);
+        assert_eq!(Detection::Always.color_choice_with(ci), ColorChoice::Always);
+        assert_eq!(
+            Detection::Never.color_choice_with(|_| None),
+            ColorChoice::Never
+        );
+    }
+
+    #[test]
+    fn console_mode() {
+        let cases = [
+            (ConsoleMode::Auto, ColorChoice::Auto, ColorChoice::Auto),
+            (ConsoleMode::Auto, ColorChoice::Always, ColorChoice::Always),
+            (
+                ConsoleMode::Ansi,
+                ColorChoice::Always,
+                ColorChoice::AlwaysAnsi,
+            ),
+            (ConsoleMode::Ansi, ColorChoice::Never, ColorChoice::Never),
+        ];
+        for (mode, choice, expected) in cases {
+            assert_eq!(mode.color_choice(choice, || true), expected, "{:?}", mode);
+        }
+
+        // only the stream being written to is checked
+        let auto =
+            |is_terminal: bool| ConsoleMode::Ansi.color_choice(ColorChoice::Auto, || is_terminal);
+        assert_eq!(auto(true), ColorChoice::AlwaysAnsi);
+        assert_eq!(auto(false), ColorChoice::Never);
+    }
+}
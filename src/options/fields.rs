@@ -0,0 +1,105 @@
+/// Which fields of a record are rendered
+///
+/// The fields are written in this order: level, time, target, thread, source and message,
+/// unless [`time_first`](#structfield.time_first) moves the time to the start of the line.
+///
+/// ***Note*** Defaults to the level, time, target and message
+///
+/// ```rust
+/// # use alto_logger::{Options, options::FieldsConfig};
+/// // e.g. `INFO  [my_app] (main) src/main.rs:12 hello`
+/// let opts = Options::default().with_fields(FieldsConfig::default().with_thread(true).with_source(true));
+/// ```
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldsConfig {
+    /// The level of the record
+    pub level: bool,
+    /// The timestamp, if the [`TimeConfig`](enum.TimeConfig.html) has one
+    pub time: bool,
+    /// The target of the record, e.g. `[my_app::db]`
+    pub target: bool,
+    /// The name (or id) of the thread rendering the record, e.g. `(main)`
+    ///
+    /// This is the thread that logged the record, unless the logger renders on another thread.
+    pub thread: bool,
+    /// The source file and line of the record, when they are known, e.g. `src/main.rs:12`
+    pub source: bool,
+    /// The message of the record
+    pub message: bool,
+    /// Whether the time is written before the level, e.g. `1714571999 INFO  [my_app] hello`
+    pub time_first: bool,
+}
+
+impl Default for FieldsConfig {
+    fn default() -> Self {
+        Self {
+            level: true,
+            time: true,
+            target: true,
+            thread: false,
+            source: false,
+            message: true,
+            time_first: false,
+        }
+    }
+}
+
+impl FieldsConfig {
+    /// Render every field
+    pub const fn all() -> Self {
+        Self {
+            level: true,
+            time: true,
+            target: true,
+            thread: true,
+            source: true,
+            message: true,
+            time_first: false,
+        }
+    }
+
+    /// Whether to render the level
+    pub const fn with_level(mut self, level: bool) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Whether to render the timestamp
+    pub const fn with_time(mut self, time: bool) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Whether to render the target
+    pub const fn with_target(mut self, target: bool) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Whether to render the thread
+    pub const fn with_thread(mut self, thread: bool) -> Self {
+        self.thread = thread;
+        self
+    }
+
+    /// Whether to render the source file and line
+    pub const fn with_source(mut self, source: bool) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Whether to render the message
+    pub const fn with_message(mut self, message: bool) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// Whether to render the time at the start of the line, before the level
+    ///
+    /// ***Note*** Defaults to false
+    pub const fn with_time_first(mut self, time_first: bool) -> Self {
+        self.time_first = time_first;
+        self
+    }
+}
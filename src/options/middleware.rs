@@ -0,0 +1,89 @@
+use crate::OwnedRecord;
+use std::sync::Arc;
+
+/// A step in the pipeline a record goes through, after it passes the filters and before it is written
+///
+/// A middleware can rewrite the record (e.g. redact secrets), or drop it by returning `None`.
+/// Closures taking and returning an [`OwnedRecord`](../struct.OwnedRecord.html) are middleware.
+///
+/// ```rust
+/// # use alto_logger::{Options, OwnedRecord};
+/// let opts = Options::default()
+///     // redaction
+///     .with_middleware(|mut record: OwnedRecord| {
+///         record.message = record.message.replace("hunter2", "*******");
+///         Some(record)
+///     })
+///     // dropping trace records
+///     .with_middleware(|record: OwnedRecord| {
+///         Some(record).filter(|record| record.level <= log::Level::Debug)
+///     });
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Transform this record, or drop it by returning `None`
+    fn transform(&self, record: OwnedRecord) -> Option<OwnedRecord>;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(OwnedRecord) -> Option<OwnedRecord> + Send + Sync,
+{
+    fn transform(&self, record: OwnedRecord) -> Option<OwnedRecord> {
+        self(record)
+    }
+}
+
+/// The middleware of some [`Options`](struct.Options.html), applied in the order they were added
+///
+/// ***Note*** Defaults to no middleware
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    steps: Vec<Arc<dyn Middleware>>,
+}
+
+impl Pipeline {
+    pub(crate) fn push(&mut self, middleware: impl Middleware + 'static) {
+        self.steps.push(Arc::new(middleware))
+    }
+
+    /// Whether there is no middleware
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// How many middleware there are
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Run the record through the middleware, calling `f` with the result unless it was dropped
+    ///
+    /// The record is only copied if there is some middleware.
+    pub(crate) fn apply(&self, record: &log::Record<'_>, f: impl FnOnce(&log::Record<'_>)) {
+        if self.steps.is_empty() {
+            return f(record);
+        }
+
+        let record = self
+            .steps
+            .iter()
+            .try_fold(OwnedRecord::from_record(record), |record, step| {
+                step.transform(record)
+            });
+        if let Some(record) = record {
+            record.with_record(f)
+        }
+    }
+}
+
+// `Options` was unwind safe before it had middleware, and a panicking middleware can't leave a record half-written
+impl std::panic::UnwindSafe for Pipeline {}
+impl std::panic::RefUnwindSafe for Pipeline {}
+
+impl std::fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("len", &self.steps.len())
+            .finish()
+    }
+}
@@ -8,6 +8,21 @@ pub enum StyleConfig {
     SingleLine,
     /// Use a multi-line format
     MultiLine,
+    /// Use a single-line format, with the level and target padded to fixed widths so the messages line up
+    ///
+    /// The widths are in characters, and the target's doesn't include its brackets.
+    /// A longer level is cut short with a trailing `…`, and a longer target with a leading `…`,
+    /// so the innermost module is kept. e.g. with a `target_width` of 12:
+    /// ```text
+    /// INFO  [my_app]       starting
+    /// WARN  […p::db::pool] slow query
+    /// ```
+    Aligned {
+        /// The width of the target
+        target_width: usize,
+        /// The width of the level
+        level_width: usize,
+    },
 }
 
 /// Defaults to Multiline
@@ -16,3 +31,42 @@ impl Default for StyleConfig {
         Self::MultiLine
     }
 }
+
+/// The letter case used when displaying the level
+///
+/// ***Note*** Defaults to Upper
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LevelCase {
+    /// e.g. `INFO`
+    #[default]
+    Upper,
+    /// e.g. `info`
+    Lower,
+    /// e.g. `Info`
+    Title,
+}
+
+impl LevelCase {
+    /// Get the name of the level in this case
+    pub const fn apply(self, level: log::Level) -> &'static str {
+        use log::Level::*;
+        match (self, level) {
+            (Self::Upper, Error) => "ERROR",
+            (Self::Upper, Warn) => "WARN",
+            (Self::Upper, Info) => "INFO",
+            (Self::Upper, Debug) => "DEBUG",
+            (Self::Upper, Trace) => "TRACE",
+            (Self::Lower, Error) => "error",
+            (Self::Lower, Warn) => "warn",
+            (Self::Lower, Info) => "info",
+            (Self::Lower, Debug) => "debug",
+            (Self::Lower, Trace) => "trace",
+            (Self::Title, Error) => "Error",
+            (Self::Title, Warn) => "Warn",
+            (Self::Title, Info) => "Info",
+            (Self::Title, Debug) => "Debug",
+            (Self::Title, Trace) => "Trace",
+        }
+    }
+}
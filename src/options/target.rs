@@ -0,0 +1,45 @@
+use std::borrow::Cow;
+
+/// Rules for rewriting the prefix of a record's target, before it is filtered and rendered
+///
+/// The first rule with a matching prefix is used, and an empty replacement strips the prefix.
+///
+/// ***Note*** Defaults to no rules
+///
+/// ```rust
+/// # use alto_logger::options::TargetRewrite;
+/// const REWRITE: TargetRewrite = TargetRewrite::new(&[
+///     ("my_company_internal_core::", "core::"),
+///     ("my_company_", ""),
+/// ]);
+/// assert_eq!(REWRITE.rewrite("my_company_internal_core::db"), "core::db");
+/// assert_eq!(REWRITE.rewrite("my_company_http::server"), "http::server");
+/// assert_eq!(REWRITE.rewrite("hyper::proto"), "hyper::proto");
+/// ```
+///
+/// Filters then use the rewritten target, e.g. `RUST_LOG=core=debug`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TargetRewrite {
+    rules: &'static [(&'static str, &'static str)],
+}
+
+impl TargetRewrite {
+    /// Create a rewrite from these `(prefix, replacement)` rules
+    pub const fn new(rules: &'static [(&'static str, &'static str)]) -> Self {
+        Self { rules }
+    }
+
+    /// Rewrite this target with the first matching rule
+    pub fn rewrite<'a>(&self, target: &'a str) -> Cow<'a, str> {
+        self.rules
+            .iter()
+            .find_map(|(prefix, replacement)| {
+                let rest = target.strip_prefix(prefix)?;
+                Some(match *replacement {
+                    "" => Cow::Borrowed(rest),
+                    replacement => Cow::Owned(format!("{}{}", replacement, rest)),
+                })
+            })
+            .unwrap_or(Cow::Borrowed(target))
+    }
+}
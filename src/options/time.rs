@@ -3,14 +3,20 @@
 /// Several helper methods for constructing this type are provided
 /// * [`Relative`](enum.TimeConfig.html#variant.Relative) timestamp style
 ///     * use [`TimeConfig::relative_now`](enum.TimeConfig.html#method.relative_now) to start the _clock_ from `now`
+///     * use [`TimeConfig::relative_from_env`](enum.TimeConfig.html#method.relative_from_env) to start it from a time given by another process
 /// * [`Timing`](enum.TimeConfig.html#variant.Timing) timestamp style
 ///     * use [`TimeConfig::relative_local`](enum.TimeConfig.html#method.relative_local).
+/// * [`MonotonicNanos`](enum.TimeConfig.html#variant.MonotonicNanos) timestamp style
+///     * use [`TimeConfig::monotonic_nanos`](enum.TimeConfig.html#method.monotonic_nanos) to start the _clock_ from `now`
+/// * [`DateTimeUptime`](enum.TimeConfig.html#variant.DateTimeUptime) timestamp style, with the `time` feature
+///     * use [`TimeConfig::date_time_with_uptime`](enum.TimeConfig.html#method.date_time_with_uptime) to start the uptime from `now`
 ///
 /// ***Note*** Defaults to the `None` timestamp
-#[derive(Debug)]
+#[derive(Debug, Default)]
 #[non_exhaustive]
 pub enum TimeConfig {
     /// No timestamp
+    #[default]
     None,
     ///
     /// Timestamp since the UNIX epoch
@@ -19,16 +25,48 @@ pub enum TimeConfig {
     ///
     /// This prints out a fractional number of seconds from when the logger was initialized.
     Relative(std::time::Instant),
+    /// Relative timestamp from a wall-clock time, e.g. the start of another process
+    ///
+    /// This prints out a fractional number of seconds since that time.
+    RelativeTo(std::time::SystemTime),
     /// Relative timestamp from the previous log statement
     ///
     /// This prints out a fractional number of seconds since the last statement was logged
+    #[cfg(not(loom))]
     Timing(std::sync::Mutex<Option<std::time::Instant>>),
+    /// Relative timestamp from the previous log statement, with loom's lock for the models in the tests
+    #[cfg(loom)]
+    Timing(loom::sync::Mutex<Option<std::time::Instant>>),
+    /// Monotonic timestamp from the start of the program, as an integer number of nanoseconds
+    ///
+    /// This is meant for post-processing, e.g. lining records up with a profiler's trace, rather than for reading.
+    MonotonicNanos(std::time::Instant),
 
     #[cfg(feature = "time")]
     /// Timestamp formatted with from UTC 'now'. See [`formatting`](https://time-rs.github.io/book/api/format-description.html)
     ///
     /// This allows you to provide a 'fixed' date time. (e.g. UTC offset or unix timestamp or whatever you want)
     DateTime(&'static [time::format_description::FormatItem<'static>]),
+
+    #[cfg(feature = "time")]
+    /// Timestamp formatted from UTC 'now' with a format description parsed at runtime. See [`TimeConfig::parse_date_time_format`](enum.TimeConfig.html#method.parse_date_time_format)
+    ParsedDateTime(time::format_description::OwnedFormatItem),
+
+    #[cfg(feature = "time")]
+    /// Timestamp formatted from 'now' in a fixed UTC offset. See [`TimeConfig::date_time_format_in`](enum.TimeConfig.html#method.date_time_format_in)
+    DateTimeIn(
+        time::UtcOffset,
+        &'static [time::format_description::FormatItem<'static>],
+    ),
+
+    #[cfg(feature = "time")]
+    /// Timestamp formatted from UTC 'now', followed by the time since the start of the program, e.g. `2024-05-02 10:03:22 (+03:12:09)`
+    ///
+    /// See [`TimeConfig::date_time_with_uptime`](enum.TimeConfig.html#method.date_time_with_uptime)
+    DateTimeUptime(
+        std::time::Instant,
+        &'static [time::format_description::FormatItem<'static>],
+    ),
 }
 
 impl Clone for TimeConfig {
@@ -37,9 +75,17 @@ impl Clone for TimeConfig {
             Self::None => Self::None,
             Self::Unix => Self::Unix,
             Self::Relative(inner) => Self::Relative(*inner),
+            Self::RelativeTo(inner) => Self::RelativeTo(*inner),
             Self::Timing(_) => Self::Timing(Default::default()),
+            Self::MonotonicNanos(inner) => Self::MonotonicNanos(*inner),
+            #[cfg(feature = "time")]
+            Self::DateTime(inner) => Self::DateTime(inner),
+            #[cfg(feature = "time")]
+            Self::ParsedDateTime(inner) => Self::ParsedDateTime(inner.clone()),
+            #[cfg(feature = "time")]
+            Self::DateTimeIn(offset, inner) => Self::DateTimeIn(*offset, inner),
             #[cfg(feature = "time")]
-            Self::DateTime(inner) => Self::DateTime(inner.clone()),
+            Self::DateTimeUptime(start, inner) => Self::DateTimeUptime(*start, inner),
         }
     }
 }
@@ -50,11 +96,56 @@ impl TimeConfig {
         Self::Relative(std::time::Instant::now())
     }
 
+    /// Create a Relative timestamp starting at `start`, which can be an `Instant` or a `SystemTime`
+    ///
+    /// A `SystemTime` lets multiple processes count from the same time, e.g. a parent's start time passed to its children:
+    /// ```rust,no_run
+    /// # use alto_logger::TimeConfig;
+    /// # use std::time::{Duration, UNIX_EPOCH};
+    /// let millis: u64 = std::env::var("APP_START_MS").unwrap().parse().unwrap();
+    /// let time = TimeConfig::relative_to(UNIX_EPOCH + Duration::from_millis(millis));
+    /// ```
+    pub fn relative_to(start: impl Into<Epoch>) -> Self {
+        match start.into() {
+            Epoch::Instant(start) => Self::Relative(start),
+            Epoch::SystemTime(start) => Self::RelativeTo(start),
+        }
+    }
+
+    /// Create a Relative timestamp starting at the UNIX time (in seconds, which can be fractional) in this environment variable
+    ///
+    /// This lets a supervisor or a parent process give its children the same start time.
+    /// If the variable isn't set, or isn't a valid time, this starts at 'now'.
+    /// ```rust,no_run
+    /// # use alto_logger::TimeConfig;
+    /// // e.g. `APP_START_UNIX=1714571999.25`
+    /// let time = TimeConfig::relative_from_env("APP_START_UNIX");
+    /// ```
+    pub fn relative_from_env(key: &str) -> Self {
+        Self::relative_from_unix(std::env::var(key).ok().as_deref())
+    }
+
+    fn relative_from_unix(secs: Option<&str>) -> Self {
+        let start = secs
+            .and_then(|secs| secs.trim().parse::<f64>().ok())
+            .and_then(|secs| std::time::Duration::try_from_secs_f64(secs).ok())
+            .and_then(|secs| std::time::UNIX_EPOCH.checked_add(secs));
+        match start {
+            Some(start) => Self::RelativeTo(start),
+            None => Self::relative_now(),
+        }
+    }
+
     /// Create a Relative timestamp based on the previous logging statement
     pub fn relative_local() -> Self {
         Self::Timing(Default::default())
     }
 
+    /// Create a MonotonicNanos timestamp starting at 'now', e.g. `1500000000` for 1.5 seconds later
+    pub fn monotonic_nanos() -> Self {
+        Self::MonotonicNanos(std::time::Instant::now())
+    }
+
     /// Create a timestamp based on the UNIX epoch (number of seconds since Jan. 1 1970)
     pub fn unix_timestamp() -> Self {
         Self::Unix
@@ -74,10 +165,122 @@ impl TimeConfig {
     ) -> Self {
         Self::DateTime(format_description)
     }
+
+    #[cfg(feature = "time")]
+    /// An ISO 8601 UTC timestamp with milliseconds, e.g. `2024-05-01T13:00:00.123Z`
+    pub fn iso8601_millis() -> Self {
+        Self::DateTime(time::macros::format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
+        ))
+    }
+
+    #[cfg(feature = "time")]
+    /// An ISO 8601 UTC timestamp with microseconds, e.g. `2024-05-01T13:00:00.123456Z`
+    pub fn iso8601_micros() -> Self {
+        Self::DateTime(time::macros::format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:6]Z"
+        ))
+    }
+
+    #[cfg(feature = "time")]
+    /// Create a DateTime format, displayed in this fixed UTC `offset` regardless of the host's time zone
+    ///
+    /// ```rust
+    /// # use alto_logger::TimeConfig;
+    /// use time::{macros::format_description, UtcOffset};
+    /// // e.g. Asia/Tokyo, which has no daylight saving time
+    /// let tokyo = UtcOffset::from_hms(9, 0, 0).unwrap();
+    /// let time = TimeConfig::date_time_format_in(
+    ///     tokyo,
+    ///     format_description!("[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory]:[offset_minute]"),
+    /// );
+    /// ```
+    pub fn date_time_format_in(
+        offset: time::UtcOffset,
+        format_description: &'static [time::format_description::FormatItem<'static>],
+    ) -> Self {
+        Self::DateTimeIn(offset, format_description)
+    }
+
+    #[cfg(feature = "time")]
+    /// Create a DateTime format followed by the time since 'now', as `(+hours:minutes:seconds)`
+    ///
+    /// ```rust
+    /// # use alto_logger::TimeConfig;
+    /// use time::macros::format_description;
+    /// // e.g. `2024-05-02 10:03:22 (+03:12:09)`
+    /// let time = TimeConfig::date_time_with_uptime(format_description!("[year]-[month]-[day] [hour]:[minute]:[second]"));
+    /// ```
+    pub fn date_time_with_uptime(
+        format_description: &'static [time::format_description::FormatItem<'static>],
+    ) -> Self {
+        Self::DateTimeUptime(std::time::Instant::now(), format_description)
+    }
+
+    #[cfg(feature = "time")]
+    /// Create a DateTime format by parsing this format description
+    ///
+    /// See the formatting description [here](https://time-rs.github.io/book/api/format-description.html)
+    ///
+    /// ```rust
+    /// # use alto_logger::TimeConfig;
+    /// let time = TimeConfig::parse_date_time_format("[hour]:[minute]:[second]").unwrap();
+    /// assert!(TimeConfig::parse_date_time_format("[hour").unwrap_err().is_invalid_time_format());
+    /// ```
+    pub fn parse_date_time_format(format: &str) -> Result<Self, crate::Error> {
+        match time::format_description::parse_owned::<1>(format) {
+            Ok(items) => Ok(Self::ParsedDateTime(items)),
+            Err(err) => Err(crate::Error::InvalidTimeFormat {
+                format: format.to_string(),
+                reason: err.to_string(),
+            }),
+        }
+    }
+}
+
+/// The start of a relative timestamp. See [`TimeConfig::relative_to`](enum.TimeConfig.html#method.relative_to)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Epoch {
+    /// A monotonic time in this process
+    Instant(std::time::Instant),
+    /// A wall-clock time, which can be shared with other processes
+    SystemTime(std::time::SystemTime),
+}
+
+impl From<std::time::Instant> for Epoch {
+    fn from(start: std::time::Instant) -> Self {
+        Self::Instant(start)
+    }
 }
 
-impl Default for TimeConfig {
-    fn default() -> Self {
-        Self::None
+impl From<std::time::SystemTime> for Epoch {
+    fn from(start: std::time::SystemTime) -> Self {
+        Self::SystemTime(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn relative_from_unix() {
+        let start = |secs| match TimeConfig::relative_from_unix(secs) {
+            TimeConfig::RelativeTo(start) => Some(start),
+            TimeConfig::Relative(..) => None,
+            time => panic!("unexpected {:?}", time),
+        };
+        assert_eq!(
+            start(Some("1714571999")),
+            Some(UNIX_EPOCH + Duration::from_secs(1_714_571_999))
+        );
+        assert_eq!(
+            start(Some(" 1714571999.25\n")),
+            Some(UNIX_EPOCH + Duration::from_millis(1_714_571_999_250))
+        );
+        for invalid in [None, Some(""), Some("yesterday"), Some("-1"), Some("NaN")] {
+            assert_eq!(start(invalid), None, "{:?}", invalid);
+        }
     }
 }
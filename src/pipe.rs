@@ -0,0 +1,182 @@
+use std::io::{Read, Write};
+
+/// Spawn a thread which logs each line read from `reader` as a record with this `level` and `target`
+///
+/// This is meant for the output of a child process, and it takes an `Option` so `child.stdout.take()` can be passed directly.
+/// The thread exits when the reader is closed, e.g. when the child exits.
+///
+/// Lines that aren't valid UTF-8 are logged lossily, and `None` is returned if there wasn't a reader.
+///
+/// ```rust,no_run
+/// # use std::process::{Command, Stdio};
+/// alto_logger::init_term_logger().unwrap();
+///
+/// let mut child = Command::new("cargo")
+///     .arg("build")
+///     .stdout(Stdio::piped())
+///     .stderr(Stdio::piped())
+///     .spawn()
+///     .unwrap();
+///
+/// alto_logger::pipe_child(child.stdout.take(), log::Level::Info, "cargo");
+/// alto_logger::pipe_child(child.stderr.take(), log::Level::Warn, "cargo");
+/// child.wait().unwrap();
+/// ```
+pub fn pipe_child<R>(
+    reader: Option<R>,
+    level: log::Level,
+    target: impl Into<String>,
+) -> Option<std::thread::JoinHandle<()>>
+where
+    R: Read + Send + 'static,
+{
+    let (mut reader, writer) = (reader?, LogWriter::new(level, target));
+    let handle = std::thread::Builder::new()
+        .name(format!("alto_logger pipe ({})", writer.target))
+        .spawn(move || {
+            let _ = std::io::copy(&mut reader, &mut { writer });
+        })
+        .expect("spawn pipe thread");
+    Some(handle)
+}
+
+/// A `std::io::Write` adapter which logs each line written to it as a record with this `level` and `target`
+///
+/// A partial line is kept until its newline is written, until this is flushed or dropped,
+/// or until it reaches [`LogWriter::MAX_LINE`] bytes.
+/// Lines that aren't valid UTF-8 are logged lossily.
+///
+/// ```rust,no_run
+/// # use std::io::Write as _;
+/// # use alto_logger::LogWriter;
+/// alto_logger::init_term_logger().unwrap();
+///
+/// let mut out = LogWriter::new(log::Level::Debug, "csv_dump");
+/// writeln!(out, "id,name").unwrap();
+/// writeln!(out, "1,foo").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct LogWriter {
+    level: log::Level,
+    target: String,
+    buf: Vec<u8>,
+}
+
+impl LogWriter {
+    /// The most bytes of a partial line that'll be kept before it's logged anyway
+    pub const MAX_LINE: usize = 64 * 1024;
+
+    /// Create a writer which logs lines with this `level` and `target`
+    pub fn new(level: log::Level, target: impl Into<String>) -> Self {
+        Self {
+            level,
+            target: target.into(),
+            buf: Vec::new(),
+        }
+    }
+
+    fn emit(level: log::Level, target: &str, line: &[u8]) {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        log::log!(target: target, level, "{}", String::from_utf8_lossy(line))
+    }
+
+    fn emit_pending(&mut self) {
+        if !self.buf.is_empty() {
+            Self::emit(self.level, &self.target, &self.buf);
+            self.buf.clear();
+        }
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let Self { level, target, .. } = self;
+        take_lines(&mut self.buf, buf, Self::MAX_LINE, |line| {
+            Self::emit(*level, target, line)
+        });
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.emit_pending();
+        log::logger().flush();
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        self.emit_pending()
+    }
+}
+
+/// Append `data` to `pending`, calling `emit` for each complete line without its newline
+///
+/// A partial line is emitted once it reaches `max` bytes.
+fn take_lines(pending: &mut Vec<u8>, data: &[u8], max: usize, mut emit: impl FnMut(&[u8])) {
+    for chunk in data.split_inclusive(|&c| c == b'\n') {
+        match chunk.strip_suffix(b"\n") {
+            Some(line) if pending.is_empty() => emit(line),
+            Some(line) => {
+                pending.extend_from_slice(line);
+                emit(pending);
+                pending.clear();
+            }
+            None => {
+                pending.extend_from_slice(chunk);
+                if pending.len() >= max {
+                    emit(pending);
+                    pending.clear();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let mut pending = vec![];
+        let mut lines = vec![];
+        let mut write = |data: &[u8]| {
+            take_lines(&mut pending, data, 8, |line| {
+                lines.push(String::from_utf8_lossy(line).into_owned())
+            })
+        };
+
+        write(b"first\nsec");
+        write(b"ond\n\ninvalid \xff\npart");
+        write(b"ial");
+        write(b"\n");
+        write(b"last");
+        write(b"0123456789");
+        write(b"ab\n");
+
+        assert_eq!(
+            lines,
+            [
+                "first",
+                "second",
+                "",
+                "invalid \u{fffd}",
+                "partial",
+                "last0123456789",
+                "ab"
+            ]
+        );
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn flush() {
+        let mut out = LogWriter::new(log::Level::Info, "pipe");
+        write!(out, "partial").unwrap();
+        assert_eq!(out.buf, b"partial");
+
+        out.flush().unwrap();
+        assert!(out.buf.is_empty());
+    }
+}
@@ -0,0 +1,390 @@
+/*! Reading records back from log files, and converting them between formats
+
+Records can be read from the text written by the loggers in this crate (without colors),
+or from the lines written by [`Format::json`](../struct.Format.html#method.json) and
+[`Format::logfmt`](../struct.Format.html#method.logfmt).
+
+```rust
+use alto_logger::{reader::{self, Input}, Format};
+
+let text = "\
+INFO  1714571999 [my_app] starting
+WARN  1714572000 [my_app::db] slow query
+";
+
+let mut json = Vec::new();
+reader::convert(Input::Text, text.as_bytes(), &Format::json(), &mut json).unwrap();
+assert_eq!(
+    String::from_utf8(json).unwrap(),
+    r#"{"schema_version":1,"time":"2024-05-01T13:59:59Z","level":"INFO","target":"my_app","message":"starting"}
+{"schema_version":1,"time":"2024-05-01T14:00:00Z","level":"WARN","target":"my_app::db","message":"slow query"}
+"#
+);
+```
+*/
+
+use crate::{date::DateTime, record::replaying, Format, OwnedRecord};
+use std::{
+    io::{BufRead, Write},
+    time::{Duration, SystemTime},
+};
+
+/// The format of the lines being read
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+    /// The text written by the loggers in this crate, without colors
+    ///
+    /// Lines that don't start a record (e.g. the `MultiLine` continuation, or newlines in a message)
+    /// are added to the message of the record before them.
+    Text,
+    /// A JSON object per line, as written by [`Format::json`](../struct.Format.html#method.json)
+    Json,
+    /// `key=value` pairs per line, as written by [`Format::logfmt`](../struct.Format.html#method.logfmt)
+    Logfmt,
+}
+
+/// Parse a single line as a record, if it is one
+///
+/// Timestamps are read from a UNIX timestamp or an RFC 3339 date-time. Records without one
+/// are given the current time.
+///
+/// For `Json` and `Logfmt`, the keys other than the time, level, target and message become key-values.
+///
+/// ```rust
+/// # use alto_logger::reader::{parse_line, Input};
+/// let record = parse_line(Input::Logfmt, r#"level=warn target=my_app msg="disk is full" free=0"#).unwrap();
+/// assert_eq!(record.level, log::Level::Warn);
+/// assert_eq!(record.message, "disk is full");
+/// assert_eq!(record.kv, [(String::from("free"), String::from("0"))]);
+/// ```
+pub fn parse_line(input: Input, line: &str) -> Option<OwnedRecord> {
+    match input {
+        Input::Text => parse_text(line),
+        Input::Json => from_fields(parse_json(line)?),
+        Input::Logfmt => from_fields(parse_logfmt(line)?),
+    }
+}
+
+/// Read the records from `reader`, skipping the lines that aren't records
+pub fn records<R: BufRead>(input: Input, reader: R) -> Records<R> {
+    Records {
+        input,
+        lines: reader.lines(),
+        pending: None,
+    }
+}
+
+/// Read the records from `reader`, and write them to `writer` with `format`
+///
+/// This returns how many records were written.
+pub fn convert(
+    input: Input,
+    reader: impl BufRead,
+    format: &Format,
+    mut writer: impl Write,
+) -> std::io::Result<u64> {
+    let (mut count, mut buf) = (0, Vec::new());
+    for record in records(input, reader) {
+        let record = record?;
+        buf.clear();
+        replaying(record.timestamp, record.thread.as_deref(), || {
+            record.with_record(|record| format.write(record, &mut buf))
+        });
+        writer.write_all(&buf)?;
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+/// An iterator over the records read from a reader. See [`records`](fn.records.html)
+#[derive(Debug)]
+pub struct Records<R> {
+    input: Input,
+    lines: std::io::Lines<R>,
+    pending: Option<OwnedRecord>,
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = std::io::Result<OwnedRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => return Some(Err(err)),
+                None => return self.pending.take().map(Ok),
+            };
+
+            let Some(record) = parse_line(self.input, &line) else {
+                if let (Input::Text, Some(pending)) = (self.input, &mut self.pending) {
+                    match line.strip_prefix("⤷ ") {
+                        Some(message) if pending.message.is_empty() => {
+                            pending.message.push_str(message)
+                        }
+                        _ => {
+                            pending.message.push('\n');
+                            pending.message.push_str(&line);
+                        }
+                    }
+                }
+                continue;
+            };
+
+            // text records are held until the next one starts, in case they continue on the next lines
+            match self.input {
+                Input::Text => match self.pending.replace(record) {
+                    Some(record) => return Some(Ok(record)),
+                    None => continue,
+                },
+                _ => return Some(Ok(record)),
+            }
+        }
+    }
+}
+
+/// Parse a `LEVEL timestamp [target] message` line
+fn parse_text(line: &str) -> Option<OwnedRecord> {
+    // a `<N>` syslog priority prefix
+    let line = match line.strip_prefix('<').and_then(|s| s.split_once('>')) {
+        Some((priority, rest)) if priority.bytes().all(|b| b.is_ascii_digit()) => rest,
+        _ => line,
+    };
+
+    // the level comes before the time, unless the layout put the time first
+    let (head, rest) = line.split_once('[')?;
+    let (level, time) = match head.split_once(' ') {
+        Some((level, time)) if level.parse::<log::Level>().is_ok() => (level, time),
+        _ => {
+            let (time, level) = head.trim_end().rsplit_once(' ').unwrap_or(("", head));
+            (level, time)
+        }
+    };
+    let level = level.trim().parse().ok()?;
+
+    let (target, rest) = match rest.starts_with('"') {
+        true => unquote(rest)?,
+        false => {
+            let (target, rest) = rest.split_at(rest.find(']')?);
+            (target.to_string(), rest)
+        }
+    };
+    let message = rest.strip_prefix(']')?;
+
+    Some(OwnedRecord {
+        timestamp: parse_time(time.trim()).unwrap_or_else(SystemTime::now),
+        message: message.strip_prefix(' ').unwrap_or(message).to_string(),
+        ..OwnedRecord::new(level, target)
+    })
+}
+
+/// Parse a flat JSON object of strings, with an optional `fields` object of strings
+///
+/// Numbers, booleans and `null` are kept as they were written.
+fn parse_json(line: &str) -> Option<Vec<(String, String)>> {
+    fn object<'a>(
+        mut s: &'a str,
+        out: &mut Vec<(String, String)>,
+        nested: bool,
+    ) -> Option<&'a str> {
+        s = s.trim_start().strip_prefix('{')?.trim_start();
+        if let Some(rest) = s.strip_prefix('}') {
+            return Some(rest);
+        }
+        loop {
+            let (key, rest) = unquote(s.trim_start())?;
+            s = rest.trim_start().strip_prefix(':')?.trim_start();
+
+            if s.starts_with('{') && !nested && key == "fields" {
+                s = object(s, out, true)?;
+            } else if s.starts_with('"') {
+                let (value, rest) = unquote(s)?;
+                out.push((key, value));
+                s = rest;
+            } else {
+                let end = s.find([',', '}']).unwrap_or(s.len());
+                let (value, rest) = s.split_at(end);
+                out.push((key, value.trim().to_string()));
+                s = rest;
+            }
+
+            s = s.trim_start();
+            match s.as_bytes().first()? {
+                b',' => s = &s[1..],
+                b'}' => return Some(&s[1..]),
+                _ => return None,
+            }
+        }
+    }
+
+    let mut fields = Vec::new();
+    let rest = object(line, &mut fields, false)?;
+    rest.trim().is_empty().then_some(fields)
+}
+
+/// Parse `key=value` pairs, where the values can be quoted
+fn parse_logfmt(line: &str) -> Option<Vec<(String, String)>> {
+    let (mut fields, mut s) = (Vec::new(), line.trim_start());
+    while !s.is_empty() {
+        let (key, rest) = s.split_once('=')?;
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return None;
+        }
+        let (value, rest) = match rest.starts_with('"') {
+            true => unquote(rest)?,
+            false => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let (value, rest) = rest.split_at(end);
+                (value.to_string(), rest)
+            }
+        };
+        fields.push((key.to_string(), value));
+        s = rest.trim_start();
+    }
+    Some(fields)
+}
+
+/// Build a record from the fields of a JSON or logfmt line, which needs at least a level
+fn from_fields(fields: Vec<(String, String)>) -> Option<OwnedRecord> {
+    let (mut level, mut target, mut message, mut timestamp) = (None, None, None, None);
+    let mut kv = Vec::new();
+    for (key, value) in fields {
+        match key.as_str() {
+            "level" | "lvl" if level.is_none() => level = value.parse().ok(),
+            "target" if target.is_none() => target = Some(value),
+            "message" | "msg" if message.is_none() => message = Some(value),
+            "time" | "ts" | "timestamp" if timestamp.is_none() => timestamp = parse_time(&value),
+            "schema_version" => {}
+            _ => kv.push((key, value)),
+        }
+    }
+
+    Some(OwnedRecord {
+        timestamp: timestamp.unwrap_or_else(SystemTime::now),
+        message: message.unwrap_or_default(),
+        kv,
+        ..OwnedRecord::new(level?, target.unwrap_or_default())
+    })
+}
+
+/// Parse a UNIX timestamp (in seconds) or an RFC 3339 date-time
+fn parse_time(time: &str) -> Option<SystemTime> {
+    let since_epoch = match time.parse::<f64>() {
+        Ok(secs) => Duration::try_from_secs_f64(secs).ok()?,
+        Err(..) => DateTime::parse_rfc3339(time)?,
+    };
+    SystemTime::UNIX_EPOCH.checked_add(since_epoch)
+}
+
+/// Unescape a quoted string at the start of `s`, returning it and the rest of `s`
+///
+/// This understands both JSON escapes (`\u00e9`) and Rust's debug escapes (`\u{e9}`)
+fn unquote(s: &str) -> Option<(String, &str)> {
+    let mut out = String::new();
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &s[i + 2..])),
+            '\\' => out.push(match chars.next()?.1 {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                'u' => {
+                    let rest = chars.as_str();
+                    let (hex, skip) = match rest.strip_prefix('{') {
+                        Some(rest) => (rest.split_once('}')?.0, rest.find('}')? + 2),
+                        None => (rest.get(..4)?, 4),
+                    };
+                    chars.nth(skip - 1);
+                    char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+                }
+                c => c,
+            }),
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{FieldsConfig, LevelCase, Options, StyleConfig, TimeConfig};
+
+    #[test]
+    fn formats() {
+        let kv = [("free", "0 B"), ("disk", "sda1")];
+        let record = log::Record::builder()
+            .level(log::Level::Warn)
+            .target("my \"app\"")
+            .args(format_args!("disk is \"full\"\n\tsee é"))
+            .key_values(&kv)
+            .build();
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_571_999);
+
+        let text = Options::default()
+            .with_time(TimeConfig::unix_timestamp())
+            .with_level_case(LevelCase::Lower);
+        let cases = [
+            (Input::Text, Format::text(text.clone()), false),
+            (
+                Input::Text,
+                Format::text(text.clone().with_style(StyleConfig::MultiLine)),
+                false,
+            ),
+            (
+                Input::Text,
+                Format::text(text.with_fields(FieldsConfig::default().with_time_first(true))),
+                false,
+            ),
+            (Input::Json, Format::json(), true),
+            (Input::Logfmt, Format::logfmt(), true),
+        ];
+
+        for (input, format, has_kv) in cases {
+            let mut buf = Vec::new();
+            replaying(timestamp, None, || format.write(&record, &mut buf));
+            buf.extend_from_slice(b"not a record\n");
+
+            let records = records(input, &*buf)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let expected = OwnedRecord {
+                timestamp,
+                message: match input {
+                    Input::Text => String::from("disk is \"full\"\n\tsee é\nnot a record"),
+                    _ => String::from("disk is \"full\"\n\tsee é"),
+                },
+                kv: match has_kv {
+                    true => vec![
+                        (String::from("free"), String::from("0 B")),
+                        (String::from("disk"), String::from("sda1")),
+                    ],
+                    false => vec![],
+                },
+                ..OwnedRecord::new(log::Level::Warn, "my \"app\"")
+            };
+            assert_eq!(records, [expected], "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for (input, line) in [
+            (Input::Text, "hello [world]"),
+            (Input::Text, "INFO no target"),
+            (Input::Json, r#"{"message":"no level"}"#),
+            (Input::Json, r#"{"level":"info""#),
+            (Input::Json, r#"{"level":"info"} trailing"#),
+            (Input::Logfmt, "level=info oops"),
+            (Input::Logfmt, r#"level=info msg="unterminated"#),
+        ] {
+            assert_eq!(parse_line(input, line), None, "{}", line);
+        }
+
+        assert_eq!(unquote(r#""\u{e9}é""#).unwrap().0, "éé");
+        assert_eq!(unquote(r#""\u{zz}""#), None);
+    }
+}
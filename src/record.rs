@@ -0,0 +1,287 @@
+use std::{
+    cell::{Cell, RefCell},
+    time::SystemTime,
+};
+
+thread_local! {
+    // the timestamp of the record being replayed on this thread
+    static REPLAYING: Cell<Option<SystemTime>> = const { Cell::new(None) };
+    // the thread the record being replayed on this thread was logged on, if it's known
+    static REPLAYING_THREAD: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// An owned copy of a `log::Record`
+///
+/// ```rust
+/// # use alto_logger::OwnedRecord;
+/// let record = OwnedRecord::from(
+///     &log::Record::builder()
+///         .level(log::Level::Warn)
+///         .target("my_app")
+///         .args(format_args!("disk is {}% full", 90))
+///         .build(),
+/// );
+/// assert_eq!(record.message, "disk is 90% full");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OwnedRecord {
+    /// The level of the record
+    pub level: log::Level,
+    /// The target of the record
+    pub target: String,
+    /// The module the record was logged from, if known
+    pub module_path: Option<String>,
+    /// The source file the record was logged from, if known
+    pub file: Option<String>,
+    /// The line the record was logged from, if known
+    pub line: Option<u32>,
+    /// When the record was copied
+    pub timestamp: SystemTime,
+    /// The name of the thread the record was logged on (or its id, if it's unnamed), if known
+    pub thread: Option<String>,
+    /// The formatted message
+    pub message: String,
+    /// The formatted key-values of the record, in order
+    pub kv: Vec<(String, String)>,
+}
+
+impl OwnedRecord {
+    /// An empty record with this level and target, with the current time as its timestamp, and no thread
+    pub fn new(level: log::Level, target: impl Into<String>) -> Self {
+        Self {
+            level,
+            target: target.into(),
+            module_path: None,
+            file: None,
+            line: None,
+            timestamp: SystemTime::now(),
+            thread: None,
+            message: String::new(),
+            kv: Vec::new(),
+        }
+    }
+
+    /// Copy this record, with the current time and thread (or those of a record being replayed)
+    pub fn from_record(record: &log::Record<'_>) -> Self {
+        let thread = match replayed_time() {
+            Some(..) => REPLAYING_THREAD.with_borrow(Clone::clone),
+            None => Some(with_thread(str::to_string)),
+        };
+        Self {
+            level: record.level(),
+            target: record.target().to_string(),
+            module_path: record.module_path().map(ToString::to_string),
+            file: record.file().map(ToString::to_string),
+            line: record.line(),
+            timestamp: replayed_time().unwrap_or_else(SystemTime::now),
+            thread,
+            message: record.args().to_string(),
+            kv: collect_kv(record.key_values()),
+        }
+    }
+
+    /// Borrow this as a `log::Record`
+    pub fn with_record<T>(&self, f: impl FnOnce(&log::Record<'_>) -> T) -> T {
+        f(&log::Record::builder()
+            .level(self.level)
+            .target(&self.target)
+            .module_path(self.module_path.as_deref())
+            .file(self.file.as_deref())
+            .line(self.line)
+            .key_values(&self.kv)
+            .args(format_args!("{}", self.message))
+            .build())
+    }
+}
+
+impl From<&log::Record<'_>> for OwnedRecord {
+    fn from(record: &log::Record<'_>) -> Self {
+        Self::from_record(record)
+    }
+}
+
+/// Feed stored records through a logger, e.g. to write a ring buffer to a file on demand, or to convert between formats
+///
+/// Records the logger doesn't enable are skipped, and the logger is flushed afterwards.
+///
+/// The loggers in this crate render the timestamp and thread of each record rather than the current ones.
+/// Loggers that hand records off to another thread will see the current time.
+///
+/// ```rust
+/// # use alto_logger::{FileLogger, Options, OwnedRecord};
+/// let records = vec![OwnedRecord::from(
+///     &log::Record::builder()
+///         .level(log::Level::Error)
+///         .args(format_args!("something failed"))
+///         .build(),
+/// )];
+///
+/// # let path = std::env::temp_dir().join(format!("alto_logger_replay_doc_{}.log", std::process::id()));
+/// let logger = FileLogger::append(Options::default(), &path).unwrap();
+/// alto_logger::replay(records, &logger);
+/// # std::fs::remove_file(path).unwrap();
+/// ```
+pub fn replay(records: impl IntoIterator<Item = OwnedRecord>, logger: &impl log::Log) {
+    for record in records {
+        record.with_record(|owned| {
+            if !logger.enabled(owned.metadata()) {
+                return;
+            }
+            replaying(record.timestamp, record.thread.as_deref(), || {
+                logger.log(owned)
+            })
+        });
+    }
+    logger.flush();
+}
+
+/// Run `f`, with `timestamp` and `thread` as the time and thread of the record being replayed on this thread
+///
+/// The previous ones are restored even if `f` panics.
+pub(crate) fn replaying<T>(
+    timestamp: SystemTime,
+    thread: Option<&str>,
+    f: impl FnOnce() -> T,
+) -> T {
+    struct Restore(Option<SystemTime>, Option<String>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            REPLAYING.with(|cell| cell.set(self.0));
+            REPLAYING_THREAD.with(|cell| cell.replace(self.1.take()));
+        }
+    }
+
+    let _restore = Restore(
+        REPLAYING.with(|cell| cell.replace(Some(timestamp))),
+        REPLAYING_THREAD.with(|cell| cell.replace(thread.map(ToString::to_string))),
+    );
+    f()
+}
+
+/// The timestamp of the record being replayed on this thread, if any
+pub(crate) fn replayed_time() -> Option<SystemTime> {
+    REPLAYING.with(Cell::get)
+}
+
+/// Run `f` with the thread of the record being replayed on this thread, or else the current thread
+///
+/// That's the thread's name, or its id if it's unnamed.
+pub(crate) fn with_thread<T>(f: impl FnOnce(&str) -> T) -> T {
+    REPLAYING_THREAD.with_borrow(|replayed| {
+        if let Some(thread) = replayed {
+            return f(thread);
+        }
+        let current = std::thread::current();
+        match current.name() {
+            Some(name) => f(name),
+            None => f(&format!("{:?}", current.id())),
+        }
+    })
+}
+
+pub(crate) fn collect_kv(source: &dyn log::kv::Source) -> Vec<(String, String)> {
+    struct Collect(Vec<(String, String)>);
+    impl<'kvs> log::kv::VisitSource<'kvs> for Collect {
+        fn visit_pair(
+            &mut self,
+            key: log::kv::Key<'kvs>,
+            value: log::kv::Value<'kvs>,
+        ) -> Result<(), log::kv::Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    let mut collect = Collect(Vec::with_capacity(source.count()));
+    let _ = source.visit(&mut collect);
+    collect.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let kv = [("id", 42_u64), ("attempt", 2)];
+        let record = OwnedRecord::from(
+            &log::Record::builder()
+                .level(log::Level::Error)
+                .target("foo")
+                .line(Some(7))
+                .key_values(&kv)
+                .args(format_args!("failed"))
+                .build(),
+        );
+        assert_eq!(
+            record.kv,
+            [
+                (String::from("id"), String::from("42")),
+                (String::from("attempt"), String::from("2"))
+            ]
+        );
+
+        let copy = record.with_record(OwnedRecord::from_record);
+        assert_eq!(
+            OwnedRecord {
+                timestamp: record.timestamp,
+                ..copy
+            },
+            record
+        );
+    }
+
+    #[test]
+    fn replaying_panic() {
+        let timestamp = SystemTime::UNIX_EPOCH;
+        let panicked = std::panic::catch_unwind(|| {
+            replaying(timestamp, Some("worker"), || {
+                assert_eq!(replayed_time(), Some(timestamp));
+                assert_eq!(with_thread(str::to_string), "worker");
+                panic!("a logger panicked")
+            })
+        });
+        assert!(panicked.is_err());
+        assert_eq!(replayed_time(), None);
+        assert_eq!(REPLAYING_THREAD.with_borrow(Clone::clone), None);
+    }
+
+    #[test]
+    fn replay() {
+        use crate::{Format, Logger, Sink};
+        use std::time::Duration;
+
+        let record = |level, message: &str| OwnedRecord {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_571_999),
+            ..OwnedRecord::from(
+                &log::Record::builder()
+                    .level(level)
+                    .target("my_app")
+                    .args(format_args!("{}", message))
+                    .build(),
+            )
+        };
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let logger = Logger::new(Format::logfmt(), Sink::channel(tx));
+        logger.reload_handle().set("info");
+        super::replay(
+            [
+                record(log::Level::Info, "a"),
+                record(log::Level::Debug, "b"),
+                record(log::Level::Warn, "c"),
+            ],
+            &logger,
+        );
+
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            [
+                r#"time=2024-05-01T13:59:59Z level=info target=my_app msg=a"#,
+                r#"time=2024-05-01T13:59:59Z level=warn target=my_app msg=c"#,
+            ]
+        );
+        assert_eq!(replayed_time(), None);
+    }
+}
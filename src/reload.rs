@@ -0,0 +1,584 @@
+use crate::{
+    filters::{file, SharedFilters},
+    Filters,
+};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+/// A handle for replacing the filters of a logger at runtime
+///
+/// This can be cloned and sent to other threads.
+///
+/// ```rust
+/// # use alto_logger::TermLogger;
+/// let logger = TermLogger::default();
+/// let handle = logger.reload_handle();
+/// // ..
+/// handle.set("my_crate=trace");
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReloadHandle {
+    filters: SharedFilters,
+}
+
+impl ReloadHandle {
+    pub(crate) fn new(filters: SharedFilters) -> Self {
+        Self { filters }
+    }
+
+    /// Replace the filters with these directives, using the same syntax as `RUST_LOG`
+    ///
+    /// See [`ReloadHandle::set_by`](#method.set_by) for the events this produces
+    pub fn set(&self, directives: &str) {
+        self.set_by(directives, "reload handle")
+    }
+
+    /// Replace the filters with these directives, unless any of them isn't valid
+    ///
+    /// See [`Filters::try_parse`](struct.Filters.html#method.try_parse)
+    pub fn try_set(&self, directives: &str) -> Result<(), crate::Error> {
+        let filters = Filters::try_parse(directives)?;
+        self.replace(filters, String::from("reload handle"));
+        Ok(())
+    }
+
+    /// Replace the filters with these directives, recording `source` as what changed them.
+    ///
+    /// This logs an `INFO` record with the `alto_logger::reload` target describing the change,
+    /// and then calls any observers added with [`ReloadHandle::on_change`](#method.on_change)
+    pub fn set_by(&self, directives: &str, source: impl Into<String>) {
+        self.replace(Filters::from_str(directives), source.into())
+    }
+
+    /// Replace the filters with these directives for `ttl`, and then revert to the previous filters
+    ///
+    /// When these overlap, the filters from before the first of them are restored once they've all expired,
+    /// and until then, the newest one that hasn't expired applies.
+    /// The revert is skipped if the filters were replaced again in the meantime.
+    /// The reverts for every handle are made by a single background thread.
+    ///
+    /// ```rust,no_run
+    /// # use alto_logger::TermLogger;
+    /// # use std::time::Duration;
+    /// # let handle = TermLogger::default().reload_handle();
+    /// // debug an issue in production, without having to remember to turn it off
+    /// handle.set_filters_for("my_crate=trace", Duration::from_secs(10 * 60));
+    /// ```
+    pub fn set_filters_for(&self, directives: &str, ttl: Duration) -> TtlHandle {
+        let filters = Filters::from_str(directives);
+        let (id, change) = {
+            let mut ttls = self.filters.ttls();
+            if ttls.generation != self.filters.generation() {
+                // the filters were replaced since the last ttl, so those are kept instead
+                ttls.live.clear();
+            }
+
+            // the filters themselves are kept, as their directives don't include e.g. whether a file is watched
+            let (change, old) = self.exchange(filters.clone(), String::from("reload handle"));
+            if ttls.live.is_empty() {
+                ttls.baseline = Some(old);
+            }
+            ttls.next_id += 1;
+            let id = ttls.next_id;
+            ttls.live.push((id, filters));
+            ttls.generation = self.filters.generation();
+            (id, change)
+        };
+        self.announce(&change);
+
+        let this = self.clone();
+        let (done, reverted) = std::sync::mpsc::channel();
+        timer::schedule(
+            Instant::now() + ttl,
+            Box::new(move || {
+                this.expire(id);
+                let _ = done.send(());
+            }),
+        );
+        TtlHandle(reverted)
+    }
+
+    /// Restore the filters from before the ttl `id`, unless a newer one is still live
+    fn expire(&self, id: u64) {
+        let change = {
+            let mut ttls = self.filters.ttls();
+            let Some(i) = ttls.live.iter().position(|(live, _)| *live == id) else {
+                return;
+            };
+            ttls.live.remove(i);
+            if ttls.generation != self.filters.generation() || i < ttls.live.len() {
+                return;
+            }
+
+            let restore = match ttls.live.last() {
+                Some((_, filters)) => filters.clone(),
+                None => match ttls.baseline.take() {
+                    Some(baseline) => baseline,
+                    None => return,
+                },
+            };
+            let (change, _) = self.exchange(restore, String::from("filter ttl"));
+            ttls.generation = self.filters.generation();
+            change
+        };
+        self.announce(&change);
+    }
+
+    /// Replace the filters with these `Filters`, e.g. a [`Preset`](enum.Preset.html)
+    ///
+    /// See [`ReloadHandle::set_by`](#method.set_by) for the events this produces
+    pub fn set_filters(&self, filters: Filters) {
+        self.replace(filters, String::from("reload handle"))
+    }
+
+    pub(crate) fn replace(&self, new: Filters, source: String) {
+        let (change, _) = self.exchange(new, source);
+        self.announce(&change);
+    }
+
+    /// Replace the filters, without announcing it, returning the change and the previous filters
+    fn exchange(&self, new: Filters, source: String) -> (FilterChange, Filters) {
+        let directives = new.to_string();
+        let old = self.filters.replace(new);
+        let change = FilterChange {
+            new: directives,
+            old: old.to_string(),
+            source,
+        };
+        (change, old)
+    }
+
+    fn announce(&self, change: &FilterChange) {
+        log::info!(
+            target: "alto_logger::reload",
+            "filters changed by {}: '{}' -> '{}'",
+            change.source,
+            change.old,
+            change.new
+        );
+        self.filters.notify(change);
+    }
+
+    /// Disable this target, and the modules below it, on top of the current filters
+    ///
+    /// This is kept when the filters are replaced, until [`ReloadHandle::unmute_target`](#method.unmute_target) is called.
+    ///
+    /// ```rust
+    /// # use alto_logger::TermLogger;
+    /// # let handle = TermLogger::default().reload_handle();
+    /// handle.mute_target("hyper::proto");
+    /// // ..
+    /// handle.unmute_target("hyper::proto");
+    /// ```
+    pub fn mute_target(&self, target: &str) {
+        if self.filters.mute(target) {
+            log::info!(target: "alto_logger::reload", "muted target: '{}'", target);
+        }
+    }
+
+    /// Enable a target disabled with [`ReloadHandle::mute_target`](#method.mute_target) again
+    pub fn unmute_target(&self, target: &str) {
+        if self.filters.unmute(target) {
+            log::info!(target: "alto_logger::reload", "unmuted target: '{}'", target);
+        }
+    }
+
+    /// Get the targets disabled with [`ReloadHandle::mute_target`](#method.mute_target)
+    pub fn muted_targets(&self) -> Vec<String> {
+        self.filters.muted()
+    }
+
+    /// Call `observer` whenever the filters are replaced through any handle for this logger
+    pub fn on_change(&self, observer: impl Fn(&FilterChange) + Send + Sync + 'static) {
+        self.filters.observe(Arc::new(observer))
+    }
+
+    /// Get the current filter directives
+    pub fn directives(&self) -> String {
+        self.filters.to_string()
+    }
+
+    /// Get a copy of the current filters, e.g. to show what is enabled
+    pub fn filters(&self) -> Filters {
+        self.filters.get()
+    }
+
+    /// Poll the `source` every `interval` on a background thread, replacing the filters when it changes.
+    ///
+    /// Errors from the source are logged as a warning, and it'll be tried again at the next interval.
+    ///
+    /// The thread keeps polling until [`PollHandle::stop`](struct.PollHandle.html#method.stop) is called.
+    pub fn poll(&self, source: impl RemoteFilterSource, interval: Duration) -> PollHandle {
+        let this = self.clone();
+        poll_while(move || Some(this.clone()), source, interval)
+    }
+}
+
+/// Poll the `source` every `interval` for the handle from `handle`, until it returns `None` or the poller is stopped
+pub(crate) fn poll_while(
+    handle: impl Fn() -> Option<ReloadHandle> + Send + 'static,
+    mut source: impl RemoteFilterSource,
+    interval: Duration,
+) -> PollHandle {
+    let stopped = Arc::new(AtomicBool::new(false));
+    let thread = std::thread::Builder::new()
+        .name(String::from("alto_logger filter poller"))
+        .spawn({
+            let stopped = Arc::clone(&stopped);
+            move || {
+                while !stopped.load(Ordering::SeqCst) {
+                    let Some(this) = handle() else { break };
+                    match source.fetch() {
+                        // the directives are compared as they'd be displayed, so e.g. a file with comments
+                        // or whitespace doesn't replace the filters every time
+                        Ok(Some(directives))
+                            if Filters::from_str(&directives).to_string() != this.directives() =>
+                        {
+                            this.set_by(&directives, "remote filter source")
+                        }
+                        Ok(..) => {}
+                        Err(err) => {
+                            log::warn!(target: "alto_logger", "cannot fetch filters: {}", err)
+                        }
+                    }
+                    drop(this);
+                    std::thread::park_timeout(interval);
+                }
+            }
+        })
+        .expect("spawn filter poller thread");
+    PollHandle { stopped, thread }
+}
+
+/// The filters set by [`ReloadHandle::set_filters_for`](struct.ReloadHandle.html#method.set_filters_for) which haven't expired
+#[derive(Default)]
+pub(crate) struct Ttls {
+    // the filters from before the oldest live ttl
+    baseline: Option<Filters>,
+    // the live ttls, oldest first
+    live: Vec<(u64, Filters)>,
+    // the generation of the filters after the last change made for a ttl
+    generation: u64,
+    next_id: u64,
+}
+
+/// The pending revert of [`ReloadHandle::set_filters_for`](struct.ReloadHandle.html#method.set_filters_for)
+#[derive(Debug)]
+pub struct TtlHandle(std::sync::mpsc::Receiver<()>);
+
+impl TtlHandle {
+    /// Wait until the filters have been reverted, or the revert was skipped
+    pub fn wait(self) {
+        let _ = self.0.recv();
+    }
+}
+
+mod timer {
+    use crate::sync::{Condvar, Mutex};
+    use std::{sync::Once, time::Instant};
+
+    type Job = Box<dyn FnOnce() + Send>;
+
+    crate::sync::static_lock! {
+        static JOBS: Mutex<Vec<(Instant, Job)>> = Mutex::new(Vec::new());
+    }
+    crate::sync::static_lock! {
+        static WAKE: Condvar = Condvar::new();
+    }
+
+    /// Run `job` at `at`, on the timer thread
+    pub(super) fn schedule(at: Instant, job: Job) {
+        static STARTED: Once = Once::new();
+        STARTED.call_once(|| {
+            std::thread::Builder::new()
+                .name(String::from("alto_logger filter ttl"))
+                .spawn(run)
+                .expect("spawn filter ttl thread");
+        });
+
+        crate::sync::lock(&JOBS).push((at, job));
+        WAKE.notify_one();
+    }
+
+    fn run() {
+        let mut jobs = crate::sync::lock(&JOBS);
+        loop {
+            let now = Instant::now();
+            if let Some(i) = jobs.iter().position(|(at, _)| *at <= now) {
+                let (_, job) = jobs.swap_remove(i);
+                drop(jobs);
+                job();
+                jobs = crate::sync::lock(&JOBS);
+                continue;
+            }
+
+            jobs = match jobs.iter().map(|(at, _)| *at).min() {
+                Some(next) => {
+                    crate::sync::wait_timeout(&WAKE, jobs, next.saturating_duration_since(now))
+                }
+                None => crate::sync::wait(&WAKE, jobs),
+            };
+        }
+    }
+}
+
+/// The thread polling a [`RemoteFilterSource`](trait.RemoteFilterSource.html), from [`ReloadHandle::poll`](struct.ReloadHandle.html#method.poll)
+///
+/// Dropping this doesn't stop the thread.
+#[derive(Debug)]
+#[must_use = "dropping this doesn't stop polling, call `PollHandle::stop` to stop it"]
+pub struct PollHandle {
+    stopped: Arc<AtomicBool>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl PollHandle {
+    /// Stop polling, and wait for the thread to finish
+    pub fn stop(self) {
+        self.cancel();
+        let _ = self.thread.join();
+    }
+
+    /// Stop polling, without waiting for the thread
+    pub(crate) fn cancel(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.thread.thread().unpark();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn stopped(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stopped)
+    }
+}
+
+/// A change of filters made through a [`ReloadHandle`](struct.ReloadHandle.html)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FilterChange {
+    /// The previous filter directives
+    pub old: String,
+    /// The new filter directives
+    pub new: String,
+    /// What changed the filters
+    pub source: String,
+}
+
+/// A source of filter directives which can be polled with [`ReloadHandle::poll`](struct.ReloadHandle.html#method.poll)
+///
+/// This can be implemented for e.g. an HTTP endpoint or a key in a configuration store.
+///
+/// It is implemented for closures returning `std::io::Result<Option<String>>`:
+/// ```rust,no_run
+/// # use alto_logger::TermLogger;
+/// # use std::time::Duration;
+/// # let handle = TermLogger::default().reload_handle();
+/// # fn fetch_from_config_store(_: &str) -> std::io::Result<String> { Ok(String::new()) }
+/// let poller = handle.poll(
+///     || fetch_from_config_store("my_service/log_level").map(Some),
+///     Duration::from_secs(30),
+/// );
+/// ```
+pub trait RemoteFilterSource: Send + 'static {
+    /// Fetch the filter directives, using the same syntax as `RUST_LOG`
+    ///
+    /// This should return `Ok(None)` if the directives haven't changed since the last fetch
+    fn fetch(&mut self) -> std::io::Result<Option<String>>;
+}
+
+impl<F> RemoteFilterSource for F
+where
+    F: FnMut() -> std::io::Result<Option<String>> + Send + 'static,
+{
+    fn fetch(&mut self) -> std::io::Result<Option<String>> {
+        self()
+    }
+}
+
+/// Reads filter directives from a file, when it has been modified
+///
+/// The directives can be separated by commas or newlines, and lines starting with `#` are ignored.
+/// A file with a `.toml` extension is read like [`Filters::from_file`](struct.Filters.html#method.from_file).
+#[derive(Debug)]
+pub struct FileSource {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl FileSource {
+    /// Read the directives from this file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+        }
+    }
+}
+
+impl RemoteFilterSource for FileSource {
+    fn fetch(&mut self) -> std::io::Result<Option<String>> {
+        let modified = std::fs::metadata(&self.path)?.modified()?;
+        if self.modified == Some(modified) {
+            return Ok(None);
+        }
+
+        let data = std::fs::read_to_string(&self.path)?;
+        self.modified.replace(modified);
+
+        match file::parse(&self.path, &data) {
+            Ok(config) => Ok(Some(config.directives)),
+            Err(line) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid filter: '{}'", line),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_source() {
+        let path = std::env::temp_dir().join(format!("alto_logger_source_{}", std::process::id()));
+        std::fs::write(&path, "# comment\ninfo\nfoo=trace,bar=off\n").unwrap();
+
+        let mut source = FileSource::new(&path);
+        assert_eq!(
+            source.fetch().unwrap().as_deref(),
+            Some("info,foo=trace,bar=off")
+        );
+        assert_eq!(source.fetch().unwrap(), None);
+
+        let handle = ReloadHandle::new(SharedFilters::default());
+        let (tx, rx) = std::sync::mpsc::channel();
+        handle.on_change(move |change| tx.send(change.clone()).unwrap());
+
+        handle.set("info,foo=trace,bar=off");
+        assert_eq!(handle.directives(), "info,foo=trace,bar=off");
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            FilterChange {
+                old: String::from("off"),
+                new: String::from("info,foo=trace,bar=off"),
+                source: String::from("reload handle"),
+            }
+        );
+
+        std::fs::remove_file(path).unwrap();
+        assert!(source.fetch().is_err());
+    }
+
+    #[test]
+    fn poll() {
+        let handle = ReloadHandle::new(SharedFilters::default());
+        let (tx, rx) = std::sync::mpsc::channel();
+        handle.on_change(move |change| tx.send(change.new.clone()).unwrap());
+
+        // this isn't how the directives are displayed, but it only changes the filters once
+        let (fetched, fetches) = std::sync::mpsc::channel();
+        let poller = handle.poll(
+            move || {
+                let _ = fetched.send(());
+                Ok(Some(String::from("foo=trace,info")))
+            },
+            Duration::from_millis(1),
+        );
+        for _ in 0..5 {
+            fetches.recv().unwrap();
+        }
+        poller.stop();
+
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), ["info,foo=trace"]);
+    }
+
+    #[test]
+    fn reentrant_observer() {
+        let handle = ReloadHandle::new(SharedFilters::default());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let this = handle.clone();
+        handle.on_change(move |change| {
+            tx.send(this.directives()).unwrap();
+            if change.new == "info" {
+                this.set("warn");
+            }
+        });
+
+        handle.set("info");
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), ["info", "warn"]);
+    }
+
+    #[test]
+    fn ttl() {
+        let handle = ReloadHandle::new(SharedFilters::default());
+        handle.set("info");
+
+        handle
+            .set_filters_for("foo=trace", Duration::from_millis(10))
+            .wait();
+        assert_eq!(handle.directives(), "info");
+
+        let ttl = handle.set_filters_for("foo=trace", Duration::from_millis(50));
+        handle.set("warn");
+        ttl.wait();
+        assert_eq!(handle.directives(), "warn");
+
+        // the previous filters are restored as they were, not re-parsed from their directives
+        let mut watched = Filters::from_str("info");
+        watched.watch = Some(std::path::PathBuf::from("filters.toml"));
+        handle.set_filters(watched.clone());
+        handle
+            .set_filters_for("foo=trace", Duration::from_millis(10))
+            .wait();
+        assert_eq!(handle.filters().watch, watched.watch);
+    }
+
+    #[test]
+    fn nested_ttl() {
+        let handle = ReloadHandle::new(SharedFilters::default());
+        handle.set("info");
+
+        let outer = handle.set_filters_for("trace", Duration::from_millis(80));
+        let inner = handle.set_filters_for("debug", Duration::from_millis(10));
+        assert_eq!(handle.directives(), "debug");
+        inner.wait();
+        assert_eq!(handle.directives(), "trace");
+        outer.wait();
+        assert_eq!(handle.directives(), "info");
+
+        // an older ttl expiring doesn't replace a newer one
+        let outer = handle.set_filters_for("trace", Duration::from_millis(10));
+        let inner = handle.set_filters_for("debug", Duration::from_millis(80));
+        outer.wait();
+        assert_eq!(handle.directives(), "debug");
+        inner.wait();
+        assert_eq!(handle.directives(), "info");
+    }
+
+    #[test]
+    fn mute() {
+        let filters = SharedFilters::new(Filters::parse("trace"));
+        let handle = ReloadHandle::new(filters.clone());
+        let enabled = |target| filters.is_target_enabled(target, log::Level::Error);
+
+        handle.mute_target("noisy::module");
+        assert!(!enabled("noisy::module"));
+        assert!(!enabled("noisy::module::inner"));
+        assert!(enabled("noisy::modules"));
+        assert!(enabled("noisy"));
+
+        handle.set("debug");
+        assert!(!enabled("noisy::module"));
+        assert_eq!(handle.muted_targets(), ["noisy::module"]);
+
+        handle.unmute_target("noisy::module");
+        assert!(enabled("noisy::module"));
+        assert!(handle.muted_targets().is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+use crate::{
+    layout::{Element, LayoutWriter, SystemClock},
+    options::{color_spec, ColorDepth, Options},
+};
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use termcolor::WriteColor;
+
+/// Renders a record with the layout shared by all of the loggers, with colors
+///
+/// Loggers that don't support colors should wrap their writer in a `termcolor::NoColor`
+pub(crate) struct Renderer<'a> {
+    options: &'a Options,
+    color_depth: ColorDepth,
+}
+
+impl<'a> Renderer<'a> {
+    pub(crate) const fn new(options: &'a Options, color_depth: ColorDepth) -> Self {
+        Self {
+            options,
+            color_depth,
+        }
+    }
+
+    pub(crate) fn render(&self, record: &log::Record<'_>, out: &mut impl WriteColor) {
+        let mut out = ColorWriter {
+            renderer: self,
+            out,
+        };
+        crate::layout::render(self.options, record, &SystemClock, &mut out)
+    }
+}
+
+/// Writes records to stderr as well, if they're at or above the `stderr_mirror` level of the options
+///
+/// Like the `TermLogger`'s, the buffer is reused for each record on a thread.
+pub(crate) struct StderrMirror {
+    // there's no writer if nothing is mirrored
+    writer: Option<termcolor::BufferWriter>,
+    color_depth: ColorDepth,
+    id: usize,
+}
+
+thread_local! {
+    // the buffer is reused for each record on a thread, its keyed by the mirror that created it
+    static MIRROR_BUFFER: RefCell<Option<(usize, termcolor::Buffer)>> = const { RefCell::new(None) };
+}
+
+impl StderrMirror {
+    pub(crate) fn new(options: &Options) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Self {
+            writer: options
+                .stderr_mirror
+                .map(|_| termcolor::BufferWriter::stderr(options.color_choice(true))),
+            color_depth: options.color_depth.resolve(),
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn write(&self, options: &Options, record: &log::Record<'_>) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+        if !mirrors(options, record.level()) {
+            return;
+        }
+
+        MIRROR_BUFFER.with(|cell| match cell.try_borrow_mut() {
+            Ok(mut cell) => {
+                let buffer = match &mut *cell {
+                    Some((id, buffer)) if *id == self.id => {
+                        buffer.clear();
+                        buffer
+                    }
+                    cell => &mut cell.insert((self.id, writer.buffer())).1,
+                };
+                mirror(options, self.color_depth, record, buffer);
+                let _ = writer.print(buffer);
+            }
+
+            // something logged while a record was being mirrored on this thread
+            Err(..) => {
+                let mut buffer = writer.buffer();
+                mirror(options, self.color_depth, record, &mut buffer);
+                let _ = writer.print(&buffer);
+            }
+        })
+    }
+}
+
+/// Whether a record at this level is at or above the `stderr_mirror` level of the options
+fn mirrors(options: &Options, level: log::Level) -> bool {
+    options.stderr_mirror.is_some_and(|mirror| level <= mirror)
+}
+
+/// Render the record to `out` if it's at or above the `stderr_mirror` level of the options, returning whether it was
+fn mirror(
+    options: &Options,
+    color_depth: ColorDepth,
+    record: &log::Record<'_>,
+    out: &mut impl WriteColor,
+) -> bool {
+    if !mirrors(options, record.level()) {
+        return false;
+    }
+
+    Renderer::new(options, color_depth).render(record, out);
+    true
+}
+
+/// Paints each element of the layout with the colors of the options
+struct ColorWriter<'a, W> {
+    renderer: &'a Renderer<'a>,
+    out: &'a mut W,
+}
+
+impl<W: WriteColor> std::fmt::Write for ColorWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.out
+            .write_all(s.as_bytes())
+            .map_err(|_| std::fmt::Error)
+    }
+}
+
+impl<W: WriteColor> LayoutWriter for ColorWriter<'_, W> {
+    fn begin(&mut self, element: Element) {
+        let color = &self.renderer.options.color;
+        let (fg, attributes) = match element {
+            Element::Level(level) => {
+                let fg = match level {
+                    log::Level::Error => color.level_error,
+                    log::Level::Warn => color.level_warn,
+                    log::Level::Info => color.level_info,
+                    log::Level::Debug => color.level_debug,
+                    log::Level::Trace => color.level_trace,
+                };
+                (fg, color.level_attributes)
+            }
+            Element::Timestamp => (color.timestamp, color.timestamp_attributes),
+            Element::Target | Element::Thread | Element::Source => {
+                (color.target, color.target_attributes)
+            }
+            Element::Continuation => (color.continuation, color.continuation_attributes),
+            Element::Span => (color.span, color.span_attributes),
+            Element::Message => (color.message, color.message_attributes),
+            Element::Panic => (color.level_error, color.level_attributes),
+        };
+        let spec = color_spec(fg, attributes, self.renderer.color_depth);
+        let _ = self.out.set_color(&spec);
+    }
+
+    fn end(&mut self) {
+        let _ = self.out.reset();
+    }
+}
+
+/// Displays a record with the same formatting as the loggers, without colors
+///
+/// This is useful for including a record in e.g. an alert or an email.
+///
+/// The trailing newline is not included.
+///
+/// ```rust
+/// # use alto_logger::{options::StyleConfig, Options, RecordDisplay};
+/// let options = Options::default().with_style(StyleConfig::SingleLine);
+/// let record = log::Record::builder()
+///     .level(log::Level::Error)
+///     .target("my_app")
+///     .args(format_args!("disk full"))
+///     .build();
+/// assert_eq!(
+///     RecordDisplay::new(&options, &record).to_string(),
+///     "ERROR [my_app] disk full"
+/// );
+/// ```
+pub struct RecordDisplay<'a> {
+    options: &'a Options,
+    record: &'a log::Record<'a>,
+}
+
+impl<'a> RecordDisplay<'a> {
+    /// Display this `record` with these `options`
+    pub const fn new(options: &'a Options, record: &'a log::Record<'a>) -> Self {
+        Self { options, record }
+    }
+}
+
+impl std::fmt::Display for RecordDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        crate::layout::render(self.options, self.record, &SystemClock, &mut out);
+        f.write_str(out.strip_suffix('\n').unwrap_or(&out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layout::split_spans,
+        options::{LevelCase, StyleConfig},
+    };
+
+    fn render(options: &Options, record: &log::Record<'_>) -> String {
+        let mut out = termcolor::NoColor::new(vec![]);
+        Renderer::new(options, ColorDepth::TrueColor).render(record, &mut out);
+        String::from_utf8(out.into_inner()).unwrap()
+    }
+
+    fn with_record<T>(level: log::Level, f: impl FnOnce(&log::Record<'_>) -> T) -> T {
+        with_target(level, "foo::bar", f)
+    }
+
+    fn with_target<T>(level: log::Level, target: &str, f: impl FnOnce(&log::Record<'_>) -> T) -> T {
+        f(&log::Record::builder()
+            .level(level)
+            .target(target)
+            .args(format_args!("hello world"))
+            .build())
+    }
+
+    #[test]
+    fn styles() {
+        let single = Options::default().with_style(StyleConfig::SingleLine);
+        let out = with_record(log::Level::Info, |r| render(&single, r));
+        assert_eq!(out, "INFO  [foo::bar] hello world\n");
+
+        let multi = Options::default().with_style(StyleConfig::MultiLine);
+        let out = with_record(log::Level::Warn, |r| render(&multi, r));
+        assert_eq!(out, "WARN  [foo::bar]\n⤷ hello world\n");
+    }
+
+    #[test]
+    fn stderr_mirror() {
+        let mirrored = |options: &Options, level| {
+            let mut out = termcolor::NoColor::new(vec![]);
+            with_record(level, |r| {
+                mirror(options, ColorDepth::TrueColor, r, &mut out)
+            });
+            String::from_utf8(out.into_inner()).unwrap()
+        };
+
+        let none = Options::default().with_style(StyleConfig::SingleLine);
+        assert_eq!(mirrored(&none, log::Level::Error), "");
+        assert!(StderrMirror::new(&none).writer.is_none());
+
+        let warn = none.clone().with_stderr_mirror(Some(log::Level::Warn));
+        assert_eq!(
+            mirrored(&warn, log::Level::Error),
+            "ERROR [foo::bar] hello world\n"
+        );
+        assert_eq!(
+            mirrored(&warn, log::Level::Warn),
+            "WARN  [foo::bar] hello world\n"
+        );
+        assert_eq!(mirrored(&warn, log::Level::Info), "");
+        assert_eq!(mirrored(&warn, log::Level::Trace), "");
+    }
+
+    #[test]
+    fn level_case() {
+        let cases = [
+            (LevelCase::Upper, "ERROR [foo::bar] hello world\n"),
+            (LevelCase::Lower, "error [foo::bar] hello world\n"),
+            (LevelCase::Title, "Error [foo::bar] hello world\n"),
+        ];
+
+        for (case, expected) in cases {
+            let options = Options::default()
+                .with_style(StyleConfig::SingleLine)
+                .with_level_case(case);
+            let out = with_record(log::Level::Error, |r| render(&options, r));
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn quoted_targets() {
+        let options = Options::default().with_style(StyleConfig::SingleLine);
+        let cases = [
+            ("foo::bar", "[foo::bar]"),
+            ("my target", r#"["my target"]"#),
+            ("foo]bar", r#"["foo]bar"]"#),
+            ("[foo", r#"["[foo"]"#),
+            ("foo\nbar", r#"["foo\nbar"]"#),
+            ("say \"hi\"", r#"["say \"hi\""]"#),
+            ("", r#"[""]"#),
+        ];
+
+        for (target, expected) in cases {
+            let out = with_target(log::Level::Info, target, |r| render(&options, r));
+            assert_eq!(out, format!("INFO  {} hello world\n", expected));
+        }
+    }
+
+    #[test]
+    fn priority_prefix() {
+        let options = Options::default().with_priority_prefix(true);
+        let out = with_record(log::Level::Warn, |r| render(&options, r));
+        assert_eq!(out, "<4>WARN  [foo::bar]\n<4>⤷ hello world\n");
+
+        let options = options.with_style(StyleConfig::SingleLine);
+        let out = render(
+            &options,
+            &log::Record::builder()
+                .level(log::Level::Debug)
+                .target("foo")
+                .args(format_args!("multiple\nlines"))
+                .build(),
+        );
+        assert_eq!(out, "<7>DEBUG [foo] multiple\n<7>lines\n");
+    }
+
+    #[test]
+    fn continuation_marker() {
+        let record = |f: &dyn Fn(&log::Record<'_>) -> String| {
+            f(&log::Record::builder()
+                .level(log::Level::Error)
+                .target("foo")
+                .args(format_args!("failed\n  at main.rs"))
+                .build())
+        };
+
+        let options = Options::default().with_continuation_marker(Some("\t"));
+        let out = record(&|r| render(&options, r));
+        assert_eq!(out, "ERROR [foo]\n\t⤷ failed\n\t  at main.rs\n");
+
+        let single = options.clone().with_style(StyleConfig::SingleLine);
+        let out = record(&|r| render(&single, r));
+        assert_eq!(out, "ERROR [foo] failed\n\t  at main.rs\n");
+
+        let journald = options.with_priority_prefix(true);
+        let out = record(&|r| render(&journald, r));
+        assert_eq!(out, "<3>ERROR [foo]\n<3>\t⤷ failed\n<3>\t  at main.rs\n");
+    }
+
+    #[test]
+    fn level_styles() {
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_level_style(log::Level::Error, StyleConfig::MultiLine);
+
+        let out = with_record(log::Level::Info, |r| render(&options, r));
+        assert_eq!(out, "INFO  [foo::bar] hello world\n");
+
+        let out = with_record(log::Level::Error, |r| render(&options, r));
+        assert_eq!(out, "ERROR [foo::bar]\n⤷ hello world\n");
+    }
+
+    #[test]
+    fn panicking_message() {
+        struct Oops;
+        impl std::fmt::Display for Oops {
+            fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                panic!("oops")
+            }
+        }
+
+        let options = Options::default().with_style(StyleConfig::SingleLine);
+        let record = |options: &Options| {
+            render(
+                options,
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target("foo")
+                    .args(format_args!("{}", Oops))
+                    .build(),
+            )
+        };
+        assert_eq!(record(&options), "INFO  [foo] <message panicked: oops>\n");
+
+        let options = options.with_repanic_in_debug(true);
+        let result = std::panic::catch_unwind(|| record(&options));
+        assert_eq!(result.is_err(), cfg!(debug_assertions));
+    }
+
+    #[test]
+    fn tracing_spans() {
+        let cases = [
+            (
+                "request{id=1}:db{table=\"users\"}: query done",
+                Some(("request{id=1}:db{table=\"users\"}", "query done")),
+            ),
+            (
+                "request{id=1}:db: query done",
+                Some(("request{id=1}:db", "query done")),
+            ),
+            ("request{id=1}:  padded", Some(("request{id=1}", " padded"))),
+            ("error: something failed", None),
+            ("request{id=1} something", None),
+            ("a message {with} braces", None),
+            ("", None),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(split_spans(message), expected, "{}", message);
+        }
+
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_tracing_spans(true);
+        let out = render(
+            &options,
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target("my_app")
+                .args(format_args!("request{{id={}}}: done", 1))
+                .build(),
+        );
+        assert_eq!(out, "INFO  [my_app] request{id=1}: done\n");
+    }
+
+    #[test]
+    fn target_rewrite() {
+        use crate::options::TargetRewrite;
+
+        let options = Options::default()
+            .with_style(StyleConfig::SingleLine)
+            .with_target_rewrite(TargetRewrite::new(&[(
+                "my_company_internal_core::",
+                "core::",
+            )]));
+        let record = log::Record::builder()
+            .level(log::Level::Debug)
+            .target("my_company_internal_core::db")
+            .args(format_args!("connected"))
+            .build();
+        assert_eq!(render(&options, &record), "DEBUG [core::db] connected\n");
+
+        let filters = crate::filters::SharedFilters::new(crate::Filters::parse("info,core=debug"));
+        assert!(options.allows(&filters, record.metadata()));
+        assert!(!Options::default().allows(&filters, record.metadata()));
+    }
+}
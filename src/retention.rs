@@ -0,0 +1,370 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+/// Removes old log files from a directory
+///
+/// Only files named like the loggers' files are considered: a name, then `_`, `-` or `.`,
+/// then a timestamp, date or index, then the extension.
+/// e.g. `app_1714571999.log`, `app-2024-05-01.log` or `app.3.log`
+///
+/// By default, any name with a `.log` extension is considered. Newer files are always kept over older files.
+///
+/// ```rust,no_run
+/// # use alto_logger::Retention;
+/// # use std::time::Duration;
+/// let removed = Retention::new("logs")
+///     .max_files(10)
+///     .max_age(Duration::from_secs(7 * 24 * 60 * 60))
+///     .apply()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Retention {
+    dir: PathBuf,
+    prefix: Option<String>,
+    max_files: Option<usize>,
+    max_age: Option<Duration>,
+    max_total_size: Option<u64>,
+    keep: Option<PathBuf>,
+}
+
+impl Retention {
+    /// Create a retention policy for this directory. By default, nothing is removed.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: None,
+            max_files: None,
+            max_age: None,
+            max_total_size: None,
+            keep: None,
+        }
+    }
+
+    /// Only consider files with this name, e.g. `app` for `app_1714571999.log` but not `application.log`
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix.replace(prefix.into());
+        self
+    }
+
+    /// Keep at most this many files, removing the oldest files first
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files.replace(max_files);
+        self
+    }
+
+    /// Remove files last modified longer ago than this
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age.replace(max_age);
+        self
+    }
+
+    /// Keep at most this many bytes of files in total, removing the oldest files first
+    ///
+    /// This can be combined with `max_files` so the directory stays within a disk quota
+    pub fn max_total_size(mut self, bytes: u64) -> Self {
+        self.max_total_size.replace(bytes);
+        self
+    }
+
+    /// Never remove this file (e.g. the file currently being written to)
+    pub fn keep(mut self, path: impl Into<PathBuf>) -> Self {
+        self.keep.replace(path.into());
+        self
+    }
+
+    pub(crate) fn has_prefix(&self) -> bool {
+        self.prefix.is_some()
+    }
+
+    /// The directory this policy applies to
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Apply this policy, returning how many files were removed
+    pub fn apply(&self) -> Result<usize, crate::Error> {
+        let now = SystemTime::now();
+        let keep = self.keep.as_ref().and_then(|p| p.canonicalize().ok());
+        let mut total = keep
+            .as_ref()
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map(|md| md.len())
+            .unwrap_or_default();
+
+        let mut files = vec![];
+        for entry in std::fs::read_dir(&self.dir).map_err(crate::Error::FileLogger)? {
+            let entry = entry.map_err(crate::Error::FileLogger)?;
+            if !self.matches(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+
+            let path = entry.path();
+            if keep.is_some() && path.canonicalize().ok() == keep {
+                continue;
+            }
+
+            match entry.metadata() {
+                Ok(md) if md.is_file() => {
+                    let modified = md.modified().unwrap_or(now);
+                    files.push((modified, md.len(), path));
+                }
+                _ => continue,
+            }
+        }
+
+        // newest first
+        files.sort_by(|(l, ..), (r, ..)| r.cmp(l));
+
+        // the kept file counts towards the total
+        let max_files = self
+            .max_files
+            .map(|max| max.saturating_sub(keep.is_some() as usize));
+
+        // once a file doesn't fit, it and every file older than it are removed
+        let mut too_big = false;
+        let mut removed = 0;
+        for (i, (modified, size, path)) in files.into_iter().enumerate() {
+            let too_many = max_files.filter(|&max| i >= max).is_some();
+            too_big |= self
+                .max_total_size
+                .filter(|&max| total + size > max)
+                .is_some();
+            let too_old = self
+                .max_age
+                .zip(now.duration_since(modified).ok())
+                .filter(|(max, age)| age > max)
+                .is_some();
+
+            if !(too_many || too_big || too_old) {
+                total += size;
+            } else if std::fs::remove_file(path).is_ok() {
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Apply this policy every `interval` on a background thread, until it's stopped
+    ///
+    /// Errors are ignored, the policy is tried again at the next interval
+    pub fn spawn(self, interval: Duration) -> RetentionHandle {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::Builder::new()
+            .name(String::from("alto_logger retention"))
+            .spawn({
+                let stopped = Arc::clone(&stopped);
+                move || {
+                    while !stopped.load(Ordering::SeqCst) {
+                        let _ = self.apply();
+                        std::thread::park_timeout(interval);
+                    }
+                }
+            })
+            .expect("spawn retention thread");
+        RetentionHandle { stopped, thread }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let Some(prefix) = self.prefix.as_deref() else {
+            // the name is everything before a separator that's followed by a stamp
+            return name.match_indices(SEPARATORS).any(|(i, _)| {
+                i > 0 && stamped(&name[i + 1..]).is_some_and(|ext| ext.starts_with(".log"))
+            });
+        };
+
+        // e.g. a prefix of `app-` already includes the separator
+        let Some(rest) = name.strip_prefix(prefix) else {
+            return false;
+        };
+        let rest = match prefix.ends_with(SEPARATORS) {
+            true => Some(rest),
+            false => rest.strip_prefix(SEPARATORS),
+        };
+        rest.and_then(stamped).is_some()
+    }
+}
+
+/// The separators between the name of a logger's file and its stamp
+const SEPARATORS: [char; 3] = ['_', '-', '.'];
+
+/// Strip the timestamp, date or index from the start of `rest`, returning the extension (if any) after it
+fn stamped(rest: &str) -> Option<&str> {
+    if !rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let ext = rest.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-');
+    (ext.is_empty() || ext.starts_with('.')).then_some(ext)
+}
+
+/// The thread applying a [`Retention`](struct.Retention.html) policy, from [`Retention::spawn`](struct.Retention.html#method.spawn)
+///
+/// Dropping this doesn't stop the thread.
+#[derive(Debug)]
+#[must_use = "dropping this doesn't stop the retention thread, call `RetentionHandle::stop` to stop it"]
+pub struct RetentionHandle {
+    stopped: Arc<AtomicBool>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl RetentionHandle {
+    /// Stop applying the policy, and wait for the thread to finish
+    pub fn stop(self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.thread.thread().unpark();
+        let _ = self.thread.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("alto_logger_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn touch(dir: &Path, name: &str, age: Duration) {
+        write(dir, name, age, 0)
+    }
+
+    fn write(dir: &Path, name: &str, age: Duration, len: usize) {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![b'.'; len]).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    fn list(dir: &Path) -> Vec<String> {
+        let mut files = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn retention() {
+        let dir = temp_dir("retention");
+        let hour = Duration::from_secs(60 * 60);
+        for i in 0..5 {
+            touch(&dir, &format!("app-{}.log", i), hour * i);
+        }
+        touch(&dir, "other-9.log", hour * 9);
+        touch(&dir, "notes.txt", hour * 9);
+
+        let removed = Retention::new(&dir)
+            .prefix("app-")
+            .max_files(3)
+            .apply()
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(
+            list(&dir),
+            [
+                "app-0.log",
+                "app-1.log",
+                "app-2.log",
+                "notes.txt",
+                "other-9.log"
+            ]
+        );
+
+        let removed = Retention::new(&dir)
+            .max_age(hour + hour / 2)
+            .keep(dir.join("app-2.log"))
+            .apply()
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(
+            list(&dir),
+            ["app-0.log", "app-1.log", "app-2.log", "notes.txt"]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn matches() {
+        let app = Retention::new("logs").prefix("app");
+        let any = Retention::new("logs");
+        let cases = [
+            ("app_1714571999.log", true, true),
+            ("app_1714571999.log.gz", true, true),
+            ("app-2024-05-01.log", true, true),
+            ("app-2024-05-01-13.log", true, true),
+            ("app.3.log", true, true),
+            ("app_1714571999", true, false),
+            ("app.log", false, false),
+            ("application.log", false, false),
+            ("application_1714571999.log", false, true),
+            ("app_v2.log", false, false),
+            ("notes-2024-05-01.txt", false, false),
+        ];
+        for (name, prefixed, default) in cases {
+            assert_eq!(app.matches(name), prefixed, "{}", name);
+            assert_eq!(any.matches(name), default, "{}", name);
+        }
+
+        // the prefix can include the separator
+        assert!(Retention::new("logs").prefix("app-").matches("app-0.log"));
+        assert!(!Retention::new("logs").prefix("app-").matches("app-v2.log"));
+    }
+
+    #[test]
+    fn spawn() {
+        let dir = temp_dir("retention_spawn");
+        touch(&dir, "app-0.log", Duration::from_secs(60 * 60));
+
+        let handle = Retention::new(&dir)
+            .max_age(Duration::from_secs(60))
+            .spawn(Duration::from_secs(60 * 60));
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while dir.join("app-0.log").exists() && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(list(&dir), <[&str; 0]>::default());
+
+        // stopping wakes the thread up, rather than waiting for the interval
+        handle.stop();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn total_size() {
+        let dir = temp_dir("total_size");
+        let hour = Duration::from_secs(60 * 60);
+        for i in 0..5 {
+            write(&dir, &format!("app-{}.log", i), hour * i, 100);
+        }
+
+        // the kept file is the oldest, but it still counts towards the total
+        let removed = Retention::new(&dir)
+            .max_total_size(250)
+            .keep(dir.join("app-4.log"))
+            .apply()
+            .unwrap();
+        assert_eq!(removed, 3);
+        assert_eq!(list(&dir), ["app-0.log", "app-4.log"]);
+
+        // a smaller file older than one that didn't fit is removed as well
+        write(&dir, "app-1.log", hour, 200);
+        write(&dir, "app-2.log", hour * 2, 10);
+        let removed = Retention::new(&dir).max_total_size(250).apply().unwrap();
+        assert_eq!(removed, 3);
+        assert_eq!(list(&dir), ["app-0.log"]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+/// Create a handle for a component, whose records are passed to the installed logger with this `scope`
+///
+/// The message of each record is prefixed with the scope, e.g. `tenant-42: connected`,
+/// and the scope is added as a `scope` key-value, so e.g. a [`ChannelLogger`](struct.ChannelLogger.html) can group them.
+///
+/// This is meant for libraries hosting multiple isolated components in one process.
+/// The handle implements `log::Log`, so it can be passed to the `log` macros (with `log` 0.4.27 or later):
+/// ```rust
+/// alto_logger::init_term_logger().unwrap();
+///
+/// let tenant = alto_logger::scoped("tenant-42");
+/// log::info!(logger: tenant, "connected");
+/// ```
+pub fn scoped(scope: impl Into<String>) -> Scoped {
+    Scoped {
+        scope: scope.into(),
+    }
+}
+
+/// A handle which adds a scope to its records. See [`scoped`](fn.scoped.html)
+#[derive(Clone, Debug)]
+pub struct Scoped {
+    scope: String,
+}
+
+impl Scoped {
+    /// Get the scope of this handle
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    fn forward(&self, record: &log::Record<'_>, logger: &dyn log::Log) {
+        let kv = ScopeSource {
+            scope: &self.scope,
+            inner: record.key_values(),
+        };
+        logger.log(
+            &record
+                .to_builder()
+                .args(format_args!("{}: {}", self.scope, record.args()))
+                .key_values(&kv)
+                .build(),
+        )
+    }
+}
+
+impl log::Log for Scoped {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        log::logger().enabled(metadata)
+    }
+
+    #[inline]
+    fn log(&self, record: &log::Record<'_>) {
+        self.forward(record, log::logger())
+    }
+
+    #[inline]
+    fn flush(&self) {
+        log::logger().flush()
+    }
+}
+
+/// The key-values of a record, with the `scope` added first
+struct ScopeSource<'a> {
+    scope: &'a str,
+    inner: &'a dyn log::kv::Source,
+}
+
+impl log::kv::Source for ScopeSource<'_> {
+    fn visit<'kvs>(
+        &'kvs self,
+        visitor: &mut dyn log::kv::VisitSource<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        visitor.visit_pair(
+            log::kv::Key::from("scope"),
+            log::kv::Value::from(self.scope),
+        )?;
+        self.inner.visit(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChannelLogger;
+
+    #[test]
+    fn scope() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let logger = ChannelLogger::records(tx);
+        logger.reload_handle().set("trace");
+
+        let kv = [("id", 1)];
+        scoped("tenant-42").forward(
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .args(format_args!("connected"))
+                .key_values(&kv)
+                .build(),
+            &logger,
+        );
+
+        let record = rx.try_recv().unwrap();
+        assert_eq!(record.message, "tenant-42: connected");
+        assert_eq!(
+            record.kv,
+            [
+                (String::from("scope"), String::from("tenant-42")),
+                (String::from("id"), String::from("1"))
+            ]
+        );
+    }
+}
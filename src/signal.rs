@@ -0,0 +1,31 @@
+use signal_hook::{
+    consts::{SIGINT, SIGTERM},
+    iterator::Signals,
+};
+
+/// Flush and close the installed logger when the process receives `SIGINT` or `SIGTERM`, before the default action
+///
+/// Without this, the last records of a file or async logger are often lost when a service is stopped.
+/// The signals are handled on a background thread, which calls [`shutdown`](fn.shutdown.html) so queued records
+/// and file footers are written, and then terminates the process like the default action would.
+///
+/// ***Note*** This requires the `signal-hook` feature, and is only available on unix
+///
+/// ```rust,no_run
+/// # use alto_logger::{AsyncLogger, FileLogger, Options, Overflow};
+/// let file = FileLogger::append(Options::default(), "service.log").unwrap();
+/// AsyncLogger::new(file, 1024, Overflow::Block).init().unwrap();
+/// alto_logger::flush_on_signal().unwrap();
+/// ```
+pub fn flush_on_signal() -> std::io::Result<std::thread::JoinHandle<()>> {
+    let mut signals = Signals::new([SIGINT, SIGTERM])?;
+    std::thread::Builder::new()
+        .name(String::from("alto_logger signals"))
+        .spawn(move || {
+            if let Some(signal) = signals.forever().next() {
+                crate::shutdown();
+                signals.handle().close();
+                let _ = signal_hook::low_level::emulate_default_handler(signal);
+            }
+        })
+}
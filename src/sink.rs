@@ -0,0 +1,96 @@
+use crate::sync::Mutex;
+use std::{io::Write, net::TcpStream, path::Path, sync::mpsc::Sender};
+
+/// Where a [`Logger`](struct.Logger.html) writes each formatted line
+///
+/// ```rust,no_run
+/// # use alto_logger::Sink;
+/// let stdout = Sink::stdout();
+/// let file = Sink::file("out.log").unwrap();
+/// let socket = Sink::tcp("127.0.0.1:5170").unwrap();
+/// ```
+pub struct Sink {
+    kind: SinkKind,
+}
+
+enum SinkKind {
+    Stdout,
+    Stderr,
+    Writer(Mutex<Box<dyn Write + Send>>),
+    Channel(Sender<String>),
+}
+
+impl Sink {
+    /// Write to stdout
+    pub const fn stdout() -> Self {
+        Self {
+            kind: SinkKind::Stdout,
+        }
+    }
+
+    /// Write to stderr
+    pub const fn stderr() -> Self {
+        Self {
+            kind: SinkKind::Stderr,
+        }
+    }
+
+    /// Append to this file, creating it if it doesn't exist
+    pub fn file(path: impl AsRef<Path>) -> Result<Self, crate::Error> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(crate::Error::FileLogger)?;
+        Ok(Self::writer(file))
+    }
+
+    /// Connect to this TCP address, e.g. a log collector
+    ///
+    /// ***Note*** The connection isn't re-established if it is lost
+    pub fn tcp(addr: &str) -> Result<Self, crate::Error> {
+        let stream = TcpStream::connect(addr).map_err(|err| crate::Error::NetworkError {
+            url: addr.to_string(),
+            reason: err.to_string(),
+        })?;
+        Ok(Self::writer(stream))
+    }
+
+    /// Send each line, without the trailing newline, over this channel
+    pub fn channel(sender: Sender<String>) -> Self {
+        Self {
+            kind: SinkKind::Channel(sender),
+        }
+    }
+
+    /// Write to this writer
+    pub fn writer(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            kind: SinkKind::Writer(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    /// Write this line, which ends with a newline
+    pub(crate) fn write(&self, line: &[u8]) -> std::io::Result<()> {
+        match &self.kind {
+            SinkKind::Stdout => std::io::stdout().lock().write_all(line),
+            SinkKind::Stderr => std::io::stderr().lock().write_all(line),
+            SinkKind::Writer(writer) => crate::sync::lock(writer).write_all(line),
+            SinkKind::Channel(sender) => {
+                let line = line.strip_suffix(b"\n").unwrap_or(line);
+                sender
+                    .send(String::from_utf8_lossy(line).into_owned())
+                    .map_err(|_| std::io::ErrorKind::BrokenPipe.into())
+            }
+        }
+    }
+
+    pub(crate) fn flush(&self) -> std::io::Result<()> {
+        match &self.kind {
+            SinkKind::Stdout => std::io::stdout().flush(),
+            SinkKind::Stderr => std::io::stderr().flush(),
+            SinkKind::Writer(writer) => crate::sync::lock(writer).flush(),
+            SinkKind::Channel(..) => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! The locks used by the loggers
+//!
+//! These are `std`'s locks, or `parking_lot`'s with the `parking_lot` feature.
+//! Either way, a lock which was held by a thread that panicked is still used.
+//!
+//! With `--cfg loom`, they're `loom`'s locks, for the models in the tests.
+//! Those can't be created in a `const`, so [`static_lock!`] creates the locks of `static`s lazily.
+
+use std::time::Duration;
+
+#[cfg(all(not(loom), not(feature = "parking_lot")))]
+pub(crate) use std::sync::{Condvar, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(all(not(loom), feature = "parking_lot"))]
+pub(crate) use parking_lot::{
+    Condvar, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+
+#[cfg(loom)]
+pub(crate) use loom::sync::{
+    Condvar, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+
+#[cfg(any(loom, not(feature = "parking_lot")))]
+use std::sync::PoisonError;
+
+/// Declare a `static` with a lock in it, which is created when it's first used with `--cfg loom`
+macro_rules! static_lock {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr;) => {
+        #[cfg(not(loom))]
+        $(#[$attr])*
+        $vis static $name: $ty = $init;
+
+        #[cfg(loom)]
+        $(#[$attr])*
+        $vis static $name: std::sync::LazyLock<$ty> = std::sync::LazyLock::new(|| $init);
+    };
+}
+pub(crate) use static_lock;
+
+/// Lock this mutex, even if a thread panicked while holding it
+///
+/// At worst, the record being written when the thread panicked was partially written,
+/// which is better than panicking on every record after it.
+#[inline]
+pub(crate) fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    #[cfg(any(loom, not(feature = "parking_lot")))]
+    return mutex.lock().unwrap_or_else(PoisonError::into_inner);
+    #[cfg(all(not(loom), feature = "parking_lot"))]
+    return mutex.lock();
+}
+
+/// Lock this `RwLock` for reading, even if a thread panicked while holding it
+#[inline]
+pub(crate) fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    #[cfg(any(loom, not(feature = "parking_lot")))]
+    return lock.read().unwrap_or_else(PoisonError::into_inner);
+    #[cfg(all(not(loom), feature = "parking_lot"))]
+    return lock.read();
+}
+
+/// Lock this `RwLock` for writing, even if a thread panicked while holding it
+#[inline]
+pub(crate) fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    #[cfg(any(loom, not(feature = "parking_lot")))]
+    return lock.write().unwrap_or_else(PoisonError::into_inner);
+    #[cfg(all(not(loom), feature = "parking_lot"))]
+    return lock.write();
+}
+
+/// Get the value of this mutex, even if a thread panicked while holding it
+#[inline]
+pub(crate) fn get_mut<T: ?Sized>(mutex: &mut Mutex<T>) -> &mut T {
+    #[cfg(any(loom, not(feature = "parking_lot")))]
+    return mutex.get_mut().unwrap_or_else(PoisonError::into_inner);
+    #[cfg(all(not(loom), feature = "parking_lot"))]
+    return mutex.get_mut();
+}
+
+/// Wait on this condvar, even if a thread panicked while holding the lock
+#[inline]
+pub(crate) fn wait<'a, T>(condvar: &Condvar, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+    #[cfg(any(loom, not(feature = "parking_lot")))]
+    return condvar.wait(guard).unwrap_or_else(PoisonError::into_inner);
+    #[cfg(all(not(loom), feature = "parking_lot"))]
+    {
+        let mut guard = guard;
+        condvar.wait(&mut guard);
+        guard
+    }
+}
+
+/// Wait on this condvar for up to `timeout`, even if a thread panicked while holding the lock
+#[inline]
+pub(crate) fn wait_timeout<'a, T>(
+    condvar: &Condvar,
+    guard: MutexGuard<'a, T>,
+    timeout: Duration,
+) -> MutexGuard<'a, T> {
+    #[cfg(any(loom, not(feature = "parking_lot")))]
+    return condvar
+        .wait_timeout(guard, timeout)
+        .unwrap_or_else(PoisonError::into_inner)
+        .0;
+    #[cfg(all(not(loom), feature = "parking_lot"))]
+    {
+        let mut guard = guard;
+        condvar.wait_for(&mut guard, timeout);
+        guard
+    }
+}
+
+/// Wait on this condvar until `condition` is false, even if a thread panicked while holding the lock
+#[inline]
+pub(crate) fn wait_while<'a, T>(
+    condvar: &Condvar,
+    guard: MutexGuard<'a, T>,
+    condition: impl FnMut(&mut T) -> bool,
+) -> MutexGuard<'a, T> {
+    #[cfg(loom)]
+    {
+        let (mut guard, mut condition) = (guard, condition);
+        while condition(&mut guard) {
+            guard = wait(condvar, guard);
+        }
+        guard
+    }
+    #[cfg(all(not(loom), not(feature = "parking_lot")))]
+    return condvar
+        .wait_while(guard, condition)
+        .unwrap_or_else(PoisonError::into_inner);
+    #[cfg(all(not(loom), feature = "parking_lot"))]
+    {
+        let mut guard = guard;
+        condvar.wait_while(&mut guard, condition);
+        guard
+    }
+}
+
+/// Wait on this condvar for up to `timeout` until `condition` is false, even if a thread panicked while holding the lock
+#[cfg(feature = "alert")]
+#[inline]
+pub(crate) fn wait_timeout_while<'a, T>(
+    condvar: &Condvar,
+    guard: MutexGuard<'a, T>,
+    timeout: Duration,
+    condition: impl FnMut(&mut T) -> bool,
+) -> MutexGuard<'a, T> {
+    #[cfg(loom)]
+    {
+        let (mut guard, mut condition) = (guard, condition);
+        if condition(&mut guard) {
+            guard = wait_timeout(condvar, guard, timeout);
+        }
+        guard
+    }
+    #[cfg(all(not(loom), not(feature = "parking_lot")))]
+    return condvar
+        .wait_timeout_while(guard, timeout, condition)
+        .unwrap_or_else(PoisonError::into_inner)
+        .0;
+    #[cfg(all(not(loom), feature = "parking_lot"))]
+    {
+        let mut guard = guard;
+        condvar.wait_while_for(&mut guard, condition, timeout);
+        guard
+    }
+}
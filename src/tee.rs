@@ -0,0 +1,86 @@
+use std::io::Write;
+use termcolor::WriteColor;
+
+/// A writer which duplicates everything written to it into two writers
+///
+/// This is usable as the writer of a [`FileLogger`](struct.FileLogger.html), e.g. to write to a file and stderr at once.
+///
+/// It also implements `termcolor::WriteColor`, by setting the colors of both writers.
+///
+/// ```rust,no_run
+/// # use alto_logger::{FileLogger, Options, TeeWriter};
+/// let file = std::fs::File::create("out.log").unwrap();
+/// let writer = TeeWriter::new(file, std::io::stderr());
+/// FileLogger::new(Options::default(), writer).init().unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct TeeWriter<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> TeeWriter<A, B> {
+    /// Create a writer which writes to both `a` and `b`
+    pub const fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+
+    /// Get the two writers back
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // both writers have to get all of the bytes, or they'd drift apart
+        self.a.write_all(buf)?;
+        self.b.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.a.flush()?;
+        self.b.flush()
+    }
+}
+
+impl<A: WriteColor, B: WriteColor> WriteColor for TeeWriter<A, B> {
+    fn supports_color(&self) -> bool {
+        self.a.supports_color() || self.b.supports_color()
+    }
+
+    fn set_color(&mut self, spec: &termcolor::ColorSpec) -> std::io::Result<()> {
+        self.a.set_color(spec)?;
+        self.b.set_color(spec)
+    }
+
+    fn reset(&mut self) -> std::io::Result<()> {
+        self.a.reset()?;
+        self.b.reset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicates() {
+        let mut tee = TeeWriter::new(
+            termcolor::Ansi::new(vec![]),
+            termcolor::NoColor::new(vec![]),
+        );
+        assert!(tee.supports_color());
+
+        tee.set_color(termcolor::ColorSpec::new().set_fg(Some(termcolor::Color::Red)))
+            .unwrap();
+        write!(tee, "hello").unwrap();
+        tee.reset().unwrap();
+        writeln!(tee, " world").unwrap();
+
+        let (a, b) = tee.into_inner();
+        assert_eq!(a.into_inner(), b"\x1b[0m\x1b[31mhello\x1b[0m world\n");
+        assert_eq!(b.into_inner(), b"hello world\n");
+    }
+}
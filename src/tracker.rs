@@ -0,0 +1,205 @@
+use crate::{sync::RwLock, OwnedRecord};
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+// log::Level is 1 (Error) through 5 (Trace), so a lower value is worse
+static WORST: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+// indexed from Error to Trace
+static LEVELS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+
+// targets are only counted once something asked for them, see `track_targets`
+static TRACK_TARGETS: AtomicBool = AtomicBool::new(false);
+
+crate::sync::static_lock! {
+    static TARGETS: RwLock<Option<HashMap<String, AtomicU64>>> = RwLock::new(None);
+}
+
+// records for targets seen after `MAX_TARGETS` were already being counted
+static OTHER_TARGETS: AtomicU64 = AtomicU64::new(0);
+
+/// How many of the busiest targets are included in the message of a stats record
+const TOP_TARGETS: usize = 5;
+
+/// How many targets are counted separately, records for any more are counted as `other`
+const MAX_TARGETS: usize = 256;
+
+#[inline]
+pub(crate) fn record(metadata: &log::Metadata<'_>) {
+    let level = metadata.level();
+    WORST.fetch_min(level as usize, Ordering::Relaxed);
+    LEVELS[level as usize - 1].fetch_add(1, Ordering::Relaxed);
+
+    if TRACK_TARGETS.load(Ordering::Relaxed) {
+        record_target(metadata.target())
+    }
+}
+
+fn record_target(target: &str) {
+    if let Some(targets) = &*crate::sync::read(&TARGETS) {
+        match targets.get(target) {
+            Some(count) => {
+                count.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            None if targets.len() >= MAX_TARGETS => {
+                OTHER_TARGETS.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            None => {}
+        }
+    }
+
+    let mut targets = crate::sync::write(&TARGETS);
+    let targets = targets.get_or_insert_with(HashMap::new);
+    match targets.get(target) {
+        Some(count) => count.fetch_add(1, Ordering::Relaxed),
+        None if targets.len() >= MAX_TARGETS => OTHER_TARGETS.fetch_add(1, Ordering::Relaxed),
+        None => {
+            targets.insert(target.to_string(), AtomicU64::new(1));
+            0
+        }
+    };
+}
+
+/// Start counting records per target, for the busiest targets in a [`stats_record`](fn.stats_record.html)
+///
+/// Counting the targets costs a lookup for every record, so it is off until this is called.
+/// [`stats_record`](fn.stats_record.html) and [`emit_stats_every`](fn.emit_stats_every.html) call this,
+/// so the targets logged before the first of those are called aren't counted.
+///
+/// Up to 256 targets are counted separately, records for any more targets are counted as `other`.
+pub fn track_targets() {
+    TRACK_TARGETS.store(true, Ordering::Relaxed)
+}
+
+/// Get a record summarizing how many records have been logged by this crate's loggers, per level and per target
+///
+/// The record is logged at `INFO` with the target `alto_logger::stats`. Its message has the count for each level
+/// and the busiest targets, and its key-values have the count for each level.
+///
+/// Only records that passed the filters are counted, once by each logger that wrote them.
+/// Targets are only counted after [`track_targets`](fn.track_targets.html), which this calls.
+///
+/// ```rust
+/// alto_logger::init_term_logger().unwrap();
+/// alto_logger::track_targets();
+/// log::warn!("disk is almost full");
+///
+/// let stats = alto_logger::stats_record();
+/// stats.with_record(|record| log::logger().log(record));
+/// ```
+pub fn stats_record() -> OwnedRecord {
+    use log::Level::*;
+    track_targets();
+
+    let levels = [Error, Warn, Info, Debug, Trace]
+        .into_iter()
+        .zip(&LEVELS)
+        .map(|(level, count)| {
+            let level = level.as_str().to_ascii_lowercase();
+            (level, count.load(Ordering::Relaxed).to_string())
+        })
+        .collect::<Vec<_>>();
+
+    let mut targets = crate::sync::read(&TARGETS)
+        .iter()
+        .flatten()
+        .map(|(target, count)| (count.load(Ordering::Relaxed), target.clone()))
+        .collect::<Vec<_>>();
+    match OTHER_TARGETS.load(Ordering::Relaxed) {
+        0 => {}
+        other => targets.push((other, String::from("other"))),
+    }
+    targets.sort_by(|(a, left), (b, right)| b.cmp(a).then_with(|| left.cmp(right)));
+
+    let mut message = levels
+        .iter()
+        .map(|(level, count)| format!("{}={}", level, count))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !targets.is_empty() {
+        let top = targets
+            .iter()
+            .take(TOP_TARGETS)
+            .map(|(count, target)| format!("{}={}", target, count))
+            .collect::<Vec<_>>();
+        message = format!("{}; busiest targets: {}", message, top.join(" "));
+    }
+
+    OwnedRecord {
+        level: log::Level::Info,
+        target: String::from("alto_logger::stats"),
+        module_path: None,
+        file: None,
+        line: None,
+        timestamp: SystemTime::now(),
+        thread: None,
+        message: format!("records logged: {}", message),
+        kv: levels,
+    }
+}
+
+/// Log a [`stats_record`](fn.stats_record.html) every `interval`, through the installed logger
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// alto_logger::init_term_logger().unwrap();
+/// alto_logger::emit_stats_every(Duration::from_secs(5 * 60));
+/// ```
+pub fn emit_stats_every(interval: Duration) -> std::thread::JoinHandle<()> {
+    track_targets();
+    std::thread::Builder::new()
+        .name(String::from("alto_logger stats"))
+        .spawn(move || loop {
+            std::thread::sleep(interval);
+            stats_record().with_record(|record| log::logger().log(record));
+        })
+        .expect("spawn stats thread")
+}
+
+/// Get the most severe level that has been logged by this crate's loggers, if anything has been logged
+///
+/// Only records that passed the filters are counted.
+///
+/// ```rust,no_run
+/// fn main() -> std::process::ExitCode {
+///     alto_logger::init_term_logger().unwrap();
+///     // ...
+///     alto_logger::exit_code()
+/// }
+/// ```
+pub fn worst_level_seen() -> Option<log::Level> {
+    use log::Level::*;
+    [Error, Warn, Info, Debug, Trace]
+        .into_iter()
+        .find(|&level| level as usize == WORST.load(Ordering::Relaxed))
+}
+
+/// Get an exit code for the process: `FAILURE` if any `ERROR` records were logged, otherwise `SUCCESS`
+///
+/// See [`worst_level_seen`](fn.worst_level_seen.html)
+pub fn exit_code() -> std::process::ExitCode {
+    match worst_level_seen() {
+        Some(log::Level::Error) => std::process::ExitCode::FAILURE,
+        _ => std::process::ExitCode::SUCCESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_targets() {
+        for i in 0..MAX_TARGETS + 10 {
+            record_target(&format!("tracker::tests::{}", i));
+        }
+        let len = crate::sync::read(&TARGETS).as_ref().map_or(0, HashMap::len);
+        assert_eq!(len, MAX_TARGETS);
+        assert!(OTHER_TARGETS.load(Ordering::Relaxed) >= 10);
+    }
+}